@@ -1,13 +1,13 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod transaction;
 
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::Utf8Error;
 
-use anyhow::Context;
 use anyhow::Result;
 pub use parser::Hunk;
 pub use parser::ParseError;
@@ -36,6 +36,14 @@ pub enum ApplyPatchError {
     /// Error that occurs while computing replacements when applying patch chunks
     #[error("{0}")]
     ComputeReplacements(String),
+    /// Hunk `hunk` (1-based) could not be applied. Patches are applied
+    /// atomically, so no file has been changed.
+    #[error("Failed to apply hunk {hunk} ({}): {message}. No files were changed.", .path.display())]
+    HunkFailed {
+        hunk: usize,
+        path: PathBuf,
+        message: String,
+    },
 }
 
 impl From<std::io::Error> for ApplyPatchError {
@@ -388,16 +396,8 @@ pub fn apply_hunks(
             Ok(())
         }
         Err(err) => {
-            let msg = err.to_string();
-            writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
-            if let Some(io) = err.downcast_ref::<std::io::Error>() {
-                Err(ApplyPatchError::from(io))
-            } else {
-                Err(ApplyPatchError::IoError(IoError {
-                    context: msg,
-                    source: std::io::Error::other(err),
-                }))
-            }
+            writeln!(stderr, "{err}").map_err(ApplyPatchError::from)?;
+            Err(err)
         }
     }
 }
@@ -412,67 +412,19 @@ pub struct AffectedPaths {
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
+/// Either every hunk is applied or, if any of them fails, no file is changed.
+fn apply_hunks_to_files(hunks: &[Hunk]) -> Result<AffectedPaths, ApplyPatchError> {
     if hunks.is_empty() {
-        anyhow::bail!("No files were modified.");
+        return Err(ApplyPatchError::IoError(IoError {
+            context: "No files were modified".to_string(),
+            source: std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "patch contains no hunks",
+            ),
+        }));
     }
 
-    let mut added: Vec<PathBuf> = Vec::new();
-    let mut modified: Vec<PathBuf> = Vec::new();
-    let mut deleted: Vec<PathBuf> = Vec::new();
-    for hunk in hunks {
-        match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
-                added.push(path.clone());
-            }
-            Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
-                deleted.push(path.clone());
-            }
-            Hunk::UpdateFile {
-                path,
-                move_path,
-                chunks,
-            } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
-                if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
-                    }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
-                    modified.push(dest.clone());
-                } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
-                    modified.push(path.clone());
-                }
-            }
-        }
-    }
-    Ok(AffectedPaths {
-        added,
-        modified,
-        deleted,
-    })
+    transaction::apply_hunks_atomically(hunks)
 }
 
 struct AppliedPatch {
@@ -496,6 +448,20 @@ fn derive_new_contents_from_chunks(
         }
     };
 
+    let new_contents = derive_new_contents_from_text(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
+
+/// Apply the chunks to `original_contents`, the current contents of the file
+/// at `path`, and return the new contents.
+fn derive_new_contents_from_text(
+    original_contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents
         .split('\n')
         .map(|s| s.to_string())
@@ -513,11 +479,7 @@ fn derive_new_contents_from_chunks(
     if !new_lines.last().is_some_and(|s| s.is_empty()) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -1335,4 +1297,183 @@ g
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_failed_hunk_leaves_all_files_untouched() {
+        let dir = tempdir().unwrap();
+        let added = dir.path().join("nested/added.txt");
+        let updated = dir.path().join("updated.txt");
+        let stale = dir.path().join("stale.txt");
+        fs::write(&updated, "before\n").unwrap();
+        fs::write(&stale, "actual\n").unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Add File: {}\n+new\n*** Update File: {}\n@@\n-before\n+after\n*** Update File: {}\n@@\n-expected\n+replacement",
+            added.display(),
+            updated.display(),
+            stale.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert_eq!(
+            result,
+            Err(ApplyPatchError::HunkFailed {
+                hunk: 3,
+                path: stale.clone(),
+                message: format!(
+                    "Failed to find expected lines [\"expected\"] in {}",
+                    stale.display()
+                ),
+            })
+        );
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            format!(
+                "Failed to apply hunk 3 ({}): Failed to find expected lines [\"expected\"] in {}. No files were changed.\n",
+                stale.display(),
+                stale.display()
+            )
+        );
+        assert!(stdout.is_empty());
+        assert!(!dir.path().join("nested").exists());
+        assert_eq!(fs::read_to_string(&updated).unwrap(), "before\n");
+        assert_eq!(fs::read_to_string(&stale).unwrap(), "actual\n");
+    }
+
+    #[test]
+    fn test_commit_failure_rolls_back_applied_hunks() {
+        let dir = tempdir().unwrap();
+        let updated = dir.path().join("updated.txt");
+        let deleted = dir.path().join("deleted.txt");
+        let added = dir.path().join("nested/added.txt");
+        // A non-empty directory cannot be replaced by a file, so committing
+        // the last hunk fails after the others have been applied.
+        let occupied = dir.path().join("occupied");
+        fs::write(&updated, "before\n").unwrap();
+        fs::write(&deleted, "keep me\n").unwrap();
+        fs::create_dir(&occupied).unwrap();
+        fs::write(occupied.join("inner.txt"), "inner\n").unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after\n*** Delete File: {}\n*** Add File: {}\n+new\n*** Add File: {}\n+clobber",
+            updated.display(),
+            deleted.display(),
+            added.display(),
+            occupied.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        let Err(ApplyPatchError::HunkFailed { hunk, path, .. }) = result else {
+            panic!("expected a failed hunk, got {result:?}");
+        };
+        assert_eq!((hunk, path), (4, occupied.clone()));
+        assert_eq!(fs::read_to_string(&updated).unwrap(), "before\n");
+        assert_eq!(fs::read_to_string(&deleted).unwrap(), "keep me\n");
+        assert!(!dir.path().join("nested").exists());
+        assert_eq!(
+            fs::read_to_string(occupied.join("inner.txt")).unwrap(),
+            "inner\n"
+        );
+        // No staged files or backups are left behind.
+        let mut entries: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec!["deleted.txt", "occupied", "updated.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_through_symlink_keeps_the_link() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&target, "before\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after",
+            link.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_link(&link).unwrap(), target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "after\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_keeps_the_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo before\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-echo before\n+echo after",
+            script.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        let mode = fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "echo after\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_keeps_hard_links() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("original.txt");
+        let link = dir.path().join("hard_link.txt");
+        fs::write(&path, "before\n").unwrap();
+        fs::hard_link(&path, &link).unwrap();
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-before\n+after",
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+        assert_eq!(fs::read_to_string(&link).unwrap(), "after\n");
+        // No staged files or backups are left behind.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_later_hunks_see_earlier_hunks_of_the_same_patch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("twice.txt");
+
+        let patch = wrap_patch(&format!(
+            "*** Add File: {}\n+one\n*** Update File: {}\n@@\n-one\n+two",
+            path.display(),
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two\n");
+    }
 }
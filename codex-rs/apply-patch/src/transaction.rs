@@ -0,0 +1,447 @@
+//! Applies parsed hunks to the filesystem as a single transaction.
+//!
+//! The patch is applied in three phases so that a failure leaves the tree
+//! exactly as it was:
+//!
+//! 1. **Verify**: every hunk is resolved against the current file contents
+//!    (and the results of earlier hunks) in memory. Nothing is written.
+//! 2. **Stage**: the final contents of every written file are staged to a
+//!    temporary file in the destination directory.
+//! 3. **Commit**: existing files are moved aside to backups and the staged
+//!    files are renamed into place. If any step fails, the changes committed
+//!    so far are undone by restoring the backups.
+//!
+//! Writing through a symlink replaces the file it points to, and the staged
+//! file gets the permissions and, where possible, the owner of the file it
+//! replaces. A file with other hard links is overwritten in place instead of
+//! renamed over, so that the links keep sharing its contents.
+
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tempfile::NamedTempFile;
+use tempfile::TempPath;
+
+use crate::AffectedPaths;
+use crate::ApplyPatchError;
+use crate::IoError;
+use crate::derive_new_contents_from_text;
+use crate::parser::Hunk;
+
+/// What a path should look like once the patch has been applied.
+enum FinalState {
+    Write(String),
+    Delete,
+}
+
+struct PlannedChange {
+    /// 1-based index of the last hunk that touched `path`.
+    hunk: usize,
+    path: PathBuf,
+    state: FinalState,
+}
+
+/// The new contents of a file, staged next to the file they replace.
+struct StagedFile {
+    /// The file written: the target of `path` if it is a symlink.
+    target: PathBuf,
+    file: NamedTempFile,
+    /// Set when `target` has other hard links, which renaming over it would
+    /// detach from it.
+    in_place: bool,
+}
+
+/// A change that has been committed and how to undo it.
+enum JournalEntry {
+    /// `path` did not exist before the patch.
+    Created { path: PathBuf },
+    /// The original contents of `path` were moved to `backup`.
+    Replaced { path: PathBuf, backup: TempPath },
+    /// The original contents of `path` were copied to `backup` before `path`
+    /// was overwritten in place.
+    Overwritten { path: PathBuf, backup: TempPath },
+}
+
+/// Apply `hunks` all-or-nothing, returning which files were added, modified,
+/// or deleted.
+pub(crate) fn apply_hunks_atomically(hunks: &[Hunk]) -> Result<AffectedPaths, ApplyPatchError> {
+    let (changes, affected) = plan(hunks)?;
+
+    let mut created_dirs = Vec::new();
+    let staged = match stage(&changes, &mut created_dirs) {
+        Ok(staged) => staged,
+        Err(err) => {
+            remove_created_dirs(&created_dirs);
+            return Err(err);
+        }
+    };
+
+    let mut journal = Vec::new();
+    if let Err(err) = commit(&changes, staged, &mut journal) {
+        return match roll_back(journal) {
+            Ok(()) => {
+                remove_created_dirs(&created_dirs);
+                Err(err)
+            }
+            Err(rollback_err) => {
+                let context = match err {
+                    ApplyPatchError::HunkFailed {
+                        hunk,
+                        path,
+                        message,
+                    } => format!(
+                        "Failed to apply hunk {hunk} ({}): {message}; rolling back also failed and files may be partially patched",
+                        path.display()
+                    ),
+                    err => format!("{err}; rolling back also failed"),
+                };
+                Err(ApplyPatchError::IoError(IoError {
+                    context,
+                    source: rollback_err,
+                }))
+            }
+        };
+    }
+
+    // Dropping the journal deletes the backups of the replaced files.
+    drop(journal);
+    Ok(affected)
+}
+
+/// Resolve every hunk in memory, in order, so that later hunks see the
+/// results of earlier ones.
+fn plan(hunks: &[Hunk]) -> Result<(Vec<PlannedChange>, AffectedPaths), ApplyPatchError> {
+    let mut changes: Vec<PlannedChange> = Vec::new();
+    let mut affected = AffectedPaths {
+        added: Vec::new(),
+        modified: Vec::new(),
+        deleted: Vec::new(),
+    };
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let hunk_number = index + 1;
+        let fail = |path: &Path, message: String| ApplyPatchError::HunkFailed {
+            hunk: hunk_number,
+            path: path.to_path_buf(),
+            message,
+        };
+
+        match hunk {
+            Hunk::AddFile { path, contents } => {
+                if planned_state(&changes, path).is_none() && is_read_only(path) {
+                    return Err(fail(path, "file is read-only".to_string()));
+                }
+                set_state(
+                    &mut changes,
+                    hunk_number,
+                    path,
+                    FinalState::Write(contents.clone()),
+                );
+                affected.added.push(path.clone());
+            }
+            Hunk::DeleteFile { path } => {
+                match planned_state(&changes, path) {
+                    Some(FinalState::Write(_)) => {}
+                    Some(FinalState::Delete) => {
+                        return Err(fail(
+                            path,
+                            "file was deleted by an earlier hunk".to_string(),
+                        ));
+                    }
+                    None => match std::fs::metadata(path) {
+                        Ok(metadata) if metadata.is_file() => {}
+                        Ok(_) => return Err(fail(path, "not a file".to_string())),
+                        Err(err) => {
+                            return Err(fail(path, format!("Failed to delete file: {err}")));
+                        }
+                    },
+                }
+                set_state(&mut changes, hunk_number, path, FinalState::Delete);
+                affected.deleted.push(path.clone());
+            }
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                let original_contents = match planned_state(&changes, path) {
+                    Some(FinalState::Write(contents)) => contents.clone(),
+                    Some(FinalState::Delete) => {
+                        return Err(fail(
+                            path,
+                            "file was deleted by an earlier hunk".to_string(),
+                        ));
+                    }
+                    None => std::fs::read_to_string(path).map_err(|err| {
+                        fail(path, format!("Failed to read file to update: {err}"))
+                    })?,
+                };
+                let new_contents = derive_new_contents_from_text(&original_contents, path, chunks)
+                    .map_err(|err| fail(path, err.to_string()))?;
+
+                match move_path {
+                    Some(dest) if dest != path => {
+                        if planned_state(&changes, dest).is_none() && is_read_only(dest) {
+                            return Err(fail(dest, "file is read-only".to_string()));
+                        }
+                        set_state(
+                            &mut changes,
+                            hunk_number,
+                            dest,
+                            FinalState::Write(new_contents),
+                        );
+                        set_state(&mut changes, hunk_number, path, FinalState::Delete);
+                        affected.modified.push(dest.clone());
+                    }
+                    _ => {
+                        if planned_state(&changes, path).is_none() && is_read_only(path) {
+                            return Err(fail(path, "file is read-only".to_string()));
+                        }
+                        set_state(
+                            &mut changes,
+                            hunk_number,
+                            path,
+                            FinalState::Write(new_contents),
+                        );
+                        affected.modified.push(path.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((changes, affected))
+}
+
+fn planned_state<'a>(changes: &'a [PlannedChange], path: &Path) -> Option<&'a FinalState> {
+    changes
+        .iter()
+        .find(|change| change.path == path)
+        .map(|change| &change.state)
+}
+
+fn set_state(changes: &mut Vec<PlannedChange>, hunk: usize, path: &Path, state: FinalState) {
+    match changes.iter_mut().find(|change| change.path == path) {
+        Some(change) => {
+            change.hunk = hunk;
+            change.state = state;
+        }
+        None => changes.push(PlannedChange {
+            hunk,
+            path: path.to_path_buf(),
+            state,
+        }),
+    }
+}
+
+/// Writing in place would fail for a read-only file; renaming over it would
+/// not, so check explicitly to keep the previous behavior.
+fn is_read_only(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly())
+}
+
+/// Write the new contents of every file to a temporary file next to it. The
+/// returned vector is parallel to `changes`. Directories that had to be
+/// created are recorded in `created_dirs`, outermost first.
+fn stage(
+    changes: &[PlannedChange],
+    created_dirs: &mut Vec<PathBuf>,
+) -> Result<Vec<Option<StagedFile>>, ApplyPatchError> {
+    changes
+        .iter()
+        .map(|change| {
+            let FinalState::Write(contents) = &change.state else {
+                return Ok(None);
+            };
+            stage_file(&change.path, contents, created_dirs)
+                .map(Some)
+                .map_err(|err| ApplyPatchError::HunkFailed {
+                    hunk: change.hunk,
+                    path: change.path.clone(),
+                    message: format!("Failed to write file: {err}"),
+                })
+        })
+        .collect()
+}
+
+fn stage_file(
+    path: &Path,
+    contents: &str,
+    created_dirs: &mut Vec<PathBuf>,
+) -> std::io::Result<StagedFile> {
+    let target = write_target(path);
+    let parent = parent_dir(&target);
+    let missing_dirs: Vec<PathBuf> = parent
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    std::fs::create_dir_all(parent)?;
+    created_dirs.extend(missing_dirs.into_iter().rev());
+
+    let mut file = NamedTempFile::new_in(parent)?;
+    file.write_all(contents.as_bytes())?;
+    let metadata = std::fs::metadata(&target).ok();
+    if let Some(metadata) = &metadata {
+        file.as_file().set_permissions(metadata.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Only root may give a file away, so keeping the owner is best
+            // effort; the group is kept where the user belongs to it.
+            let _ = std::os::unix::fs::fchown(
+                file.as_file(),
+                Some(metadata.uid()),
+                Some(metadata.gid()),
+            );
+        }
+    }
+    Ok(StagedFile {
+        target,
+        file,
+        in_place: metadata.as_ref().is_some_and(has_other_links),
+    })
+}
+
+#[cfg(unix)]
+fn has_other_links(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() > 1
+}
+
+#[cfg(not(unix))]
+fn has_other_links(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// The file that writing to `path` changes: the target of a symlink, so that
+/// the link is kept. A dangling symlink is replaced by a regular file.
+fn write_target(path: &Path) -> PathBuf {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Move the original files aside and the staged files into place, recording
+/// every step in `journal` so that it can be undone.
+fn commit(
+    changes: &[PlannedChange],
+    staged: Vec<Option<StagedFile>>,
+    journal: &mut Vec<JournalEntry>,
+) -> Result<(), ApplyPatchError> {
+    for (change, staged) in changes.iter().zip(staged) {
+        let fail = |action: &str, err: std::io::Error| ApplyPatchError::HunkFailed {
+            hunk: change.hunk,
+            path: change.path.clone(),
+            message: format!("Failed to {action} file: {err}"),
+        };
+        let action = match change.state {
+            FinalState::Write(_) => "write",
+            FinalState::Delete => "delete",
+        };
+        let path = staged
+            .as_ref()
+            .map_or(&change.path, |staged| &staged.target);
+
+        if let Some(StagedFile {
+            target,
+            file,
+            in_place: true,
+        }) = &staged
+        {
+            let backup = backup_next_to(target).map_err(|err| fail(action, err))?;
+            std::fs::copy(target, &backup).map_err(|err| fail(action, err))?;
+            journal.push(JournalEntry::Overwritten {
+                path: target.clone(),
+                backup,
+            });
+            std::fs::copy(file.path(), target).map_err(|err| fail(action, err))?;
+            continue;
+        }
+
+        let existed = std::fs::symlink_metadata(path).is_ok();
+        if existed {
+            let backup = backup_next_to(path).map_err(|err| fail(action, err))?;
+            std::fs::rename(path, &backup).map_err(|err| fail(action, err))?;
+            journal.push(JournalEntry::Replaced {
+                path: path.clone(),
+                backup,
+            });
+        }
+
+        if let Some(staged) = staged {
+            staged
+                .file
+                .persist(&staged.target)
+                .map_err(|err| fail(action, err.error))?;
+            if !existed {
+                journal.push(JournalEntry::Created {
+                    path: staged.target,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An empty temporary file in the directory of `path` to keep its original
+/// contents in.
+fn backup_next_to(path: &Path) -> std::io::Result<TempPath> {
+    Ok(tempfile::Builder::new()
+        .prefix(".apply_patch_backup")
+        .tempfile_in(parent_dir(path))?
+        .into_temp_path())
+}
+
+/// Undo the committed changes in reverse order.
+fn roll_back(journal: Vec<JournalEntry>) -> std::io::Result<()> {
+    let mut result = Ok(());
+    for entry in journal.into_iter().rev() {
+        let restored = match entry {
+            JournalEntry::Created { path } => std::fs::remove_file(path),
+            JournalEntry::Replaced { path, backup } => match std::fs::rename(&backup, &path) {
+                Ok(()) => Ok(()),
+                Err(err) => Err(keep_backup(&path, backup, err)),
+            },
+            JournalEntry::Overwritten { path, backup } => match std::fs::copy(&backup, &path) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(keep_backup(&path, backup, err)),
+            },
+        };
+        if result.is_ok() {
+            result = restored;
+        }
+    }
+    result
+}
+
+/// Keep the backup of `path`, which could not be restored, so that the
+/// original contents survive.
+fn keep_backup(path: &Path, backup: TempPath, err: std::io::Error) -> std::io::Error {
+    let message = format!(
+        "failed to restore {} from {}: {err}",
+        path.display(),
+        backup.display()
+    );
+    let _ = backup.keep();
+    std::io::Error::new(err.kind(), message)
+}
+
+/// Remove directories created while staging, innermost first. Directories
+/// that are not empty (or cannot be removed) are left alone.
+fn remove_created_dirs(created_dirs: &[PathBuf]) {
+    for dir in created_dirs.iter().rev() {
+        let _ = std::fs::remove_dir(dir);
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
@@ -0,0 +1,267 @@
+use std::path::PathBuf;
+
+use codex_common::CliConfigOverrides;
+use codex_core::audit::AUDIT_FILENAME;
+use codex_core::audit::ApprovalSubject;
+use codex_core::audit::AuditEvent;
+use codex_core::audit::AuditRecord;
+use codex_core::audit::AuditVerification;
+use codex_core::audit::read_audit_log;
+use codex_core::audit::verify_audit_log;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+
+use crate::login::load_config_or_exit;
+
+pub fn run_audit_verify(cli_config_overrides: CliConfigOverrides, file: Option<PathBuf>) -> ! {
+    let path = resolve_audit_log_path(cli_config_overrides, file);
+    match verify_audit_log(&path) {
+        Ok(AuditVerification::Intact {
+            records,
+            head_checked,
+        }) => {
+            println!("{}: OK ({records} records)", path.display());
+            if !head_checked {
+                eprintln!(
+                    "warning: {}.head is missing, so records removed from the end of the log cannot be detected",
+                    path.display()
+                );
+            }
+            std::process::exit(0);
+        }
+        Ok(AuditVerification::Broken { line, reason }) => {
+            eprintln!("{}:{line}: {reason}", path.display());
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn run_audit_show(
+    cli_config_overrides: CliConfigOverrides,
+    file: Option<PathBuf>,
+    session: Option<String>,
+) -> ! {
+    let path = resolve_audit_log_path(cli_config_overrides, file);
+    let records = match read_audit_log(&path) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let session = session.map(|s| s.trim().to_ascii_lowercase());
+    for record in records.iter().filter(|record| {
+        session
+            .as_deref()
+            .is_none_or(|prefix| record.session_id.to_string().starts_with(prefix))
+    }) {
+        println!("{}", format_record(record));
+    }
+    std::process::exit(0);
+}
+
+/// The log given on the command line, else the configured one.
+fn resolve_audit_log_path(
+    cli_config_overrides: CliConfigOverrides,
+    file: Option<PathBuf>,
+) -> PathBuf {
+    if let Some(file) = file {
+        return file;
+    }
+    let config = load_config_or_exit(cli_config_overrides);
+    config
+        .audit_log
+        .unwrap_or_else(|| config.codex_home.join(AUDIT_FILENAME))
+}
+
+fn format_record(record: &AuditRecord) -> String {
+    let session: String = record.session_id.to_string().chars().take(8).collect();
+    format!(
+        "{:>5}  {}  {session}  {}",
+        record.seq,
+        record.timestamp,
+        describe_event(&record.event)
+    )
+}
+
+fn describe_event(event: &AuditEvent) -> String {
+    match event {
+        AuditEvent::SessionStarted {
+            model,
+            cwd,
+            approval_policy,
+            sandbox_policy,
+        } => format!(
+            "session started: model={model} approval={approval_policy} sandbox={} cwd={}",
+            sandbox_label(sandbox_policy),
+            cwd.display()
+        ),
+        AuditEvent::ConfigOverride {
+            cwd,
+            approval_policy,
+            sandbox_policy,
+            model,
+            effort,
+            summary,
+        } => {
+            let mut changes = Vec::new();
+            if let Some(model) = model {
+                changes.push(format!("model={model}"));
+            }
+            if let Some(effort) = effort {
+                changes.push(format!("effort={effort}"));
+            }
+            if let Some(summary) = summary {
+                changes.push(format!("summary={summary}"));
+            }
+            if let Some(approval_policy) = approval_policy {
+                changes.push(format!("approval={approval_policy}"));
+            }
+            if let Some(sandbox_policy) = sandbox_policy {
+                changes.push(format!("sandbox={}", sandbox_label(sandbox_policy)));
+            }
+            if let Some(cwd) = cwd {
+                changes.push(format!("cwd={}", cwd.display()));
+            }
+            format!("config override: {}", changes.join(" "))
+        }
+        AuditEvent::ExecCommand {
+            call_id,
+            command,
+            cwd,
+            exit_code,
+            duration_ms,
+        } => format!(
+            "exec {call_id}: {} (in {}) exited {exit_code} after {duration_ms}ms",
+            command.join(" "),
+            cwd.display()
+        ),
+        AuditEvent::ExecSessionCommand { call_id, cmd } => {
+            format!("exec session {call_id}: {cmd}")
+        }
+        AuditEvent::PatchApplied {
+            call_id,
+            paths,
+            success,
+        } => format!(
+            "patch {call_id} {}: {}",
+            if *success { "applied" } else { "failed" },
+            display_paths(paths)
+        ),
+        AuditEvent::ApprovalDecision {
+            id,
+            call_id,
+            subject,
+            decision,
+        } => {
+            let subject = match subject {
+                ApprovalSubject::Exec { command, cwd } => {
+                    format!("command {} (in {})", command.join(" "), cwd.display())
+                }
+                ApprovalSubject::Patch { paths } => format!("patch {}", display_paths(paths)),
+                ApprovalSubject::McpTool {
+                    server,
+                    tool,
+                    arguments,
+                } => format!(
+                    "MCP tool call {server}/{tool}{}",
+                    display_arguments(arguments.as_ref())
+                ),
+            };
+            let decision = match decision {
+                ReviewDecision::Approved => "approved",
+                ReviewDecision::ApprovedForSession => "approved for session",
+                ReviewDecision::ApprovedPrefixForSession => "approved by prefix for session",
+                ReviewDecision::ApprovedProgramInDirectory => "approved program in directory",
+                ReviewDecision::ApprovedTemporarily => "approved temporarily",
+                ReviewDecision::Denied => "denied",
+                ReviewDecision::Abort => "aborted",
+            };
+            format!("approval: {subject} {decision} ({call_id}, request {id})")
+        }
+        AuditEvent::McpToolCall {
+            call_id,
+            server,
+            tool,
+            arguments,
+            success,
+            duration_ms,
+        } => format!(
+            "mcp {call_id}: {server}/{tool}{} {} after {duration_ms}ms",
+            display_arguments(arguments.as_ref()),
+            if *success { "succeeded" } else { "failed" }
+        ),
+    }
+}
+
+fn sandbox_label(policy: &SandboxPolicy) -> &'static str {
+    match policy {
+        SandboxPolicy::DangerFullAccess => "danger-full-access",
+        SandboxPolicy::ReadOnly { .. } => "read-only",
+        SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+    }
+}
+
+fn display_arguments(arguments: Option<&serde_json::Value>) -> String {
+    arguments
+        .map(|arguments| format!(" {arguments}"))
+        .unwrap_or_default()
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_commands_and_approvals() {
+        let exec = AuditEvent::ExecCommand {
+            call_id: "call-1".to_string(),
+            command: vec!["cargo".to_string(), "test".to_string()],
+            cwd: PathBuf::from("/repo"),
+            exit_code: 101,
+            duration_ms: 2500,
+        };
+        assert_eq!(
+            describe_event(&exec),
+            "exec call-1: cargo test (in /repo) exited 101 after 2500ms"
+        );
+
+        let approval = AuditEvent::ApprovalDecision {
+            id: "7".to_string(),
+            call_id: "call-2".to_string(),
+            subject: ApprovalSubject::Patch {
+                paths: vec![PathBuf::from("src/lib.rs")],
+            },
+            decision: ReviewDecision::Denied,
+        };
+        assert_eq!(
+            describe_event(&approval),
+            "approval: patch src/lib.rs denied (call-2, request 7)"
+        );
+
+        let mcp = AuditEvent::McpToolCall {
+            call_id: "call-3".to_string(),
+            server: "github".to_string(),
+            tool: "create_issue".to_string(),
+            arguments: Some(serde_json::json!({ "title": "flaky test" })),
+            success: true,
+            duration_ms: 80,
+        };
+        assert_eq!(
+            describe_event(&mcp),
+            r#"mcp call-3: github/create_issue {"title":"flaky test"} succeeded after 80ms"#
+        );
+    }
+}
@@ -1,7 +1,12 @@
+pub mod audit;
 pub mod debug_sandbox;
 mod exit_status;
 pub mod login;
+pub mod mcp;
+pub mod policy;
 pub mod proto;
+pub mod sessions;
+pub mod usage;
 
 use clap::Parser;
 use codex_common::CliConfigOverrides;
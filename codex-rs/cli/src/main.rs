@@ -7,14 +7,25 @@ use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
 use codex_cli::LandlockCommand;
 use codex_cli::SeatbeltCommand;
+use codex_cli::audit::run_audit_show;
+use codex_cli::audit::run_audit_verify;
 use codex_cli::login::run_login_status;
 use codex_cli::login::run_login_with_api_key;
 use codex_cli::login::run_login_with_chatgpt;
 use codex_cli::login::run_logout;
+use codex_cli::mcp::run_mcp_trust;
+use codex_cli::policy::run_policy_check;
+use codex_cli::policy::run_policy_explain;
+use codex_cli::policy::run_policy_suggest;
+use codex_cli::policy::run_policy_test;
 use codex_cli::proto;
+use codex_cli::sessions::run_sessions_list;
+use codex_cli::usage::UsageGroupByArg;
+use codex_cli::usage::run_usage;
 use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
 use codex_tui::Cli as TuiCli;
+use codex_tui::ResumeSelection;
 use std::path::PathBuf;
 
 use crate::proto::ProtoCli;
@@ -56,8 +67,24 @@ enum Subcommand {
     /// Remove stored authentication credentials.
     Logout(LogoutCommand),
 
+    /// Browse recorded sessions.
+    Sessions(SessionsCommand),
+
+    /// Resume a previous interactive session. Without arguments, shows a
+    /// picker of recorded sessions.
+    Resume(ResumeCommand),
+
+    /// Inspect the tamper-evident audit log of agent actions.
+    Audit(AuditCommand),
+
+    /// Summarize token usage and cost across recorded sessions.
+    Usage(UsageCommand),
+
+    /// Check commands against the exec policy and test policy files.
+    Policy(PolicyCommand),
+
     /// Experimental: run Codex as an MCP server.
-    Mcp,
+    Mcp(McpCommand),
 
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
@@ -78,6 +105,159 @@ enum Subcommand {
     GenerateTs(GenerateTsCommand),
 }
 
+#[derive(Debug, Parser)]
+struct SessionsCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum SessionsSubcommand {
+    /// List recorded sessions, most recent first.
+    List {
+        /// Maximum number of sessions to show.
+        #[arg(long = "limit", short = 'n')]
+        limit: Option<usize>,
+
+        /// Print one JSON object per session.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct AuditCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: AuditSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum AuditSubcommand {
+    /// Check that no record of the audit log was modified or removed.
+    Verify {
+        /// Audit log to check. Defaults to the configured log.
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// Print the audit log in a human-readable form.
+    Show {
+        /// Audit log to print. Defaults to the configured log.
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Only show records of the session with this id (or id prefix).
+        #[arg(long = "session", value_name = "SESSION_ID")]
+        session: Option<String>,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct UsageCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    /// Group sessions by the day they started, their working directory, or
+    /// the model that served them.
+    #[arg(long = "by", value_enum, default_value_t = UsageGroupByArg::Day)]
+    by: UsageGroupByArg,
+
+    /// Only count sessions started on or after this day (YYYY-MM-DD, UTC).
+    #[arg(long = "since", value_name = "DATE")]
+    since: Option<String>,
+
+    /// Print one JSON object per group.
+    #[arg(long = "json", default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct PolicyCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: PolicySubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum PolicySubcommand {
+    /// Print whether a command would be allowed, need approval, or be
+    /// forbidden. Exits with 1 if it is forbidden.
+    Check {
+        /// Print the decision as a JSON object.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+
+        /// Command to check, e.g. `codex policy check -- git push --force`.
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Show which rules matched a command and how they resolved its
+    /// arguments.
+    Explain {
+        /// Print one JSON object per command.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+
+        /// Command to explain.
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Run the `should_match` and `should_not_match` examples of policy
+    /// files. Exits with 1 if any fails.
+    Test {
+        /// Directory of `.policy` files. Defaults to `~/.codex/policy` and
+        /// `.codex/policy`.
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Print one JSON object per policy file.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+    },
+
+    /// Suggest `define_program` rules generalized from the commands approved
+    /// "for session" in this project.
+    Suggest {
+        /// Use the approvals recorded in every project, not just this one.
+        #[arg(long = "all-projects", default_value_t = false)]
+        all_projects: bool,
+
+        /// Only suggest rules that cover at least this many approvals.
+        #[arg(long = "min-count", value_name = "N", default_value_t = 2)]
+        min_count: usize,
+
+        /// Append the suggested rules to this policy file, e.g.
+        /// `.codex/policy/approved.policy`.
+        #[arg(long = "save", value_name = "FILE")]
+        save: Option<PathBuf>,
+
+        /// Print one JSON object per suggested rule.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct ResumeCommand {
+    /// Id (or unique id prefix) of the session to resume.
+    #[arg(value_name = "SESSION_ID", conflicts_with = "last")]
+    session_id: Option<String>,
+
+    /// Resume the most recent session.
+    #[arg(long = "last", default_value_t = false)]
+    last: bool,
+}
+
 #[derive(Debug, Parser)]
 struct CompletionCommand {
     /// Shell to generate completions for
@@ -118,6 +298,28 @@ enum LoginSubcommand {
     Status,
 }
 
+#[derive(Debug, Parser)]
+struct McpCommand {
+    #[clap(skip)]
+    config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    action: Option<McpSubcommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum McpSubcommand {
+    /// Review the changed tool definitions of an MCP server and trust them.
+    Trust {
+        /// Name of the server, as configured under `mcp_servers`.
+        server: String,
+
+        /// Trust the definitions without asking for confirmation.
+        #[arg(long = "yes", short = 'y', default_value_t = false)]
+        yes: bool,
+    },
+}
+
 #[derive(Debug, Parser)]
 struct LogoutCommand {
     #[clap(skip)]
@@ -158,8 +360,86 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut exec_cli.config_overrides, cli.config_overrides);
             codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
         }
-        Some(Subcommand::Mcp) => {
-            codex_mcp_server::run_main(codex_linux_sandbox_exe, cli.config_overrides).await?;
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(&mut sessions_cli.config_overrides, cli.config_overrides);
+            match sessions_cli.action {
+                SessionsSubcommand::List { limit, json } => {
+                    run_sessions_list(sessions_cli.config_overrides, limit, json);
+                }
+            }
+        }
+        Some(Subcommand::Resume(resume_cli)) => {
+            let mut tui_cli = cli.interactive;
+            prepend_config_flags(&mut tui_cli.config_overrides, cli.config_overrides);
+            tui_cli.resume = Some(match (resume_cli.session_id, resume_cli.last) {
+                (Some(id), _) => ResumeSelection::Id(id),
+                (None, true) => ResumeSelection::Last,
+                (None, false) => ResumeSelection::Picker,
+            });
+            let usage = codex_tui::run_main(tui_cli, codex_linux_sandbox_exe).await?;
+            if !usage.is_zero() {
+                println!("{}", codex_core::protocol::FinalOutput::from(usage));
+            }
+        }
+        Some(Subcommand::Audit(mut audit_cli)) => {
+            prepend_config_flags(&mut audit_cli.config_overrides, cli.config_overrides);
+            match audit_cli.action {
+                AuditSubcommand::Verify { file } => {
+                    run_audit_verify(audit_cli.config_overrides, file);
+                }
+                AuditSubcommand::Show { file, session } => {
+                    run_audit_show(audit_cli.config_overrides, file, session);
+                }
+            }
+        }
+        Some(Subcommand::Usage(mut usage_cli)) => {
+            prepend_config_flags(&mut usage_cli.config_overrides, cli.config_overrides);
+            run_usage(
+                usage_cli.config_overrides,
+                usage_cli.by,
+                usage_cli.since,
+                usage_cli.json,
+            );
+        }
+        Some(Subcommand::Policy(mut policy_cli)) => {
+            prepend_config_flags(&mut policy_cli.config_overrides, cli.config_overrides);
+            match policy_cli.action {
+                PolicySubcommand::Check { json, command } => {
+                    run_policy_check(policy_cli.config_overrides, command, json);
+                }
+                PolicySubcommand::Explain { json, command } => {
+                    run_policy_explain(policy_cli.config_overrides, command, json);
+                }
+                PolicySubcommand::Test { dir, json } => {
+                    run_policy_test(policy_cli.config_overrides, dir, json);
+                }
+                PolicySubcommand::Suggest {
+                    all_projects,
+                    min_count,
+                    save,
+                    json,
+                } => {
+                    run_policy_suggest(
+                        policy_cli.config_overrides,
+                        all_projects,
+                        min_count,
+                        save,
+                        json,
+                    );
+                }
+            }
+        }
+        Some(Subcommand::Mcp(mut mcp_cli)) => {
+            prepend_config_flags(&mut mcp_cli.config_overrides, cli.config_overrides);
+            match mcp_cli.action {
+                Some(McpSubcommand::Trust { server, yes }) => {
+                    run_mcp_trust(mcp_cli.config_overrides, server, yes);
+                }
+                None => {
+                    codex_mcp_server::run_main(codex_linux_sandbox_exe, mcp_cli.config_overrides)
+                        .await?;
+                }
+            }
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(&mut login_cli.config_overrides, cli.config_overrides);
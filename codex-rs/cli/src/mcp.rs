@@ -0,0 +1,70 @@
+use std::io::BufRead;
+use std::io::Write;
+
+use codex_common::CliConfigOverrides;
+use codex_core::mcp_tool_pins::definition_diff;
+use codex_core::mcp_tool_pins::mcp_tool_pins_path;
+use codex_core::mcp_tool_pins::pending_changes;
+use codex_core::mcp_tool_pins::trust_tools;
+
+use crate::login::load_config_or_exit;
+
+/// Prints the changed tool definitions of `server` that are withheld from the
+/// model and, once confirmed (or with `yes`), pins them so that the tools are
+/// offered again.
+pub fn run_mcp_trust(cli_config_overrides: CliConfigOverrides, server: String, yes: bool) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    if !config.mcp_servers.contains_key(&server) {
+        eprintln!("No MCP server named `{server}` is configured");
+        std::process::exit(1);
+    }
+
+    let path = mcp_tool_pins_path(&config.codex_home);
+    let changes = match pending_changes(&path, &server) {
+        Ok(changes) => changes,
+        Err(e) => {
+            eprintln!("Error reading MCP tool pins: {e}");
+            std::process::exit(1);
+        }
+    };
+    if changes.is_empty() {
+        println!("MCP server `{server}` has no changed tools to trust.");
+        std::process::exit(0);
+    }
+
+    for change in &changes {
+        println!("Tool `{}`:", change.current.name);
+        print!("{}", definition_diff(change));
+        println!();
+    }
+
+    if !yes
+        && !confirm(&format!(
+            "Trust the new definitions of {} tool(s)?",
+            changes.len()
+        ))
+    {
+        println!("Nothing was trusted.");
+        std::process::exit(1);
+    }
+
+    let definitions = changes.into_iter().map(|change| change.current).collect();
+    if let Err(e) = trust_tools(&path, &server, definitions) {
+        eprintln!("Error writing MCP tool pins: {e}");
+        std::process::exit(1);
+    }
+    println!("Trusted. The tools are available in new sessions.");
+    std::process::exit(0);
+}
+
+/// Ask `question` on stdout and read a yes/no answer from stdin. Anything but
+/// `y` or `yes` is a no.
+fn confirm(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
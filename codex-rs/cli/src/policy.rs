@@ -0,0 +1,364 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_common::CliConfigOverrides;
+use codex_core::approval_history::RuleSuggestion;
+use codex_core::approval_history::approvals_path;
+use codex_core::approval_history::read_approvals;
+use codex_core::approval_history::suggest_rules;
+use codex_core::config::Config;
+use codex_core::exec_policy::CommandExplanation;
+use codex_core::exec_policy::ExecPolicy;
+use codex_core::exec_policy::ExecPolicyDecision;
+use codex_core::exec_policy::PolicyFileTest;
+use codex_core::exec_policy::policy_dirs;
+use codex_core::exec_policy::test_policy_dir;
+use codex_execpolicy::ArgType;
+use codex_execpolicy::MatchedExec;
+use serde::Serialize;
+
+use crate::login::load_config_or_exit;
+
+/// Checks `command` against the exec policies that apply in the configured
+/// working directory. Exits with 1 if the command is forbidden.
+pub fn run_policy_check(
+    cli_config_overrides: CliConfigOverrides,
+    command: Vec<String>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    let policy = load_policy(&config);
+    let decision = policy.evaluate(&command, &config.sandbox_policy, &config.cwd);
+
+    if json {
+        #[derive(Serialize)]
+        struct CheckOutput<'a> {
+            command: &'a [String],
+            #[serde(flatten)]
+            decision: &'a ExecPolicyDecision,
+        }
+        print_json(&CheckOutput {
+            command: &command,
+            decision: &decision,
+        });
+    } else {
+        println!("{}", format_decision(&decision));
+    }
+
+    let exit_code = match decision {
+        ExecPolicyDecision::Forbidden { .. } => 1,
+        ExecPolicyDecision::Allow
+        | ExecPolicyDecision::Ask { .. }
+        | ExecPolicyDecision::NoMatch => 0,
+    };
+    std::process::exit(exit_code);
+}
+
+/// Prints, for each command of `command`, the decision and every rule that
+/// matched it, including how the rule resolved each argument.
+pub fn run_policy_explain(
+    cli_config_overrides: CliConfigOverrides,
+    command: Vec<String>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    let policy = load_policy(&config);
+    let explanations = policy.explain(&command, &config.sandbox_policy, &config.cwd);
+
+    for explanation in &explanations {
+        if json {
+            print_json(explanation);
+        } else {
+            for line in format_explanation(explanation) {
+                println!("{line}");
+            }
+        }
+    }
+    std::process::exit(0);
+}
+
+/// Runs the `should_match` and `should_not_match` examples of the policy files
+/// in `dir`, or in the user and project policy directories. Exits with 1 if a
+/// file does not parse or an example fails.
+pub fn run_policy_test(
+    cli_config_overrides: CliConfigOverrides,
+    dir: Option<PathBuf>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    let dirs = match dir {
+        Some(dir) => vec![dir],
+        None => policy_dirs(&config.codex_home, &config.cwd),
+    };
+    let results: Vec<PolicyFileTest> = dirs.iter().flat_map(|dir| test_policy_dir(dir)).collect();
+
+    if json {
+        for result in &results {
+            print_json(result);
+        }
+    } else if results.is_empty() {
+        let dirs: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        eprintln!("No policy files found in {}", dirs.join(", "));
+    } else {
+        for result in &results {
+            for line in format_test_result(result) {
+                println!("{line}");
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|result| !result.passed()).count();
+    if failed > 0 {
+        if !json {
+            eprintln!("{failed} of {} policy file(s) failed", results.len());
+        }
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
+
+/// Generalizes the commands approved "for session" into `define_program`
+/// rules. Commands that a policy already allows or forbids are ignored. With
+/// `save`, the rules are appended to that policy file.
+pub fn run_policy_suggest(
+    cli_config_overrides: CliConfigOverrides,
+    all_projects: bool,
+    min_count: usize,
+    save: Option<PathBuf>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+    let project = (!all_projects).then_some(config.cwd.as_path());
+    let records = match read_approvals(&approvals_path(&config.codex_home), project) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading approval history: {e}");
+            std::process::exit(1);
+        }
+    };
+    let policy = load_policy(&config);
+    let commands: Vec<Vec<String>> = records
+        .iter()
+        .flat_map(|record| policy.explain(&record.command, &config.sandbox_policy, &record.cwd))
+        .filter(|explanation| explanation.decision == ExecPolicyDecision::NoMatch)
+        .map(|explanation| explanation.command)
+        .collect();
+    let suggestions = suggest_rules(&commands, min_count);
+
+    if json {
+        for suggestion in &suggestions {
+            print_json(suggestion);
+        }
+    } else if suggestions.is_empty() {
+        eprintln!(
+            "No rules to suggest from {} recorded approval(s)",
+            records.len()
+        );
+    } else {
+        println!("{}", format_suggestions(&suggestions));
+    }
+
+    if let Some(path) = save
+        && !suggestions.is_empty()
+    {
+        if let Err(e) = append_rules(&path, &suggestions) {
+            eprintln!("Error writing {}: {e}", path.display());
+            std::process::exit(1);
+        }
+        eprintln!("Saved {} rule(s) to {}", suggestions.len(), path.display());
+    }
+    std::process::exit(0);
+}
+
+fn format_suggestions(suggestions: &[RuleSuggestion]) -> String {
+    suggestions
+        .iter()
+        .map(|suggestion| {
+            format!(
+                "# Generalized from {} approval(s) of `{}`.\n{}",
+                suggestion.approvals, suggestion.program, suggestion.rule
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn append_rules(path: &Path, suggestions: &[RuleSuggestion]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    write!(file, "\n{}", format_suggestions(suggestions))
+}
+
+fn load_policy(config: &Config) -> ExecPolicy {
+    let (policy, errors) = ExecPolicy::load(&config.codex_home, &config.cwd);
+    for error in errors {
+        eprintln!("warning: {error}");
+    }
+    policy
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string(value) {
+        Ok(line) => println!("{line}"),
+        Err(e) => {
+            eprintln!("Error serializing output: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn format_decision(decision: &ExecPolicyDecision) -> String {
+    match decision {
+        ExecPolicyDecision::Allow => "allow".to_string(),
+        ExecPolicyDecision::Ask { reason } => format!("ask: {reason}"),
+        ExecPolicyDecision::Forbidden { reason } => format!("forbidden: {reason}"),
+        ExecPolicyDecision::NoMatch => "no match".to_string(),
+    }
+}
+
+fn format_explanation(explanation: &CommandExplanation) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{}: {}",
+        explanation.command.join(" "),
+        format_decision(&explanation.decision)
+    )];
+    if explanation.matches.is_empty() {
+        lines.push("  no rule matched".to_string());
+    }
+    for rule_match in &explanation.matches {
+        match &rule_match.result {
+            MatchedExec::Match { exec } => {
+                lines.push(format!(
+                    "  {}: matched `{}`",
+                    rule_match.policy, exec.program
+                ));
+                for flag in &exec.flags {
+                    lines.push(format!("    {}  flag", flag.name));
+                }
+                for opt in &exec.opts {
+                    lines.push(format!(
+                        "    {} {}  {}",
+                        opt.name,
+                        opt.value,
+                        arg_type_label(&opt.r#type)
+                    ));
+                }
+                for arg in &exec.args {
+                    lines.push(format!(
+                        "    {}  {}",
+                        arg.value,
+                        arg_type_label(&arg.r#type)
+                    ));
+                }
+            }
+            MatchedExec::Forbidden { reason, .. } => {
+                lines.push(format!("  {}: forbidden: {reason}", rule_match.policy));
+            }
+        }
+    }
+    lines
+}
+
+fn arg_type_label(arg_type: &ArgType) -> &'static str {
+    match arg_type {
+        ArgType::Literal(_) => "literal",
+        ArgType::OpaqueNonFile => "opaque value",
+        ArgType::ReadableFile => "readable file",
+        ArgType::WriteableFile => "writeable file",
+        ArgType::PositiveInteger => "positive integer",
+        ArgType::SedCommand => "sed command",
+        ArgType::Unknown => "unknown",
+    }
+}
+
+fn format_test_result(result: &PolicyFileTest) -> Vec<String> {
+    let status = if result.passed() { "ok" } else { "FAIL" };
+    let mut lines = vec![format!("{status} {}", result.path.display())];
+    if let Some(error) = &result.error {
+        lines.push(format!("  failed to parse: {error}"));
+    }
+    for failed in &result.failed_should_match {
+        lines.push(format!(
+            "  should_match example `{} {}` was rejected: {:?}",
+            failed.program,
+            failed.args.join(" "),
+            failed.error
+        ));
+    }
+    for passed in &result.passed_should_not_match {
+        lines.push(format!(
+            "  should_not_match example `{} {}` was accepted",
+            passed.program,
+            passed.args.join(" ")
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::exec_policy::RuleMatch;
+    use codex_execpolicy::MatchedArg;
+    use codex_execpolicy::ValidExec;
+
+    #[test]
+    fn explanation_lists_resolved_args() {
+        let explanation = CommandExplanation {
+            command: vec!["cp".to_string(), "a.txt".to_string(), "b.txt".to_string()],
+            decision: ExecPolicyDecision::Allow,
+            matches: vec![RuleMatch {
+                policy: "default.policy".to_string(),
+                result: MatchedExec::Match {
+                    exec: ValidExec::new(
+                        "cp",
+                        vec![
+                            MatchedArg::new(0, ArgType::ReadableFile, "a.txt").unwrap(),
+                            MatchedArg::new(1, ArgType::WriteableFile, "b.txt").unwrap(),
+                        ],
+                        &[],
+                    ),
+                },
+            }],
+        };
+        assert_eq!(
+            format_explanation(&explanation),
+            vec![
+                "cp a.txt b.txt: allow",
+                "  default.policy: matched `cp`",
+                "    a.txt  readable file",
+                "    b.txt  writeable file",
+            ]
+        );
+    }
+
+    #[test]
+    fn decisions_are_formatted_with_reason() {
+        assert_eq!(
+            format_decision(&ExecPolicyDecision::Forbidden {
+                reason: "no force-push".to_string()
+            }),
+            "forbidden: no force-push"
+        );
+        assert_eq!(format_decision(&ExecPolicyDecision::NoMatch), "no match");
+    }
+
+    #[test]
+    fn suggestions_are_labelled_with_their_approvals() {
+        let suggestion = RuleSuggestion {
+            program: "cargo".to_string(),
+            approvals: 3,
+            examples: vec![vec!["test".to_string()]],
+            rule: "define_program(\n    program=\"cargo\",\n    args=[\"test\"],\n)\n".to_string(),
+        };
+        assert_eq!(
+            format_suggestions(&[suggestion]),
+            "# Generalized from 3 approval(s) of `cargo`.\n\
+             define_program(\n    program=\"cargo\",\n    args=[\"test\"],\n)\n"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use codex_common::CliConfigOverrides;
+use codex_core::SessionSummary;
+use codex_core::list_sessions;
+
+use crate::login::load_config_or_exit;
+
+/// Maximum number of characters of the first user message shown per session.
+const FIRST_MESSAGE_MAX_CHARS: usize = 60;
+
+pub fn run_sessions_list(
+    cli_config_overrides: CliConfigOverrides,
+    limit: Option<usize>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+
+    let sessions = match list_sessions(&config.codex_home) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("Error listing sessions: {e}");
+            std::process::exit(1);
+        }
+    };
+    let sessions = sessions.into_iter().take(limit.unwrap_or(usize::MAX));
+
+    if json {
+        for session in sessions {
+            match serde_json::to_string(&session) {
+                Ok(line) => println!("{line}"),
+                Err(e) => {
+                    eprintln!("Error serializing session {}: {e}", session.id);
+                    std::process::exit(1);
+                }
+            }
+        }
+    } else {
+        let mut any = false;
+        for session in sessions {
+            any = true;
+            println!("{}", format_session_line(&session));
+        }
+        if !any {
+            eprintln!("No sessions recorded in {}", config.codex_home.display());
+        }
+    }
+    std::process::exit(0);
+}
+
+fn format_session_line(session: &SessionSummary) -> String {
+    let cwd = session
+        .cwd
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let branch = session.git_branch.as_deref().unwrap_or("-");
+    let message = session
+        .first_user_message
+        .as_deref()
+        .map(first_line_truncated)
+        .unwrap_or_default();
+    format!(
+        "{}  {}  {branch}  {cwd}  {message}",
+        session.id, session.timestamp
+    )
+}
+
+fn first_line_truncated(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > FIRST_MESSAGE_MAX_CHARS {
+        let truncated: String = line.chars().take(FIRST_MESSAGE_MAX_CHARS - 1).collect();
+        format!("{truncated}…")
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::first_line_truncated;
+
+    #[test]
+    fn keeps_short_first_line() {
+        assert_eq!(first_line_truncated("audit\nthe parser"), "audit");
+    }
+
+    #[test]
+    fn truncates_long_first_line() {
+        let text = "x".repeat(100);
+        let truncated = first_line_truncated(&text);
+        assert_eq!(truncated.chars().count(), 60);
+        assert!(truncated.ends_with('…'));
+    }
+}
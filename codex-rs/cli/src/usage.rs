@@ -0,0 +1,179 @@
+use clap::ValueEnum;
+use codex_common::CliConfigOverrides;
+use codex_core::usage::UsageGroupBy;
+use codex_core::usage::UsageSummaryRow;
+use codex_core::usage::summarize_usage;
+
+use crate::login::load_config_or_exit;
+
+/// How `codex usage` groups sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum UsageGroupByArg {
+    #[default]
+    Day,
+    Project,
+    Model,
+}
+
+impl From<UsageGroupByArg> for UsageGroupBy {
+    fn from(value: UsageGroupByArg) -> Self {
+        match value {
+            UsageGroupByArg::Day => UsageGroupBy::Day,
+            UsageGroupByArg::Project => UsageGroupBy::Project,
+            UsageGroupByArg::Model => UsageGroupBy::Model,
+        }
+    }
+}
+
+pub fn run_usage(
+    cli_config_overrides: CliConfigOverrides,
+    group_by: UsageGroupByArg,
+    since: Option<String>,
+    json: bool,
+) -> ! {
+    let config = load_config_or_exit(cli_config_overrides);
+
+    if let Some(since) = since.as_deref()
+        && !is_iso_date(since)
+    {
+        eprintln!("Invalid --since date `{since}`: expected YYYY-MM-DD");
+        std::process::exit(1);
+    }
+
+    let rows = match summarize_usage(&config.codex_home, group_by.into(), since.as_deref()) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Error reading sessions: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if json {
+        for row in &rows {
+            match serde_json::to_string(row) {
+                Ok(line) => println!("{line}"),
+                Err(e) => {
+                    eprintln!("Error serializing usage for {}: {e}", row.key);
+                    std::process::exit(1);
+                }
+            }
+        }
+    } else if rows.is_empty() {
+        eprintln!("No usage recorded in {}", config.codex_home.display());
+    } else {
+        for line in format_usage_table(&rows) {
+            println!("{line}");
+        }
+    }
+    std::process::exit(0);
+}
+
+fn format_usage_table(rows: &[UsageSummaryRow]) -> Vec<String> {
+    let header = ["", "sessions", "input", "cached", "output", "cost"].map(String::from);
+    let mut table = vec![header];
+    for row in rows {
+        table.push([
+            row.key.clone(),
+            row.sessions.to_string(),
+            row.usage.input_tokens.to_string(),
+            row.usage.cached_input_tokens.to_string(),
+            row.usage.output_tokens.to_string(),
+            format_cost(row.usage.cost_usd),
+        ]);
+    }
+    let total_cost = rows
+        .iter()
+        .filter_map(|row| row.usage.cost_usd)
+        .reduce(|a, b| a + b);
+    table.push([
+        "total".to_string(),
+        rows.iter().map(|row| row.sessions).sum::<u64>().to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format_cost(total_cost),
+    ]);
+
+    let mut widths = [0usize; 6];
+    for cells in &table {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    table
+        .iter()
+        .map(|cells| {
+            let mut line = format!("{:<width$}", cells[0], width = widths[0]);
+            for (cell, width) in cells.iter().zip(widths).skip(1) {
+                line.push_str(&format!("  {cell:>width$}"));
+            }
+            line
+        })
+        .collect()
+}
+
+fn format_cost(cost_usd: Option<f64>) -> String {
+    cost_usd.map_or_else(|| "-".to_string(), |cost| format!("${cost:.2}"))
+}
+
+fn is_iso_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(idx, b)| match idx {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::ModelUsage;
+
+    #[test]
+    fn table_is_aligned_with_total_row() {
+        let rows = vec![
+            UsageSummaryRow {
+                key: "2025-03-01".to_string(),
+                sessions: 2,
+                usage: ModelUsage {
+                    requests: 4,
+                    input_tokens: 12_000,
+                    cached_input_tokens: 8_000,
+                    output_tokens: 900,
+                    reasoning_output_tokens: 0,
+                    cost_usd: Some(0.5),
+                },
+            },
+            UsageSummaryRow {
+                key: "2025-03-02".to_string(),
+                sessions: 1,
+                usage: ModelUsage {
+                    requests: 1,
+                    input_tokens: 300,
+                    cached_input_tokens: 0,
+                    output_tokens: 20,
+                    reasoning_output_tokens: 0,
+                    cost_usd: None,
+                },
+            },
+        ];
+        assert_eq!(
+            format_usage_table(&rows),
+            vec![
+                "            sessions  input  cached  output   cost",
+                "2025-03-01         2  12000    8000     900  $0.50",
+                "2025-03-02         1    300       0      20      -",
+                "total              3                         $0.50",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_since_date() {
+        assert!(is_iso_date("2025-03-01"));
+        assert!(!is_iso_date("2025-3-1"));
+        assert!(!is_iso_date("yesterday"));
+    }
+}
@@ -0,0 +1,417 @@
+//! Approvals that outlive a single command.
+//!
+//! When the user approves a command "for session", Codex records a grant
+//! rather than only the exact argv, so that related commands are approved as
+//! well: every command with the same prefix (`cargo test -p a` covers
+//! `cargo test -p b`), every invocation of a program in a directory, or the
+//! same command for a limited time. A `bash -lc` script is covered if every
+//! command in it is.
+//!
+//! Only a session-long grant of the exact argv lets a command run outside the
+//! sandbox; commands covered by any other grant still run under the session's
+//! sandbox policy. Prefix and program grants are never offered for shells,
+//! interpreters and programs that destroy data, since any prefix of theirs
+//! covers arbitrary code or arbitrary targets.
+
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::exec_policy::shell_script;
+use crate::exec_policy::split_commands;
+use crate::protocol::ApprovalGrant;
+use crate::protocol::ApprovalScope;
+use crate::protocol::ReviewDecision;
+
+/// How long a `ReviewDecision::ApprovedTemporarily` grant lasts.
+pub const TEMPORARY_GRANT_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// Maximum number of arguments after the program that make up a prefix.
+const MAX_PREFIX_ARGS: usize = 2;
+
+/// Shells and wrappers that run another command given as arguments, locally
+/// or on another machine.
+const SHELLS: &[&str] = &[
+    "bash", "sh", "zsh", "dash", "ksh", "fish", "env", "sudo", "doas", "su", "pkexec", "runuser",
+    "xargs", "parallel", "nohup", "nice", "ionice", "timeout", "time", "stdbuf", "setsid", "flock",
+    "chroot", "unshare", "nsenter", "strace", "watch", "script", "exec", "eval", "command", "ssh",
+];
+
+/// Interpreters and package runners that run code given as arguments.
+const INTERPRETERS: &[&str] = &[
+    "python",
+    "python3",
+    "node",
+    "deno",
+    "bun",
+    "ruby",
+    "perl",
+    "php",
+    "lua",
+    "osascript",
+    "pwsh",
+    "Rscript",
+    "tclsh",
+    "expect",
+    "awk",
+    "gawk",
+    "mawk",
+    "nawk",
+    "sed",
+    "npx",
+    "pnpx",
+    "bunx",
+    "uvx",
+    "pipx",
+];
+
+/// Programs that delete or overwrite whatever they are pointed at.
+const DESTRUCTIVE_PROGRAMS: &[&str] = &[
+    "rm", "rmdir", "unlink", "dd", "shred", "mkfs", "truncate", "mv", "cp", "ln", "tee", "rsync",
+    "chmod", "chown", "find",
+];
+
+/// Whether `program` (by name or path) runs arbitrary code or can destroy
+/// arbitrary files depending on its arguments.
+pub(crate) fn is_unscoped_program(program: &str) -> bool {
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    [SHELLS, INTERPRETERS, DESTRUCTIVE_PROGRAMS]
+        .iter()
+        .any(|programs| programs.contains(&name))
+}
+
+/// The prefix that `ReviewDecision::ApprovedPrefixForSession` would approve
+/// for `command`: the program followed by its leading subcommand-like words,
+/// e.g. `cargo test` for `cargo test -p codex-core`. `None` if `command` is
+/// not a single simple command, has no subcommand, or runs an
+/// [unscoped program](is_unscoped_program).
+pub fn command_prefix(command: &[String]) -> Option<Vec<String>> {
+    let command = single_command(command)?;
+    let (program, args) = command.split_first()?;
+    if is_unscoped_program(program) {
+        return None;
+    }
+    let mut prefix = vec![program.clone()];
+    prefix.extend(
+        args.iter()
+            .take(MAX_PREFIX_ARGS)
+            .take_while(|arg| is_subcommand_like(arg))
+            .cloned(),
+    );
+    (prefix.len() > 1).then_some(prefix)
+}
+
+/// The program that `ReviewDecision::ApprovedProgramInDirectory` would
+/// approve for `command`. `None` if `command` is not a single simple command
+/// or runs an [unscoped program](is_unscoped_program).
+pub fn command_program(command: &[String]) -> Option<String> {
+    single_command(command)?
+        .into_iter()
+        .next()
+        .filter(|program| !is_unscoped_program(program))
+}
+
+/// Returns the only command of `command`, unwrapping shell scripts.
+fn single_command(command: &[String]) -> Option<Vec<String>> {
+    let mut commands = split_commands(command);
+    if commands.len() != 1 || is_shell_script(&commands[0]) {
+        return None;
+    }
+    commands.pop()
+}
+
+/// A script that could not be split into simple commands.
+fn is_shell_script(command: &[String]) -> bool {
+    shell_script(command).is_some()
+}
+
+fn is_subcommand_like(arg: &str) -> bool {
+    !arg.is_empty() && !arg.starts_with('-') && !arg.contains(['/', '.', '=', ':', '*', '~'])
+}
+
+#[derive(Debug, Clone)]
+struct Grant {
+    scope: ApprovalScope,
+    expires_at: Option<Instant>,
+}
+
+impl Grant {
+    fn is_active(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+
+    /// Whether this grant lets `command` run outside the sandbox: only a
+    /// session-long approval of the exact argv does.
+    fn unsandboxes(&self, command: &[String]) -> bool {
+        self.expires_at.is_none()
+            && matches!(&self.scope, ApprovalScope::Command { command: approved } if approved == command)
+    }
+
+    /// Whether this grant covers the simple command `command` run in `cwd`.
+    fn covers(&self, command: &[String], cwd: &Path) -> bool {
+        match &self.scope {
+            ApprovalScope::Command { command: approved } => approved == command,
+            ApprovalScope::Prefix { prefix } => command.starts_with(prefix),
+            ApprovalScope::Program {
+                program,
+                cwd: approved_cwd,
+            } => command.first() == Some(program) && cwd.starts_with(approved_cwd),
+        }
+    }
+}
+
+/// The approval grants of a session.
+#[derive(Debug, Default, Clone)]
+pub struct ApprovalGrants {
+    grants: Vec<Grant>,
+}
+
+impl ApprovalGrants {
+    /// Records the grant that `decision` makes for `command` run in `cwd`.
+    /// Returns `false` if `decision` does not grant anything beyond this run.
+    pub fn grant(&mut self, decision: ReviewDecision, command: &[String], cwd: &Path) -> bool {
+        let exact = || ApprovalScope::Command {
+            command: command.to_vec(),
+        };
+        let prefix = || {
+            command_prefix(command)
+                .map(|prefix| ApprovalScope::Prefix { prefix })
+                .unwrap_or_else(exact)
+        };
+        let (scope, expires_at) = match decision {
+            ReviewDecision::ApprovedForSession => (exact(), None),
+            ReviewDecision::ApprovedPrefixForSession => (prefix(), None),
+            ReviewDecision::ApprovedProgramInDirectory => (
+                command_program(command)
+                    .map(|program| ApprovalScope::Program {
+                        program,
+                        cwd: cwd.to_path_buf(),
+                    })
+                    .unwrap_or_else(exact),
+                None,
+            ),
+            ReviewDecision::ApprovedTemporarily => {
+                (exact(), Some(Instant::now() + TEMPORARY_GRANT_DURATION))
+            }
+            ReviewDecision::Approved | ReviewDecision::Denied | ReviewDecision::Abort => {
+                return false;
+            }
+        };
+        self.grants.retain(|grant| grant.scope != scope);
+        self.grants.push(Grant { scope, expires_at });
+        true
+    }
+
+    /// Whether `command` was approved for the whole session exactly as is,
+    /// which lets it run outside the sandbox.
+    pub fn covers_unsandboxed(&self, command: &[String]) -> bool {
+        self.grants.iter().any(|grant| grant.unsandboxes(command))
+    }
+
+    /// Whether `command`, run in `cwd`, is covered by an active grant.
+    pub fn covers(&self, command: &[String], cwd: &Path) -> bool {
+        let now = Instant::now();
+        let active: Vec<&Grant> = self
+            .grants
+            .iter()
+            .filter(|grant| grant.is_active(now))
+            .collect();
+        let exact = ApprovalScope::Command {
+            command: command.to_vec(),
+        };
+        if active.iter().any(|grant| grant.scope == exact) {
+            return true;
+        }
+        // Only the exact grant may cover a script that could not be split,
+        // since a prefix or program grant says nothing about what it runs.
+        let commands = split_commands(command);
+        !commands.iter().any(|command| is_shell_script(command))
+            && commands
+                .iter()
+                .all(|command| active.iter().any(|grant| grant.covers(command, cwd)))
+    }
+
+    /// The grants that have not expired, oldest first.
+    pub fn active(&self) -> Vec<ApprovalGrant> {
+        let now = Instant::now();
+        self.grants
+            .iter()
+            .filter(|grant| grant.is_active(now))
+            .map(|grant| ApprovalGrant {
+                scope: grant.scope.clone(),
+                expires_in_secs: grant
+                    .expires_at
+                    .map(|expires_at| expires_at.duration_since(now).as_secs()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn command(args: &str) -> Vec<String> {
+        args.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn script(script: &str) -> Vec<String> {
+        vec!["bash".to_string(), "-lc".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn prefix_stops_at_options_and_paths() {
+        assert_eq!(
+            command_prefix(&command("cargo test -p codex-core")),
+            Some(command("cargo test"))
+        );
+        // A prefix needs at least one subcommand word.
+        assert_eq!(command_prefix(&command("pytest tests/a.py")), None);
+        assert_eq!(command_prefix(&command("make")), None);
+        assert_eq!(
+            command_prefix(&script("npm run build")),
+            Some(command("npm run build"))
+        );
+        assert_eq!(command_prefix(&script("make && make install")), None);
+    }
+
+    #[test]
+    fn prefix_grant_covers_other_arguments() {
+        let cwd = Path::new("/repo");
+        let mut grants = ApprovalGrants::default();
+        assert!(grants.grant(
+            ReviewDecision::ApprovedPrefixForSession,
+            &command("cargo test -p a"),
+            cwd
+        ));
+        assert!(grants.covers(&command("cargo test -p b"), cwd));
+        assert!(grants.covers(&script("cargo test -p b && cargo test --doc"), cwd));
+        assert!(!grants.covers(&script("cargo test -p b && rm -rf build"), cwd));
+        assert!(!grants.covers(&command("cargo run"), cwd));
+        assert!(!grants.covers_unsandboxed(&command("cargo test -p b")));
+    }
+
+    #[test]
+    fn unscoped_programs_are_only_approved_exactly() {
+        for unscoped in [
+            "rm build/tmp",
+            "rm -rf build",
+            "rm foo",
+            "bash build.sh",
+            "python -c print(1)",
+            "node -e 1",
+            "/usr/bin/env make",
+            "env FOO=1 make",
+            "sudo make install",
+            "ssh build-host make",
+            "npx create-react-app app",
+            "awk {system(\"rm\")} file",
+            "sed -i s/a/b/ file",
+            "find . -exec rm {} ;",
+            "xargs rm",
+            "timeout 10 make",
+            "cp build/out /usr/bin/tool",
+        ] {
+            assert_eq!(command_prefix(&command(unscoped)), None, "{unscoped}");
+            assert_eq!(command_program(&command(unscoped)), None, "{unscoped}");
+        }
+
+        let cwd = Path::new("/repo");
+        let mut grants = ApprovalGrants::default();
+        grants.grant(
+            ReviewDecision::ApprovedPrefixForSession,
+            &command("rm build/tmp"),
+            cwd,
+        );
+        grants.grant(
+            ReviewDecision::ApprovedProgramInDirectory,
+            &command("python -c print(1)"),
+            cwd,
+        );
+        assert!(grants.covers(&command("rm build/tmp"), cwd));
+        assert!(!grants.covers(&command("rm -rf /"), cwd));
+        assert!(!grants.covers(&command("python -c print(2)"), cwd));
+    }
+
+    #[test]
+    fn only_exact_session_grants_run_unsandboxed() {
+        let cwd = Path::new("/repo");
+        let mut grants = ApprovalGrants::default();
+        grants.grant(
+            ReviewDecision::ApprovedForSession,
+            &command("make install"),
+            cwd,
+        );
+        grants.grant(
+            ReviewDecision::ApprovedProgramInDirectory,
+            &command("make test"),
+            cwd,
+        );
+        grants.grant(
+            ReviewDecision::ApprovedTemporarily,
+            &command("cargo build"),
+            cwd,
+        );
+        assert!(grants.covers_unsandboxed(&command("make install")));
+        assert!(grants.covers(&command("make clean"), cwd));
+        assert!(!grants.covers_unsandboxed(&command("make clean")));
+        assert!(grants.covers(&command("cargo build"), cwd));
+        assert!(!grants.covers_unsandboxed(&command("cargo build")));
+    }
+
+    #[test]
+    fn program_grant_is_limited_to_the_directory() {
+        let mut grants = ApprovalGrants::default();
+        grants.grant(
+            ReviewDecision::ApprovedProgramInDirectory,
+            &command("make test"),
+            Path::new("/repo"),
+        );
+        assert!(grants.covers(&command("make clean"), Path::new("/repo/core")));
+        assert!(!grants.covers(&command("make clean"), Path::new("/other")));
+        assert_eq!(
+            grants.active(),
+            vec![ApprovalGrant {
+                scope: ApprovalScope::Program {
+                    program: "make".to_string(),
+                    cwd: "/repo".into(),
+                },
+                expires_in_secs: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn temporary_grants_expire() {
+        let cwd = Path::new("/repo");
+        let mut grants = ApprovalGrants::default();
+        grants.grant(
+            ReviewDecision::ApprovedTemporarily,
+            &command("cargo test"),
+            cwd,
+        );
+        assert!(grants.covers(&command("cargo test"), cwd));
+        assert!(!grants.covers(&command("cargo test -p codex-core"), cwd));
+        let expires_in_secs = grants.active()[0].expires_in_secs;
+        assert!(expires_in_secs.is_some_and(|secs| secs <= TEMPORARY_GRANT_DURATION.as_secs()));
+
+        grants.grants[0].expires_at = Some(Instant::now());
+        assert!(!grants.covers(&command("cargo test"), cwd));
+        assert_eq!(grants.active(), Vec::new());
+    }
+
+    #[test]
+    fn unparsed_scripts_are_only_covered_exactly() {
+        let cwd = Path::new("/repo");
+        let redirect = script("echo hi > out.txt");
+        let mut grants = ApprovalGrants::default();
+        assert!(grants.grant(ReviewDecision::ApprovedPrefixForSession, &redirect, cwd));
+        assert!(grants.covers(&redirect, cwd));
+        assert!(!grants.covers(&script("echo bye > out.txt"), cwd));
+        assert!(!grants.grant(ReviewDecision::Approved, &command("ls"), cwd));
+    }
+}
@@ -0,0 +1,416 @@
+//! Persistent record of the commands approved "for session".
+//!
+//! Every time the user approves a command for the rest of a session, the
+//! command is appended to `~/.codex/approvals.jsonl` together with the working
+//! directory it was approved in, **one JSON object per line**:
+//!
+//! ````text
+//! {"ts":<unix_seconds>,"cwd":"/path/to/project","command":["cargo","test","-p","codex-core"]}
+//! ````
+//!
+//! Recorded approvals are not re-applied to later sessions. Instead,
+//! [`suggest_rules`] generalizes them into candidate `define_program()` rules
+//! that the user can review and add to a policy file (`codex policy suggest`).
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::PolicyParser;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::approval_grants::is_unscoped_program;
+use crate::file_lock;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename of the approval history inside `~/.codex`.
+pub const APPROVALS_FILENAME: &str = "approvals.jsonl";
+
+/// Options through which programs commonly take code to run, e.g.
+/// `python -c` or `node -e`.
+const CODE_OPTIONS: &[&str] = &[
+    "-c",
+    "-lc",
+    "-e",
+    "--eval",
+    "--command",
+    "--exec",
+    "--execute",
+];
+
+/// A command the user approved for the rest of a session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalRecord {
+    /// Seconds since the Unix epoch.
+    pub ts: u64,
+    /// Working directory the command was approved in.
+    pub cwd: PathBuf,
+    pub command: Vec<String>,
+}
+
+impl ApprovalRecord {
+    pub(crate) fn new(cwd: PathBuf, command: Vec<String>) -> Self {
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self { ts, cwd, command }
+    }
+}
+
+/// Path of the approval history under `codex_home`.
+pub fn approvals_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(APPROVALS_FILENAME)
+}
+
+/// Append `record` to the approval history at `path`.
+pub(crate) async fn append_approval(path: PathBuf, record: ApprovalRecord) -> std::io::Result<()> {
+    tokio::task::spawn_blocking(move || append_record(&path, &record))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+fn append_record(path: &Path, record: &ApprovalRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file_lock::lock_exclusive(&file, "approval history")?;
+
+    let line = format!("{}\n", serde_json::to_string(record)?);
+    // Write the line in a single call so that it cannot be interleaved.
+    file.write_all(line.as_bytes())?;
+    file.flush()
+}
+
+/// Read the approval history at `path`. When `project` is set, only the
+/// approvals recorded in that directory or below it are returned. A missing
+/// file is an empty history; lines that do not parse are skipped.
+pub fn read_approvals(path: &Path, project: Option<&Path>) -> std::io::Result<Vec<ApprovalRecord>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let Ok(record) = serde_json::from_str::<ApprovalRecord>(&line?) else {
+            continue;
+        };
+        if project.is_none_or(|project| record.cwd.starts_with(project)) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// A candidate `define_program()` rule generalized from approved commands.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleSuggestion {
+    pub program: String,
+    /// Number of approved commands the rule covers.
+    pub approvals: usize,
+    /// The distinct approved argument lists, used as `should_match` examples.
+    pub examples: Vec<Vec<String>>,
+    /// The rule, as Starlark source for a policy file.
+    pub rule: String,
+}
+
+/// Generalize `commands` into `define_program()` rules.
+///
+/// Commands are grouped by program and first positional argument (usually the
+/// subcommand), and a rule is suggested for every group with at least
+/// `min_count` commands. Positional arguments shared by the whole group become
+/// literals and any further positional arguments are left unverified; an
+/// option that is always followed by a value becomes `opt(...)`, any other
+/// option a `flag(...)`. Rules whose own examples do not match are dropped.
+///
+/// Commands that may run arbitrary code are never generalized: shells,
+/// interpreters and programs that destroy data (including `bash -lc` scripts
+/// that could not be split), and any command passing code through an option
+/// such as `-c` or `-e`.
+pub fn suggest_rules(commands: &[Vec<String>], min_count: usize) -> Vec<RuleSuggestion> {
+    let mut by_program: BTreeMap<&str, Vec<&[String]>> = BTreeMap::new();
+    for command in commands {
+        if let Some((program, args)) = command.split_first()
+            && !is_unscoped_program(program)
+            && !args.iter().any(|arg| CODE_OPTIONS.contains(&arg.as_str()))
+        {
+            by_program.entry(program).or_default().push(args);
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for (program, invocations) in by_program {
+        let options = classify_options(&invocations);
+        let mut groups: BTreeMap<Option<&str>, Vec<Invocation>> = BTreeMap::new();
+        for args in invocations {
+            let positionals = positional_args(args, &options);
+            groups
+                .entry(positionals.first().copied())
+                .or_default()
+                .push(Invocation { args, positionals });
+        }
+
+        for group in groups.into_values() {
+            if group.len() < min_count.max(1) {
+                continue;
+            }
+            if let Some(suggestion) = suggest_rule(program, &options, &group) {
+                suggestions.push(suggestion);
+            }
+        }
+    }
+    suggestions.sort_by(|a, b| b.approvals.cmp(&a.approvals));
+    suggestions
+}
+
+/// The arguments of one approved command and which of them are positional.
+struct Invocation<'a> {
+    args: &'a [String],
+    positionals: Vec<&'a str>,
+}
+
+/// Whether each option of a program takes a value (`true`) or is a flag.
+fn classify_options<'a>(invocations: &[&'a [String]]) -> BTreeMap<&'a str, bool> {
+    let mut options: BTreeMap<&str, bool> = BTreeMap::new();
+    for args in invocations {
+        for (index, arg) in args.iter().enumerate() {
+            if is_option(arg) {
+                let has_value = args.get(index + 1).is_some_and(|next| !is_option(next));
+                let takes_value = options.entry(arg).or_insert(has_value);
+                *takes_value &= has_value;
+            }
+        }
+    }
+    options
+}
+
+fn positional_args<'a>(args: &'a [String], options: &BTreeMap<&str, bool>) -> Vec<&'a str> {
+    let mut positionals = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match options.get(arg.as_str()) {
+            Some(true) => {
+                args.next();
+            }
+            Some(false) => {}
+            None => positionals.push(arg.as_str()),
+        }
+    }
+    positionals
+}
+
+fn is_option(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-')
+}
+
+fn suggest_rule(
+    program: &str,
+    options: &BTreeMap<&str, bool>,
+    group: &[Invocation],
+) -> Option<RuleSuggestion> {
+    let mut literals: Vec<&str> = group.first()?.positionals.clone();
+    for invocation in group {
+        let common = literals
+            .iter()
+            .zip(&invocation.positionals)
+            .take_while(|(a, b)| a == b)
+            .count();
+        literals.truncate(common);
+    }
+    let varargs = group
+        .iter()
+        .any(|invocation| invocation.positionals.len() > literals.len());
+
+    let mut seen: HashSet<&[String]> = HashSet::new();
+    let examples: Vec<Vec<String>> = group
+        .iter()
+        .filter(|invocation| seen.insert(invocation.args))
+        .map(|invocation| invocation.args.to_vec())
+        .collect();
+    let used_options: BTreeMap<&str, bool> = options
+        .iter()
+        .filter(|(name, _)| examples.iter().flatten().any(|arg| arg == *name))
+        .map(|(name, takes_value)| (*name, *takes_value))
+        .collect();
+
+    let rule = format_rule(program, &used_options, &literals, varargs, &examples);
+    let policy = PolicyParser::new("suggestion", &rule).parse().ok()?;
+    if !policy.check_each_good_list_individually().is_empty() {
+        return None;
+    }
+    Some(RuleSuggestion {
+        program: program.to_string(),
+        approvals: group.len(),
+        examples,
+        rule,
+    })
+}
+
+fn format_rule(
+    program: &str,
+    options: &BTreeMap<&str, bool>,
+    literals: &[&str],
+    varargs: bool,
+    examples: &[Vec<String>],
+) -> String {
+    let mut rule = format!("define_program(\n    program={},\n", quote(program));
+    if !options.is_empty() {
+        rule.push_str("    options=[\n");
+        for (name, takes_value) in options {
+            if *takes_value {
+                rule.push_str(&format!(
+                    "        opt({}, ARG_OPAQUE_VALUE),\n",
+                    quote(name)
+                ));
+            } else {
+                rule.push_str(&format!("        flag({}),\n", quote(name)));
+            }
+        }
+        rule.push_str("    ],\n");
+    }
+    let mut args: Vec<String> = literals.iter().map(|literal| quote(literal)).collect();
+    if varargs {
+        args.push("ARG_UNVERIFIED_VARARGS".to_string());
+    }
+    rule.push_str(&format!("    args=[{}],\n", args.join(", ")));
+    rule.push_str("    should_match=[\n");
+    for example in examples {
+        let example: Vec<String> = example.iter().map(|arg| quote(arg)).collect();
+        rule.push_str(&format!("        [{}],\n", example.join(", ")));
+    }
+    rule.push_str("    ],\n)\n");
+    rule
+}
+
+/// Quotes `s` as a Starlark string literal.
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{s}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn command(args: &str) -> Vec<String> {
+        args.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn generalizes_cargo_test_variants() {
+        let commands = vec![
+            command("cargo test -p codex-core"),
+            command("cargo test -p codex-tui --lib"),
+            command("cargo test -p codex-core"),
+            command("cargo build --release"),
+        ];
+        let suggestions = suggest_rules(&commands, 2);
+        assert_eq!(
+            suggestions,
+            vec![RuleSuggestion {
+                program: "cargo".to_string(),
+                approvals: 3,
+                examples: vec![
+                    command("test -p codex-core"),
+                    command("test -p codex-tui --lib")
+                ],
+                rule: r#"define_program(
+    program="cargo",
+    options=[
+        flag("--lib"),
+        opt("-p", ARG_OPAQUE_VALUE),
+    ],
+    args=["test"],
+    should_match=[
+        ["test", "-p", "codex-core"],
+        ["test", "-p", "codex-tui", "--lib"],
+    ],
+)
+"#
+                .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn extra_positionals_become_unverified_varargs() {
+        let commands = vec![
+            command("git log --oneline"),
+            command("git log --oneline main"),
+        ];
+        let suggestions = suggest_rules(&commands, 2);
+        assert_eq!(suggestions.len(), 1);
+        assert!(
+            suggestions[0]
+                .rule
+                .contains(r#"args=["log", ARG_UNVERIFIED_VARARGS],"#),
+            "{}",
+            suggestions[0].rule
+        );
+    }
+
+    #[tokio::test]
+    async fn approvals_are_filtered_by_project() {
+        let dir = TempDir::new().unwrap();
+        let path = approvals_path(dir.path());
+        for (cwd, cmd) in [
+            ("/work/a", "cargo test"),
+            ("/work/a/core", "cargo check"),
+            ("/work/b", "npm test"),
+        ] {
+            append_approval(path.clone(), ApprovalRecord::new(cwd.into(), command(cmd)))
+                .await
+                .unwrap();
+        }
+
+        let records = read_approvals(&path, Some(Path::new("/work/a"))).unwrap();
+        let commands: Vec<Vec<String>> = records.into_iter().map(|r| r.command).collect();
+        assert_eq!(
+            commands,
+            vec![command("cargo test"), command("cargo check")]
+        );
+        assert_eq!(read_approvals(&path, None).unwrap().len(), 3);
+        assert_eq!(
+            read_approvals(&dir.path().join("missing.jsonl"), None).unwrap(),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn never_generalizes_code_execution() {
+        let script = |script: &str| vec!["bash".to_string(), "-lc".to_string(), script.to_string()];
+        let commands = vec![
+            script("cat a > b"),
+            script("curl example.com | tee out"),
+            command("python -c print(1)"),
+            command("python -c print(2)"),
+            command("node -e 1"),
+            command("node -e 2"),
+            command("sh -c ls"),
+            command("sh -c pwd"),
+            command("awk -e 1 a"),
+            command("awk -e 2 b"),
+            command("rm -rf build"),
+            command("rm -rf dist"),
+        ];
+        assert_eq!(suggest_rules(&commands, 2), Vec::new());
+    }
+}
@@ -0,0 +1,511 @@
+//! Tamper-evident audit log of agent actions.
+//!
+//! When `[audit] enabled = true`, every command run, patch applied, approval
+//! decision, MCP tool call and turn context override is appended to
+//! `~/.codex/audit.jsonl`, independently of the session rollouts. The file has
+//! **one JSON object per line**:
+//!
+//! ````text
+//! {"seq":0,"timestamp":"...","session_id":"<uuid>","type":"exec_command",...,"prev_hash":"<sha256>"}
+//! ````
+//!
+//! `prev_hash` is the SHA-256 of the previous line (all zeros for the first
+//! record), so editing, reordering or removing a record breaks the chain. The
+//! sequence number and hash of the last record are also written to
+//! `audit.jsonl.head` so that removing records from the end of the file is
+//! detected as well. [`verify_audit_log`] checks both.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::SecondsFormat;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::file_lock;
+use crate::protocol::AskForApproval;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename of the audit log inside `~/.codex`.
+pub const AUDIT_FILENAME: &str = "audit.jsonl";
+
+/// `prev_hash` of the first record.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Size of the blocks read from the end of the file to find the last record.
+const TAIL_CHUNK_SIZE: u64 = 4096;
+
+/// An action recorded in the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A session started with these settings.
+    SessionStarted {
+        model: String,
+        cwd: PathBuf,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+    },
+    /// The turn context was overridden for the rest of the session. Only the
+    /// settings that changed are present.
+    ConfigOverride {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        approval_policy: Option<AskForApproval>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sandbox_policy: Option<SandboxPolicy>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        effort: Option<ReasoningEffort>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        summary: Option<ReasoningSummary>,
+    },
+    /// A command finished running.
+    ExecCommand {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+        exit_code: i32,
+        duration_ms: u64,
+    },
+    /// A command was started in an interactive exec session.
+    ExecSessionCommand { call_id: String, cmd: String },
+    /// A patch was applied (or failed to apply).
+    PatchApplied {
+        call_id: String,
+        paths: Vec<PathBuf>,
+        success: bool,
+    },
+    /// The user answered a pending approval request.
+    ApprovalDecision {
+        /// Id of the submission that requested approval.
+        id: String,
+        call_id: String,
+        subject: ApprovalSubject,
+        decision: ReviewDecision,
+    },
+    /// An MCP tool call finished.
+    McpToolCall {
+        call_id: String,
+        server: String,
+        tool: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+        success: bool,
+        duration_ms: u64,
+    },
+}
+
+/// What an approval request asked to do.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApprovalSubject {
+    Exec {
+        command: Vec<String>,
+        cwd: PathBuf,
+    },
+    Patch {
+        paths: Vec<PathBuf>,
+    },
+    McpTool {
+        server: String,
+        tool: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+    },
+}
+
+/// One line of the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Position of the record in the log, starting at 0.
+    pub seq: u64,
+    pub timestamp: String,
+    pub session_id: Uuid,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    /// SHA-256 of the previous line.
+    pub prev_hash: String,
+}
+
+/// Contents of the `.head` file written next to the log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct AuditHead {
+    seq: u64,
+    hash: String,
+}
+
+/// Appends the actions of one session to the audit log.
+#[derive(Clone, Debug)]
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    session_id: Uuid,
+}
+
+impl AuditLog {
+    pub(crate) fn new(path: PathBuf, session_id: Uuid) -> Self {
+        Self { path, session_id }
+    }
+
+    /// Append `event` to the log. The file is locked while the previous
+    /// record is read and the new one is written so that concurrent sessions
+    /// keep a single chain.
+    pub(crate) async fn append(&self, event: AuditEvent) -> std::io::Result<()> {
+        let path = self.path.clone();
+        let session_id = self.session_id;
+        tokio::task::spawn_blocking(move || append_record(&path, session_id, event))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+}
+
+fn append_record(path: &Path, session_id: Uuid, event: AuditEvent) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.append(true).read(true).create(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file_lock::lock_exclusive(&file, "audit log")?;
+
+    // A write interrupted by a crash leaves a partial last line, which would
+    // otherwise break the chain for every later record.
+    let length = file.seek(SeekFrom::End(0))?;
+    let complete = complete_length(&mut file)?;
+    if complete < length {
+        warn!(
+            "discarding an incomplete last record ({} bytes) of {}",
+            length - complete,
+            path.display()
+        );
+        file.set_len(complete)?;
+    }
+
+    let (seq, prev_hash) = match read_last_line(&mut file)? {
+        Some(line) => {
+            let previous: AuditRecord = serde_json::from_slice(&line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("last record of {} is invalid: {e}", path.display()),
+                )
+            })?;
+            (previous.seq + 1, sha256_hex(&line))
+        }
+        None => (0, GENESIS_HASH.to_string()),
+    };
+
+    let record = AuditRecord {
+        seq,
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        session_id,
+        event,
+        prev_hash,
+    };
+    let line = serde_json::to_string(&record)?;
+    // Write the line in a single call so that it cannot be interleaved.
+    file.write_all(format!("{line}\n").as_bytes())?;
+    file.flush()?;
+
+    let head = AuditHead {
+        seq,
+        hash: sha256_hex(line.as_bytes()),
+    };
+    std::fs::write(head_path(path), serde_json::to_vec(&head)?)
+}
+
+/// Length of `file` up to and including its last newline, i.e. without a
+/// trailing partial line.
+fn complete_length(file: &mut File) -> std::io::Result<u64> {
+    let mut end = file.seek(SeekFrom::End(0))?;
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK_SIZE);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        if let Some(newline) = chunk.iter().rposition(|b| *b == b'\n') {
+            return Ok(start + newline as u64 + 1);
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// Read the last non-empty line of `file` without reading the whole file.
+fn read_last_line(file: &mut File) -> std::io::Result<Option<Vec<u8>>> {
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut tail: Vec<u8> = Vec::new();
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK_SIZE);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+
+        let content = tail.strip_suffix(b"\n").unwrap_or(&tail);
+        if let Some(newline) = content.iter().rposition(|b| *b == b'\n') {
+            return Ok(Some(content[newline + 1..].to_vec()));
+        }
+    }
+    let content = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    Ok((!content.is_empty()).then(|| content.to_vec()))
+}
+
+fn head_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".head");
+    path.with_file_name(name)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Result of [`verify_audit_log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditVerification {
+    /// Every record is chained to the previous one.
+    Intact {
+        records: u64,
+        /// Whether the end of the log was checked against the `.head` file.
+        /// When the head file is missing, removing records from the end of
+        /// the log cannot be detected.
+        head_checked: bool,
+    },
+    /// The log was modified. `line` is the 1-based line where the chain first
+    /// breaks.
+    Broken { line: usize, reason: String },
+}
+
+/// Check that the audit log at `path` has not been tampered with or
+/// truncated.
+pub fn verify_audit_log(path: &Path) -> std::io::Result<AuditVerification> {
+    let contents = std::fs::read(path)?;
+    let mut expected_prev_hash = GENESIS_HASH.to_string();
+    let mut records: u64 = 0;
+
+    let body = match contents.strip_suffix(b"\n") {
+        Some(body) => body,
+        None if contents.is_empty() => &contents[..],
+        None => {
+            let line = contents.iter().filter(|b| **b == b'\n').count() + 1;
+            return Ok(AuditVerification::Broken {
+                line,
+                reason: "the last record is incomplete".to_string(),
+            });
+        }
+    };
+    let lines = if body.is_empty() {
+        Vec::new()
+    } else {
+        body.split(|b| *b == b'\n').collect()
+    };
+
+    let line_count = lines.len();
+    for (index, line) in lines.into_iter().enumerate() {
+        let broken = |reason: String| AuditVerification::Broken {
+            line: index + 1,
+            reason,
+        };
+        let record: AuditRecord = match serde_json::from_slice(line) {
+            Ok(record) => record,
+            Err(e) => return Ok(broken(format!("not a valid audit record: {e}"))),
+        };
+        if record.seq != records {
+            return Ok(broken(format!(
+                "expected record {records} but found record {}; records were removed or reordered",
+                record.seq
+            )));
+        }
+        if record.prev_hash != expected_prev_hash {
+            return Ok(broken(
+                "prev_hash does not match the previous record; a record was modified".to_string(),
+            ));
+        }
+        expected_prev_hash = sha256_hex(line);
+        records += 1;
+    }
+
+    let head = match std::fs::read(head_path(path)) {
+        Ok(bytes) => Some(serde_json::from_slice::<AuditHead>(&bytes).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid audit head file: {e}"),
+            )
+        })?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    if let Some(head) = &head {
+        let last_seq = records.checked_sub(1);
+        if last_seq != Some(head.seq) {
+            return Ok(AuditVerification::Broken {
+                line: line_count,
+                reason: format!(
+                    "the log ends with {records} record(s) but {} were written; records were removed from the end",
+                    head.seq + 1
+                ),
+            });
+        }
+        if head.hash != expected_prev_hash {
+            return Ok(AuditVerification::Broken {
+                line: line_count,
+                reason: "the last record does not match the head file; it was modified".to_string(),
+            });
+        }
+    }
+
+    Ok(AuditVerification::Intact {
+        records,
+        head_checked: head.is_some(),
+    })
+}
+
+/// Read every record of the audit log at `path`, e.g. to display it.
+pub fn read_audit_log(path: &Path) -> std::io::Result<Vec<AuditRecord>> {
+    let contents = std::fs::read_to_string(path)?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn exec_event(call_id: &str) -> AuditEvent {
+        AuditEvent::ExecCommand {
+            call_id: call_id.to_string(),
+            command: vec!["ls".to_string(), "-l".to_string()],
+            cwd: PathBuf::from("/repo"),
+            exit_code: 0,
+            duration_ms: 12,
+        }
+    }
+
+    async fn write_log(dir: &TempDir, events: usize) -> PathBuf {
+        let path = dir.path().join(AUDIT_FILENAME);
+        let log = AuditLog::new(path.clone(), Uuid::new_v4());
+        for i in 0..events {
+            log.append(exec_event(&format!("call-{i}"))).await.unwrap();
+        }
+        path
+    }
+
+    #[tokio::test]
+    async fn appended_records_form_a_chain() {
+        let dir = TempDir::new().unwrap();
+        let path = write_log(&dir, 3).await;
+
+        let records = read_audit_log(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].prev_hash, GENESIS_HASH);
+        assert_eq!(records[2].seq, 2);
+        assert_eq!(records[2].event, exec_event("call-2"));
+        assert_eq!(
+            verify_audit_log(&path).unwrap(),
+            AuditVerification::Intact {
+                records: 3,
+                head_checked: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_modified_record() {
+        let dir = TempDir::new().unwrap();
+        let path = write_log(&dir, 3).await;
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replacen("call-1", "call-X", 1)).unwrap();
+
+        assert_eq!(
+            verify_audit_log(&path).unwrap(),
+            AuditVerification::Broken {
+                line: 3,
+                reason: "prev_hash does not match the previous record; a record was modified"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn append_discards_an_incomplete_last_record() {
+        let dir = TempDir::new().unwrap();
+        let path = write_log(&dir, 2).await;
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let partial = &contents.lines().last().unwrap()[..20];
+        // A crash while the third record was written.
+        std::fs::write(&path, format!("{contents}{partial}")).unwrap();
+
+        let log = AuditLog::new(path.clone(), Uuid::new_v4());
+        log.append(exec_event("call-2")).await.unwrap();
+
+        let records = read_audit_log(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].seq, 2);
+        assert_eq!(
+            verify_audit_log(&path).unwrap(),
+            AuditVerification::Intact {
+                records: 3,
+                head_checked: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_removed_records() {
+        let dir = TempDir::new().unwrap();
+        let path = write_log(&dir, 3).await;
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+
+        // Removing a record from the middle breaks the sequence.
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(matches!(
+            verify_audit_log(&path).unwrap(),
+            AuditVerification::Broken { line: 2, .. }
+        ));
+
+        // Removing the last record is caught by the head file.
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[1])).unwrap();
+        assert_eq!(
+            verify_audit_log(&path).unwrap(),
+            AuditVerification::Broken {
+                line: 2,
+                reason: "the log ends with 2 record(s) but 3 were written; records were removed from the end"
+                    .to_string(),
+            }
+        );
+    }
+}
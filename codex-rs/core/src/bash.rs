@@ -21,6 +21,41 @@ pub fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
 /// (parentheses, redirections, substitutions, control flow, etc.). Otherwise
 /// returns `None`.
 pub fn try_parse_word_only_commands_sequence(tree: &Tree, src: &str) -> Option<Vec<Vec<String>>> {
+    word_only_command_nodes(tree)?
+        .into_iter()
+        .map(|node| parse_plain_command_from_node(node, src))
+        .collect()
+}
+
+/// Like [`try_parse_word_only_commands_sequence`], but groups the commands by
+/// the pipeline they belong to. Pipelines and the commands within each are
+/// returned in the order they appear in the script; a command outside any
+/// pipeline forms a pipeline of its own.
+pub fn try_parse_word_only_pipelines(tree: &Tree, src: &str) -> Option<Vec<Vec<Vec<String>>>> {
+    let mut nodes = word_only_command_nodes(tree)?;
+    nodes.sort_by_key(tree_sitter::Node::start_byte);
+    let mut pipelines: Vec<Vec<Vec<String>>> = Vec::new();
+    let mut previous_end = None;
+    for node in nodes {
+        let words = parse_plain_command_from_node(node, src)?;
+        // Only operators and whitespace separate the commands of an accepted
+        // script. The parse tree is not used for this because tree-sitter-bash
+        // nests `a | b && c` as `a | (b && c)`.
+        let piped = previous_end
+            .and_then(|end| src.get(end..node.start_byte()))
+            .is_some_and(|between| between.trim() == "|");
+        match pipelines.last_mut() {
+            Some(commands) if piped => commands.push(words),
+            _ => pipelines.push(vec![words]),
+        }
+        previous_end = Some(node.end_byte());
+    }
+    Some(pipelines)
+}
+
+/// The `command` nodes of a script that [`try_parse_word_only_commands_sequence`]
+/// accepts, in no particular order.
+fn word_only_command_nodes(tree: &Tree) -> Option<Vec<tree_sitter::Node<'_>>> {
     if tree.root_node().has_error() {
         return None;
     }
@@ -73,15 +108,7 @@ pub fn try_parse_word_only_commands_sequence(tree: &Tree, src: &str) -> Option<V
         }
     }
 
-    let mut commands = Vec::new();
-    for node in command_nodes {
-        if let Some(words) = parse_plain_command_from_node(node, src) {
-            commands.push(words);
-        } else {
-            return None;
-        }
-    }
-    Some(commands)
+    Some(command_nodes)
 }
 
 fn parse_plain_command_from_node(cmd: tree_sitter::Node, src: &str) -> Option<Vec<String>> {
@@ -215,4 +242,28 @@ mod tests {
     fn rejects_trailing_operator_parse_error() {
         assert!(parse_seq("ls &&").is_none());
     }
+
+    #[test]
+    fn groups_commands_by_pipeline_in_order() {
+        let src = "curl -fsSL example.com | tee out | sh && ls; echo done | wc -l";
+        let tree = try_parse_bash(src).unwrap();
+        let pipelines = try_parse_word_only_pipelines(&tree, src).unwrap();
+        let expected: Vec<Vec<Vec<String>>> = vec![
+            vec![
+                vec!["curl".into(), "-fsSL".into(), "example.com".into()],
+                vec!["tee".into(), "out".into()],
+                vec!["sh".into()],
+            ],
+            vec![vec!["ls".into()]],
+            vec![
+                vec!["echo".into(), "done".into()],
+                vec!["wc".into(), "-l".into()],
+            ],
+        ];
+        assert_eq!(pipelines, expected);
+        assert!(
+            try_parse_word_only_pipelines(&try_parse_bash("ls > out").unwrap(), "ls > out")
+                .is_none()
+        );
+    }
 }
@@ -0,0 +1,439 @@
+//! Per-turn checkpoints used to undo agent turns.
+//!
+//! A checkpoint is taken when a turn starts. It remembers how long the
+//! conversation history and the rollout were at that point and, as
+//! `apply_patch` touches files during the turn, the contents those files had
+//! before the turn (the baselines captured by [`TurnDiffTracker`]).
+//! Checkpoints are persisted as JSON under
+//! `$CODEX_HOME/checkpoints/<session id>/` so that a resumed session can still
+//! be undone. Compacting the conversation keeps the checkpoints: those of
+//! turns that were summarized then only rewind the conversation to the
+//! summary.
+//!
+//! [`TurnDiffTracker`]: crate::turn_diff_tracker::TurnDiffTracker
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Deserialize;
+use serde::Serialize;
+use uuid::Uuid;
+
+const CHECKPOINTS_SUBDIR: &str = "checkpoints";
+
+/// Only the most recent turns can be undone; older checkpoints are pruned.
+const MAX_CHECKPOINTS: usize = 50;
+
+/// How a file looked before the turn that is being checkpointed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum FileBaseline {
+    /// The file did not exist.
+    Missing { path: PathBuf },
+    /// A regular file, with its base64-encoded contents.
+    File {
+        path: PathBuf,
+        contents: String,
+        executable: bool,
+    },
+    /// A symbolic link pointing at `target`.
+    Symlink { path: PathBuf, target: PathBuf },
+}
+
+impl FileBaseline {
+    pub(crate) fn file(path: PathBuf, contents: &[u8], executable: bool) -> Self {
+        FileBaseline::File {
+            path,
+            contents: BASE64.encode(contents),
+            executable,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        match self {
+            FileBaseline::Missing { path }
+            | FileBaseline::File { path, .. }
+            | FileBaseline::Symlink { path, .. } => path,
+        }
+    }
+
+    /// Put the file back the way it was.
+    fn restore(&self) -> std::io::Result<()> {
+        let path = self.path();
+        remove_if_exists(path)?;
+        let contents = match self {
+            FileBaseline::Missing { .. } => return Ok(()),
+            FileBaseline::File { contents, .. } => BASE64
+                .decode(contents)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            FileBaseline::Symlink { .. } => Vec::new(),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match self {
+            FileBaseline::Missing { .. } => Ok(()),
+            FileBaseline::File { executable, .. } => {
+                std::fs::write(path, contents)?;
+                set_executable(path, *executable)
+            }
+            FileBaseline::Symlink { target, .. } => create_symlink(target, path),
+        }
+    }
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: bool) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    if executable {
+        let mut permissions = std::fs::metadata(path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        std::fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: bool) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "symlinks can only be restored on Unix",
+    ))
+}
+
+/// On-disk representation of a checkpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Checkpoint {
+    turn_id: String,
+    /// Number of items in the conversation history when the turn started.
+    history_len: usize,
+    /// Number of items in the rollout when the turn started.
+    rollout_len: usize,
+    /// Files touched during the turn, as they were before it.
+    files: Vec<FileBaseline>,
+    /// The turn was folded into a summary when the history was compacted, so
+    /// `history_len` points at the end of that summary.
+    #[serde(skip)]
+    summarized: bool,
+}
+
+/// Result of undoing one or more turns.
+#[derive(Debug, PartialEq)]
+pub(crate) struct UndoOutcome {
+    /// Length the conversation history must be truncated to.
+    pub(crate) history_len: usize,
+    /// Length the rollout must be truncated to.
+    pub(crate) rollout_len: usize,
+    /// Files that were restored, sorted.
+    pub(crate) restored_paths: Vec<PathBuf>,
+    /// Files that could not be restored.
+    pub(crate) errors: Vec<String>,
+    /// The oldest undone turn was summarized by a compaction, so the summary
+    /// still mentions it.
+    pub(crate) summarized: bool,
+}
+
+/// Checkpoints of the turns of one session, oldest first.
+pub(crate) struct CheckpointStore {
+    dir: PathBuf,
+    checkpoints: Vec<(PathBuf, Checkpoint)>,
+    next_seq: u64,
+}
+
+impl CheckpointStore {
+    pub(crate) fn new(codex_home: &Path, session_id: Uuid) -> Self {
+        Self {
+            dir: codex_home
+                .join(CHECKPOINTS_SUBDIR)
+                .join(session_id.to_string()),
+            checkpoints: Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Load the checkpoints persisted for a resumed session. The history of a
+    /// resumed session is rebuilt from the rollout, so `history_len_for`
+    /// maps a rollout length to the corresponding history length; checkpoints
+    /// it cannot map are discarded.
+    pub(crate) fn load(
+        codex_home: &Path,
+        session_id: Uuid,
+        history_len_for: impl Fn(usize) -> Option<usize>,
+    ) -> Self {
+        let mut store = Self::new(codex_home, session_id);
+        let Ok(entries) = std::fs::read_dir(&store.dir) else {
+            return store;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let checkpoint = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<Checkpoint>(&text).ok());
+            match checkpoint.and_then(|mut checkpoint| {
+                checkpoint.history_len = history_len_for(checkpoint.rollout_len)?;
+                Some(checkpoint)
+            }) {
+                Some(checkpoint) => store.checkpoints.push((path, checkpoint)),
+                None => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        store.next_seq = store
+            .checkpoints
+            .last()
+            .and_then(|(path, _)| path.file_stem()?.to_str()?.parse::<u64>().ok())
+            .map_or(0, |seq| seq + 1);
+        store
+    }
+
+    /// Take a checkpoint for a turn that is about to start.
+    pub(crate) fn begin_turn(
+        &mut self,
+        turn_id: &str,
+        history_len: usize,
+        rollout_len: usize,
+    ) -> std::io::Result<()> {
+        let path = self.dir.join(format!("{:08}.json", self.next_seq));
+        self.next_seq += 1;
+        let checkpoint = Checkpoint {
+            turn_id: turn_id.to_string(),
+            history_len,
+            rollout_len,
+            files: Vec::new(),
+            summarized: false,
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        write_checkpoint(&path, &checkpoint)?;
+        self.checkpoints.push((path, checkpoint));
+
+        while self.checkpoints.len() > MAX_CHECKPOINTS {
+            let (path, _) = self.checkpoints.remove(0);
+            let _ = std::fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    /// Record the baselines of the files touched so far by the turn
+    /// `turn_id`. Paths that already have a baseline keep it.
+    pub(crate) fn record_baselines(
+        &mut self,
+        turn_id: &str,
+        baselines: Vec<FileBaseline>,
+    ) -> std::io::Result<()> {
+        let Some((path, checkpoint)) = self.checkpoints.last_mut() else {
+            return Ok(());
+        };
+        if checkpoint.turn_id != turn_id {
+            return Ok(());
+        }
+        let mut changed = false;
+        for baseline in baselines {
+            if !checkpoint
+                .files
+                .iter()
+                .any(|existing| existing.path() == baseline.path())
+            {
+                checkpoint.files.push(baseline);
+                changed = true;
+            }
+        }
+        if changed {
+            write_checkpoint(path, checkpoint)?;
+        }
+        Ok(())
+    }
+
+    /// Follow a compaction that replaced the first `summarized_len` items of
+    /// the history with `summary_len` items. Checkpoints of turns that are
+    /// still in the history are shifted; those of summarized turns now rewind
+    /// to the end of the summary. The rollout is not compacted, so the files
+    /// on disk stay valid.
+    pub(crate) fn history_compacted(&mut self, summarized_len: usize, summary_len: usize) {
+        for (_, checkpoint) in &mut self.checkpoints {
+            if checkpoint.history_len >= summarized_len {
+                checkpoint.history_len = checkpoint.history_len - summarized_len + summary_len;
+            } else {
+                checkpoint.history_len = summary_len;
+                checkpoint.summarized = true;
+            }
+        }
+    }
+
+    /// Restore the files touched by the last `turns` turns and drop their
+    /// checkpoints.
+    pub(crate) fn undo(&mut self, turns: usize) -> Result<UndoOutcome, String> {
+        if turns == 0 || self.checkpoints.is_empty() {
+            return Err("There is nothing to undo.".to_string());
+        }
+        if turns > self.checkpoints.len() {
+            return Err(format!(
+                "Only {} turn(s) can be undone.",
+                self.checkpoints.len()
+            ));
+        }
+
+        let undone = self.checkpoints.split_off(self.checkpoints.len() - turns);
+        let mut restored_paths = Vec::new();
+        let mut errors = Vec::new();
+        // Newest first, so that the state before the oldest undone turn wins.
+        for (_, checkpoint) in undone.iter().rev() {
+            for baseline in &checkpoint.files {
+                match baseline.restore() {
+                    Ok(()) => restored_paths.push(baseline.path().to_path_buf()),
+                    Err(e) => errors.push(format!(
+                        "failed to restore {}: {e}",
+                        baseline.path().display()
+                    )),
+                }
+            }
+        }
+        restored_paths.sort();
+        restored_paths.dedup();
+
+        for (path, _) in &undone {
+            let _ = std::fs::remove_file(path);
+        }
+        let (_, oldest) = &undone[0];
+        Ok(UndoOutcome {
+            history_len: oldest.history_len,
+            rollout_len: oldest.rollout_len,
+            restored_paths,
+            errors,
+            summarized: oldest.summarized,
+        })
+    }
+}
+
+fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    let json = serde_json::to_vec(checkpoint).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn undo_restores_files_from_the_oldest_undone_turn() {
+        let codex_home = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let edited = workspace.path().join("edited.txt");
+        let added = workspace.path().join("added.txt");
+        let deleted = workspace.path().join("deleted.txt");
+        std::fs::write(&edited, "v1").unwrap();
+        std::fs::write(&deleted, "keep").unwrap();
+
+        let mut store = CheckpointStore::new(codex_home.path(), Uuid::new_v4());
+        store.begin_turn("turn-1", 2, 3).unwrap();
+        store
+            .record_baselines(
+                "turn-1",
+                vec![FileBaseline::file(edited.clone(), b"v1", false)],
+            )
+            .unwrap();
+        std::fs::write(&edited, "v2").unwrap();
+
+        store.begin_turn("turn-2", 5, 7).unwrap();
+        store
+            .record_baselines(
+                "turn-2",
+                vec![
+                    FileBaseline::file(edited.clone(), b"v2", false),
+                    FileBaseline::Missing {
+                        path: added.clone(),
+                    },
+                    FileBaseline::file(deleted.clone(), b"keep", false),
+                ],
+            )
+            .unwrap();
+        std::fs::write(&edited, "v3").unwrap();
+        std::fs::write(&added, "new").unwrap();
+        std::fs::remove_file(&deleted).unwrap();
+
+        let outcome = store.undo(2).unwrap();
+
+        let mut expected_paths = vec![added.clone(), deleted.clone(), edited.clone()];
+        expected_paths.sort();
+        assert_eq!(
+            outcome,
+            UndoOutcome {
+                history_len: 2,
+                rollout_len: 3,
+                restored_paths: expected_paths,
+                errors: Vec::new(),
+                summarized: false,
+            }
+        );
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "v1");
+        assert_eq!(std::fs::read_to_string(&deleted).unwrap(), "keep");
+        assert!(!added.exists());
+        assert_eq!(store.checkpoints.len(), 0);
+        assert_eq!(store.undo(1), Err("There is nothing to undo.".to_string()));
+    }
+
+    #[test]
+    fn checkpoints_survive_reload() {
+        let codex_home = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let mut store = CheckpointStore::new(codex_home.path(), session_id);
+        store.begin_turn("turn-1", 2, 3).unwrap();
+        store.begin_turn("turn-2", 5, 7).unwrap();
+
+        // The second checkpoint refers to rollout items that no longer exist.
+        let reloaded = CheckpointStore::load(codex_home.path(), session_id, |rollout_len| {
+            (rollout_len <= 4).then_some(rollout_len - 1)
+        });
+
+        assert_eq!(reloaded.checkpoints.len(), 1);
+        assert_eq!(reloaded.checkpoints[0].1.history_len, 2);
+        assert_eq!(reloaded.next_seq, 1);
+    }
+
+    #[test]
+    fn compaction_keeps_checkpoints() {
+        let codex_home = TempDir::new().unwrap();
+        let mut store = CheckpointStore::new(codex_home.path(), Uuid::new_v4());
+        store.begin_turn("turn-1", 2, 3).unwrap();
+        store.begin_turn("turn-2", 6, 8).unwrap();
+        store.begin_turn("turn-3", 9, 12).unwrap();
+
+        // The first 7 items, covering turn-1 and the start of turn-2, were
+        // replaced by a 3-item summary.
+        store.history_compacted(7, 3);
+
+        let outcome = store.undo(1).unwrap();
+        assert_eq!((outcome.history_len, outcome.summarized), (5, false));
+        let outcome = store.undo(2).unwrap();
+        assert_eq!(
+            (outcome.history_len, outcome.rollout_len, outcome.summarized),
+            (3, 3, true)
+        );
+    }
+}
@@ -32,12 +32,14 @@ use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::messages_api::stream_messages;
 use crate::model_family::ModelFamily;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::openai_model_info::get_model_info;
 use crate::openai_tools::create_tools_json_for_responses_api;
 use crate::protocol::TokenUsage;
+use crate::provider_failover::FallbackProvider;
 use crate::user_agent::get_codex_user_agent;
 use crate::util::backoff;
 use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
@@ -91,14 +93,31 @@ impl ModelClient {
         }
     }
 
+    /// A client for the same session that sends its requests to `fallback`
+    /// instead.
+    pub(crate) fn with_fallback(&self, fallback: &FallbackProvider) -> Self {
+        let mut config = (*self.config).clone();
+        config.model = fallback.model.clone();
+        config.model_family = fallback.model_family.clone();
+        // The configured limits describe the primary model.
+        let model_info = get_model_info(&fallback.model_family);
+        config.model_context_window = model_info.as_ref().map(|info| info.context_window);
+        config.model_max_output_tokens = model_info.map(|info| info.max_output_tokens);
+        Self {
+            config: Arc::new(config),
+            provider: fallback.model_provider.clone(),
+            ..self.clone()
+        }
+    }
+
     pub fn get_model_context_window(&self) -> Option<u64> {
         self.config
             .model_context_window
             .or_else(|| get_model_info(&self.config.model_family).map(|info| info.context_window))
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
+    /// Dispatches to the Responses, Chat or Messages implementation depending
+    /// on the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
@@ -138,6 +157,17 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Messages => {
+                stream_messages(
+                    prompt,
+                    &self.config.model_family,
+                    self.effort,
+                    self.config.model_max_output_tokens,
+                    &self.client,
+                    &self.provider,
+                )
+                .await
+            }
         }
     }
 
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -22,6 +23,7 @@ use futures::prelude::*;
 use mcp_types::CallToolResult;
 use serde::Serialize;
 use serde_json;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::task::AbortHandle;
 use tracing::debug;
@@ -37,10 +39,21 @@ use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::approval_grants::ApprovalGrants;
+use crate::approval_history::ApprovalRecord;
+use crate::approval_history::append_approval;
+use crate::approval_history::approvals_path;
+use crate::audit::ApprovalSubject;
+use crate::audit::AuditEvent;
+use crate::audit::AuditLog;
+use crate::checkpoints::CheckpointStore;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::AutoCompact;
+use crate::config_types::HistoryPersistence;
+use crate::config_types::NotificationKind;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
 use crate::environment_context::EnvironmentContext;
@@ -60,8 +73,18 @@ use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
 use crate::exec_env::create_env;
+use crate::exec_policy::ExecPolicy;
+use crate::finding_tool::Finding;
+use crate::finding_tool::REPORT_FINDING_TOOL_NAME;
+use crate::finding_tool::handle_report_finding;
+use crate::hooks::HookEvent;
+use crate::hooks::HookFailure;
+use crate::hooks::HookRunner;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::McpUpdate;
+use crate::mcp_input::resolve_mcp_input;
 use crate::mcp_tool_call::handle_mcp_tool_call;
+use crate::mcp_tool_pins::mcp_tool_pins_path;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
 use crate::openai_tools::ApplyPatchToolArgs;
@@ -81,34 +104,59 @@ use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::BudgetExceeded;
+use crate::protocol::ContextCompactedEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::ExecCommandRejectedEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
+use crate::protocol::ListApprovalGrantsResponseEvent;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpListPromptsResponseEvent;
+use crate::protocol::McpListResourcesResponseEvent;
+use crate::protocol::McpServerStatusEvent;
+use crate::protocol::McpToolApprovalRequestEvent;
+use crate::protocol::McpToolsChangedEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SecretRedactedEvent;
 use crate::protocol::SessionConfiguredEvent;
+use crate::protocol::SessionUsage;
+use crate::protocol::SessionUsageEvent;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
+use crate::protocol::TaskBudget;
 use crate::protocol::TaskCompleteEvent;
+use crate::protocol::TokenUsage;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnUndoneEvent;
 use crate::protocol::WebSearchBeginEvent;
 use crate::protocol::WebSearchEndEvent;
+use crate::provider_failover::ProviderFailover;
+use crate::provider_failover::ProviderSwitch;
+use crate::provider_failover::is_provider_failure;
+use crate::redaction::SecretRedactor;
 use crate::rollout::RolloutRecorder;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
 use crate::shell;
+use crate::task_budget::BudgetTracker;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::usage::ModelPricing;
+use crate::usage::pricing_for_model;
 use crate::user_notification::UserNotification;
+use crate::user_notification::command_summary;
+use crate::user_notification::patch_summary;
 use crate::util::backoff;
 use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
@@ -256,14 +304,34 @@ impl Codex {
     }
 }
 
+/// An approval request waiting for the user's decision.
+struct PendingApproval {
+    tx_approve: oneshot::Sender<ReviewDecision>,
+    call_id: String,
+    subject: ApprovalSubject,
+}
+
 /// Mutable state of the agent
 #[derive(Default)]
 struct State {
-    approved_commands: HashSet<Vec<String>>,
+    approval_grants: ApprovalGrants,
     current_task: Option<AgentTask>,
-    pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
-    pending_input: Vec<ResponseInputItem>,
+    /// Keyed by the id of the submission that requested approval.
+    pending_approvals: HashMap<String, PendingApproval>,
+    /// MCP tools, as (server, tool), that the user approved for the rest of
+    /// the session.
+    approved_mcp_tools: HashSet<(String, String)>,
+    /// Input submitted while a task was running. MCP items are resolved by
+    /// the task when it picks the input up.
+    pending_input: Vec<Vec<InputItem>>,
     history: ConversationHistory,
+    /// Usage reported for the most recent model request, used to decide when
+    /// the conversation needs to be compacted.
+    last_token_usage: Option<TokenUsage>,
+    /// Findings reported with the `report_finding` tool, in order.
+    findings: Vec<Finding>,
+    /// Token and cost totals for the session, including resumed usage.
+    usage: SessionUsage,
 }
 
 /// Context for an initialized model agent
@@ -280,14 +348,35 @@ pub(crate) struct Session {
     /// External notifier command (will be passed as args to exec()). When
     /// `None` this feature is disabled.
     notify: Option<Vec<String>>,
+    /// Notification types passed to `notify`.
+    notify_on: Vec<NotificationKind>,
 
     /// Optional rollout recorder for persisting the conversation transcript so
     /// sessions can be replayed or inspected later.
     rollout: Mutex<Option<RolloutRecorder>>,
     state: Mutex<State>,
+    /// Default, user and repo-local execpolicy rules consulted before running
+    /// any shell command.
+    exec_policy: ExecPolicy,
     codex_linux_sandbox_exe: Option<PathBuf>,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
+    auto_compact: AutoCompact,
+    /// Masks secrets in tool outputs and user input before they are recorded.
+    secret_redactor: SecretRedactor,
+    /// Checkpoints taken at the start of every turn, used by `Op::UndoTurn`.
+    checkpoints: Mutex<CheckpointStore>,
+    /// Tamper-evident log of the actions taken in this session, if enabled.
+    audit_log: Option<AuditLog>,
+    /// Where commands approved for the session are recorded, unless history
+    /// persistence is disabled.
+    approval_history: Option<PathBuf>,
+    /// Which provider serves model requests, and how healthy each one is.
+    provider_failover: Mutex<ProviderFailover>,
+    /// Price overrides from `model_pricing`, used to cost each request.
+    model_pricing: HashMap<String, ModelPricing>,
+    /// User commands run on lifecycle events, configured under `[hooks]`.
+    hooks: HookRunner,
 }
 
 /// The context needed for a single turn of the conversation.
@@ -305,6 +394,8 @@ pub(crate) struct TurnContext {
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
     pub(crate) disable_response_storage: bool,
     pub(crate) tools_config: ToolsConfig,
+    /// Limits on the work a task started with this context may do.
+    pub(crate) task_budget: TaskBudget,
 }
 
 impl TurnContext {
@@ -386,6 +477,24 @@ impl Session {
         // Error messages to dispatch after SessionConfigured is sent.
         let mut post_session_configured_error_events = Vec::<Event>::new();
 
+        let (exec_policy, exec_policy_errors) = ExecPolicy::load(&config.codex_home, &cwd);
+        for message in exec_policy_errors {
+            warn!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
+        let (secret_redactor, redaction_errors) = SecretRedactor::new(&config.redaction);
+        for message in redaction_errors {
+            warn!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
         // Kick off independent async setup tasks in parallel to reduce startup latency.
         //
         // - initialize RolloutRecorder with new or resumed session info
@@ -406,7 +515,12 @@ impl Session {
             }
         };
 
-        let mcp_fut = McpConnectionManager::new(config.mcp_servers.clone());
+        let (mcp_updates_tx, mut mcp_updates_rx) = mpsc::unbounded_channel();
+        let mcp_fut = McpConnectionManager::new(
+            config.mcp_servers.clone(),
+            mcp_tool_pins_path(&config.codex_home),
+            mcp_updates_tx,
+        );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
 
@@ -419,9 +533,29 @@ impl Session {
             session_id: Uuid,
             rollout_recorder: Option<RolloutRecorder>,
             restored_items: Option<Vec<ResponseItem>>,
+            restored_usage: SessionUsage,
+            checkpoints: CheckpointStore,
         }
         let rollout_result = match rollout_res {
             Ok((session_id, maybe_saved, recorder)) => {
+                let checkpoints = match maybe_saved.as_ref() {
+                    // The restored history is rebuilt from the rollout, so a
+                    // checkpoint's history length is that of its rollout
+                    // prefix.
+                    Some(saved) => {
+                        CheckpointStore::load(&config.codex_home, session_id, |rollout_len| {
+                            let prefix = saved.items.get(..rollout_len)?;
+                            let mut history = ConversationHistory::new();
+                            history.record_items(prefix);
+                            Some(history.len())
+                        })
+                    }
+                    None => CheckpointStore::new(&config.codex_home, session_id),
+                };
+                let restored_usage = maybe_saved
+                    .as_ref()
+                    .map(|saved| saved.state.usage.clone())
+                    .unwrap_or_default();
                 let restored_items: Option<Vec<ResponseItem>> = initial_history.or_else(|| {
                     maybe_saved.and_then(|saved_session| {
                         if saved_session.items.is_empty() {
@@ -435,6 +569,8 @@ impl Session {
                     session_id,
                     rollout_recorder: Some(recorder),
                     restored_items,
+                    restored_usage,
+                    checkpoints,
                 }
             }
             Err(e) => {
@@ -453,10 +589,13 @@ impl Session {
                 });
                 warn!("{message}");
 
+                let session_id = Uuid::new_v4();
                 RolloutResult {
-                    session_id: Uuid::new_v4(),
+                    session_id,
                     rollout_recorder: None,
                     restored_items: None,
+                    restored_usage: SessionUsage::default(),
+                    checkpoints: CheckpointStore::new(&config.codex_home, session_id),
                 }
             }
         };
@@ -465,11 +604,14 @@ impl Session {
             session_id,
             rollout_recorder,
             restored_items,
+            restored_usage,
+            checkpoints,
         } = rollout_result;
 
         // Create the mutable state for the Session.
         let mut state = State {
             history: ConversationHistory::new(),
+            usage: restored_usage,
             ..Default::default()
         };
         if let Some(restored_items) = restored_items {
@@ -519,6 +661,7 @@ impl Session {
                 approval_policy,
                 sandbox_policy: sandbox_policy.clone(),
                 include_plan_tool: config.include_plan_tool,
+                include_finding_tool: config.include_finding_tool,
                 include_apply_patch_tool: config.include_apply_patch_tool,
                 include_web_search_request: config.tools_web_search_request,
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
@@ -531,6 +674,7 @@ impl Session {
             shell_environment_policy: config.shell_environment_policy.clone(),
             cwd,
             disable_response_storage,
+            task_budget: config.task_budget,
         };
         let sess = Arc::new(Session {
             session_id,
@@ -538,26 +682,49 @@ impl Session {
             mcp_connection_manager,
             session_manager: ExecSessionManager::default(),
             notify,
+            notify_on: config.notify_on.clone(),
             state: Mutex::new(state),
             rollout: Mutex::new(rollout_recorder),
+            exec_policy,
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            auto_compact: config.auto_compact.clone(),
+            secret_redactor,
+            checkpoints: Mutex::new(checkpoints),
+            audit_log: config
+                .audit_log
+                .clone()
+                .map(|path| AuditLog::new(path, session_id)),
+            approval_history: match config.history.persistence {
+                HistoryPersistence::SaveAll => Some(approvals_path(&config.codex_home)),
+                HistoryPersistence::None => None,
+            },
+            provider_failover: Mutex::new(ProviderFailover::new(&config)),
+            model_pricing: config.model_pricing.clone(),
+            hooks: HookRunner::new(config.hooks.clone(), session_id),
         });
 
         // record the initial user instructions and environment context,
         // regardless of whether we restored items.
-        let mut conversation_items = Vec::<ResponseItem>::with_capacity(2);
-        if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
-            conversation_items.push(Prompt::format_user_instructions_message(user_instructions));
-        }
-        conversation_items.push(ResponseItem::from(EnvironmentContext::new(
-            Some(turn_context.cwd.clone()),
-            Some(turn_context.approval_policy),
-            Some(turn_context.sandbox_policy.clone()),
-            Some(sess.user_shell.clone()),
-        )));
+        let conversation_items = sess.build_initial_context(&turn_context);
         sess.record_conversation_items(&conversation_items).await;
+        sess.audit(AuditEvent::SessionStarted {
+            model: config.model.clone(),
+            cwd: turn_context.cwd.clone(),
+            approval_policy: turn_context.approval_policy,
+            sandbox_policy: turn_context.sandbox_policy.clone(),
+        })
+        .await;
+        sess.hooks
+            .notify(
+                HookEvent::SessionStart {
+                    model: config.model.clone(),
+                    cwd: turn_context.cwd.clone(),
+                },
+                &turn_context.cwd,
+            )
+            .await;
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         let events = std::iter::once(Event {
@@ -576,6 +743,40 @@ impl Session {
             }
         }
 
+        // Forward the resources and prompts that MCP servers report as
+        // changed, changes to the health of the servers and tools withheld
+        // because their definitions changed, so that front-ends can refresh
+        // their lists.
+        let tx_mcp_updates = tx_event.clone();
+        tokio::spawn(async move {
+            while let Some(update) = mcp_updates_rx.recv().await {
+                let msg = match update {
+                    McpUpdate::Resources(resources) => {
+                        EventMsg::McpListResourcesResponse(McpListResourcesResponseEvent {
+                            resources,
+                        })
+                    }
+                    McpUpdate::Prompts(prompts) => {
+                        EventMsg::McpListPromptsResponse(McpListPromptsResponseEvent { prompts })
+                    }
+                    McpUpdate::ServerStatus(server, status) => {
+                        EventMsg::McpServerStatus(McpServerStatusEvent { server, status })
+                    }
+                    McpUpdate::ToolsChanged(server, changes) => {
+                        EventMsg::McpToolsChanged(McpToolsChangedEvent { server, changes })
+                    }
+                    McpUpdate::ToolsUnverified(message) => EventMsg::Error(ErrorEvent { message }),
+                };
+                let event = Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg,
+                };
+                if tx_mcp_updates.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
         Ok((sess, turn_context))
     }
 
@@ -604,8 +805,33 @@ impl Session {
         }
     }
 
+    /// Masks secrets in `items` and reports each item that had something
+    /// masked with a `SecretRedacted` event.
+    async fn redact_secrets(&self, sub_id: &str, items: &mut [ResponseItem]) {
+        for item in items {
+            let redactions = self.secret_redactor.redact_item(item);
+            if redactions.is_empty() {
+                continue;
+            }
+            let call_id = match item {
+                ResponseItem::FunctionCallOutput { call_id, .. }
+                | ResponseItem::CustomToolCallOutput { call_id, .. } => Some(call_id.clone()),
+                _ => None,
+            };
+            self.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::SecretRedacted(SecretRedactedEvent {
+                    call_id,
+                    redactions,
+                }),
+            })
+            .await;
+        }
+    }
+
     pub async fn request_command_approval(
         &self,
+        turn_context: &TurnContext,
         sub_id: String,
         call_id: String,
         command: Vec<String>,
@@ -616,22 +842,45 @@ impl Session {
         let event = Event {
             id: sub_id.clone(),
             msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
-                call_id,
-                command,
-                cwd,
-                reason,
+                call_id: call_id.clone(),
+                command: command.clone(),
+                cwd: cwd.clone(),
+                reason: reason.clone(),
             }),
         };
         let _ = self.tx_event.send(event).await;
-        {
-            let mut state = self.state.lock_unchecked();
-            state.pending_approvals.insert(sub_id, tx_approve);
-        }
+        self.maybe_notify(UserNotification::ApprovalRequested {
+            turn_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            summary: command_summary(&command),
+            reason: reason.clone(),
+        });
+        self.add_pending_approval(
+            sub_id,
+            tx_approve,
+            call_id.clone(),
+            ApprovalSubject::Exec {
+                command: command.clone(),
+                cwd,
+            },
+        );
+        self.hooks
+            .notify(
+                HookEvent::ApprovalRequest {
+                    call_id,
+                    command: Some(command),
+                    changes: None,
+                    reason,
+                },
+                &turn_context.cwd,
+            )
+            .await;
         rx_approve
     }
 
     pub async fn request_patch_approval(
         &self,
+        turn_context: &TurnContext,
         sub_id: String,
         call_id: String,
         action: &ApplyPatchAction,
@@ -642,38 +891,176 @@ impl Session {
         let event = Event {
             id: sub_id.clone(),
             msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
-                call_id,
+                call_id: call_id.clone(),
                 changes: convert_apply_patch_to_protocol(action),
-                reason,
+                reason: reason.clone(),
                 grant_root,
             }),
         };
         let _ = self.tx_event.send(event).await;
-        {
-            let mut state = self.state.lock_unchecked();
-            state.pending_approvals.insert(sub_id, tx_approve);
-        }
+        self.maybe_notify(UserNotification::ApprovalRequested {
+            turn_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            summary: patch_summary(action.changes().keys().map(PathBuf::as_path)),
+            reason: reason.clone(),
+        });
+        let mut paths: Vec<PathBuf> = action.changes().keys().cloned().collect();
+        paths.sort();
+        self.add_pending_approval(
+            sub_id,
+            tx_approve,
+            call_id.clone(),
+            ApprovalSubject::Patch { paths },
+        );
+        self.hooks
+            .notify(
+                HookEvent::ApprovalRequest {
+                    call_id,
+                    command: None,
+                    changes: Some(convert_apply_patch_to_protocol(action)),
+                    reason,
+                },
+                &turn_context.cwd,
+            )
+            .await;
         rx_approve
     }
 
-    pub fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
-        let entry = {
-            let mut state = self.state.lock_unchecked();
-            state.pending_approvals.remove(sub_id)
+    pub async fn request_mcp_tool_approval(
+        &self,
+        turn_context: &TurnContext,
+        sub_id: String,
+        call_id: String,
+        invocation: McpInvocation,
+        reason: Option<String>,
+    ) -> oneshot::Receiver<ReviewDecision> {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let summary = format!("{}/{}", invocation.server, invocation.tool);
+        let subject = ApprovalSubject::McpTool {
+            server: invocation.server.clone(),
+            tool: invocation.tool.clone(),
+            arguments: invocation.arguments.clone(),
         };
+        let event = Event {
+            id: sub_id.clone(),
+            msg: EventMsg::McpToolApprovalRequest(McpToolApprovalRequestEvent {
+                call_id: call_id.clone(),
+                invocation,
+                reason: reason.clone(),
+            }),
+        };
+        let _ = self.tx_event.send(event).await;
+        self.maybe_notify(UserNotification::ApprovalRequested {
+            turn_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            summary,
+            reason: reason.clone(),
+        });
+        self.add_pending_approval(sub_id, tx_approve, call_id.clone(), subject);
+        self.hooks
+            .notify(
+                HookEvent::ApprovalRequest {
+                    call_id,
+                    command: None,
+                    changes: None,
+                    reason,
+                },
+                &turn_context.cwd,
+            )
+            .await;
+        rx_approve
+    }
+
+    /// Whether `server` annotates `tool` as read-only, for display only.
+    pub(crate) fn mcp_tool_read_only_hint(&self, server: &str, tool: &str) -> bool {
+        self.mcp_connection_manager
+            .tool_read_only_hint(server, tool)
+    }
+
+    /// Whether calling `tool` of `server` needs the user's approval: the
+    /// configuration requires it and the user has not approved the tool for
+    /// the rest of the session.
+    pub(crate) fn mcp_tool_requires_approval(&self, server: &str, tool: &str) -> bool {
+        self.mcp_connection_manager
+            .tool_requires_approval(server, tool)
+            && !self
+                .state
+                .lock_unchecked()
+                .approved_mcp_tools
+                .contains(&(server.to_string(), tool.to_string()))
+    }
+
+    pub(crate) fn approve_mcp_tool_for_session(&self, server: &str, tool: &str) {
+        self.state
+            .lock_unchecked()
+            .approved_mcp_tools
+            .insert((server.to_string(), tool.to_string()));
+    }
+
+    fn add_pending_approval(
+        &self,
+        sub_id: String,
+        tx_approve: oneshot::Sender<ReviewDecision>,
+        call_id: String,
+        subject: ApprovalSubject,
+    ) {
+        self.state.lock_unchecked().pending_approvals.insert(
+            sub_id,
+            PendingApproval {
+                tx_approve,
+                call_id,
+                subject,
+            },
+        );
+    }
+
+    /// Deliver the user's `decision` on the approval requested by `sub_id`
+    /// and record it in the audit log. `Abort` interrupts the task instead.
+    pub async fn resolve_approval(&self, sub_id: &str, decision: ReviewDecision) {
+        let entry = self.state.lock_unchecked().pending_approvals.remove(sub_id);
         match entry {
-            Some(tx_approve) => {
-                tx_approve.send(decision).ok();
+            Some(PendingApproval {
+                tx_approve,
+                call_id,
+                subject,
+            }) => {
+                self.audit(AuditEvent::ApprovalDecision {
+                    id: sub_id.to_string(),
+                    call_id,
+                    subject,
+                    decision,
+                })
+                .await;
+                if decision != ReviewDecision::Abort {
+                    tx_approve.send(decision).ok();
+                }
             }
             None => {
                 warn!("No pending approval found for sub_id: {sub_id}");
             }
         }
+        if decision == ReviewDecision::Abort {
+            self.interrupt_task();
+        }
     }
 
-    pub fn add_approved_command(&self, cmd: Vec<String>) {
-        let mut state = self.state.lock_unchecked();
-        state.approved_commands.insert(cmd);
+    /// Record the grant that `decision` makes for `cmd`, and the approval in
+    /// the approval history so `codex policy suggest` can learn from it.
+    pub async fn grant_approval(&self, decision: ReviewDecision, cmd: Vec<String>, cwd: &Path) {
+        if !self
+            .state
+            .lock_unchecked()
+            .approval_grants
+            .grant(decision, &cmd, cwd)
+        {
+            return;
+        }
+        if let Some(path) = &self.approval_history
+            && let Err(e) =
+                append_approval(path.clone(), ApprovalRecord::new(cwd.to_path_buf(), cmd)).await
+        {
+            error!("failed to record approval: {e:#}");
+        }
     }
 
     /// Records items to both the rollout and the chat completions/ZDR
@@ -685,8 +1072,128 @@ impl Session {
         self.state.lock_unchecked().history.record_items(items);
     }
 
+    /// Add `finding` to the session's findings and return its 1-based number,
+    /// or the number it was already recorded under if the same rule was
+    /// reported for the same location before.
+    pub(crate) fn record_finding(&self, finding: Finding) -> Result<usize, usize> {
+        let mut state = self.state.lock_unchecked();
+        if let Some(index) = state.findings.iter().position(|existing| {
+            existing.rule_id == finding.rule_id
+                && existing.path == finding.path
+                && existing.start_line == finding.start_line
+                && existing.end_line == finding.end_line
+        }) {
+            return Err(index + 1);
+        }
+        state.findings.push(finding);
+        Ok(state.findings.len())
+    }
+
+    /// Append `event` to the audit log, if enabled.
+    pub(crate) async fn audit(&self, event: AuditEvent) {
+        if let Some(audit_log) = &self.audit_log
+            && let Err(e) = audit_log.append(event).await
+        {
+            error!("failed to write audit record: {e:#}");
+        }
+    }
+
+    /// Take the checkpoint that `Op::UndoTurn` rolls back to, before the turn
+    /// `sub_id` records anything.
+    fn begin_checkpoint(&self, sub_id: &str) {
+        let history_len = self.state.lock_unchecked().history.len();
+        let rollout_len = self
+            .rollout
+            .lock_unchecked()
+            .as_ref()
+            .map_or(0, RolloutRecorder::items_recorded);
+        if let Err(e) =
+            self.checkpoints
+                .lock_unchecked()
+                .begin_turn(sub_id, history_len, rollout_len)
+        {
+            warn!("failed to write checkpoint: {e}");
+        }
+    }
+
+    /// Restore the workspace and conversation to their state before the last
+    /// `turns` turns.
+    async fn undo_turns(&self, sub_id: &str, turns: u32) {
+        let outcome = if self.state.lock_unchecked().current_task.is_some() {
+            Err("Cannot undo while a turn is in progress.".to_string())
+        } else {
+            self.checkpoints.lock_unchecked().undo(turns as usize)
+        };
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(message) => {
+                self.send_event(Event {
+                    id: sub_id.to_string(),
+                    msg: EventMsg::Error(ErrorEvent { message }),
+                })
+                .await;
+                return;
+            }
+        };
+
+        {
+            let mut state = self.state.lock_unchecked();
+            state.history.truncate(outcome.history_len);
+            state.last_token_usage = None;
+        }
+        let recorder = self.rollout.lock_unchecked().as_ref().cloned();
+        if let Some(rec) = recorder
+            && let Err(e) = rec.truncate(outcome.rollout_len).await
+        {
+            error!("failed to truncate rollout: {e:#}");
+        }
+
+        for message in outcome.errors {
+            self.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            })
+            .await;
+        }
+        if outcome.summarized {
+            self.notify_background_event(
+                sub_id,
+                "The conversation was compacted since the undone turns; it was rewound to the summary, which still mentions them.",
+            )
+            .await;
+        }
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::TurnUndone(TurnUndoneEvent {
+                turns,
+                restored_paths: outcome.restored_paths,
+            }),
+        })
+        .await;
+    }
+
+    /// Adds one model request to the session's totals, reports them to the
+    /// client and persists them to the rollout.
+    async fn record_token_usage(&self, sub_id: &str, model: &str, token_usage: &TokenUsage) {
+        let cost_usd =
+            pricing_for_model(&self.model_pricing, model).map(|pricing| pricing.cost(token_usage));
+        let usage = {
+            let mut state = self.state.lock_unchecked();
+            state.usage.add_request(model, token_usage, cost_usd);
+            state.usage.clone()
+        };
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::SessionUsage(SessionUsageEvent { usage }),
+        })
+        .await;
+        self.record_state_snapshot(&[]).await;
+    }
+
     async fn record_state_snapshot(&self, items: &[ResponseItem]) {
-        let snapshot = { crate::rollout::SessionStateSnapshot {} };
+        let snapshot = crate::rollout::SessionStateSnapshot {
+            usage: self.state.lock_unchecked().usage.clone(),
+        };
 
         let recorder = {
             let guard = self.rollout.lock_unchecked();
@@ -721,6 +1228,13 @@ impl Session {
                 changes,
             }) => {
                 turn_diff_tracker.on_patch_begin(&changes);
+                if let Err(e) = self
+                    .checkpoints
+                    .lock_unchecked()
+                    .record_baselines(&sub_id, turn_diff_tracker.baselines())
+                {
+                    warn!("failed to update checkpoint: {e}");
+                }
 
                 EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                     call_id,
@@ -854,9 +1368,68 @@ impl Session {
         )
         .await;
 
+        let audit_event = match begin_ctx.apply_patch {
+            Some(ApplyPatchCommandContext { changes, .. }) => {
+                let mut paths = Vec::new();
+                for (path, change) in changes {
+                    if let FileChange::Update {
+                        move_path: Some(dest),
+                        ..
+                    } = change
+                    {
+                        paths.push(dest);
+                    }
+                    paths.push(path);
+                }
+                paths.sort();
+                AuditEvent::PatchApplied {
+                    call_id,
+                    paths,
+                    success: borrowed.exit_code == 0,
+                }
+            }
+            None => AuditEvent::ExecCommand {
+                call_id,
+                command: begin_ctx.command_for_display,
+                cwd: begin_ctx.cwd,
+                exit_code: borrowed.exit_code,
+                duration_ms: u64::try_from(borrowed.duration.as_millis()).unwrap_or(u64::MAX),
+            },
+        };
+        self.audit(audit_event).await;
+
         result
     }
 
+    /// Client for the provider currently serving this session's requests.
+    fn model_client(&self, turn_context: &TurnContext) -> ModelClient {
+        match self.provider_failover.lock_unchecked().active_fallback() {
+            Some(fallback) => turn_context.client.with_fallback(fallback),
+            None => turn_context.client.clone(),
+        }
+    }
+
+    /// Marks the active provider healthy and notes in the rollout that it
+    /// served this turn.
+    async fn record_turn_provider(&self, client: &ModelClient) {
+        let provider = {
+            let mut failover = self.provider_failover.lock_unchecked();
+            failover.record_success();
+            failover.active_provider_id().to_string()
+        };
+        let recorder = {
+            let guard = self.rollout.lock_unchecked();
+            guard.as_ref().cloned()
+        };
+        if let Some(rec) = recorder
+            && let Err(e) = rec
+                .record_turn_provider(&provider, &client.get_model())
+                .await
+        {
+            error!("failed to record turn provider: {e:#}");
+        }
+    }
+
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
@@ -871,15 +1444,34 @@ impl Session {
     }
 
     async fn notify_stream_error(&self, sub_id: &str, message: impl Into<String>) {
+        let message = message.into();
+        self.maybe_notify(UserNotification::StreamError {
+            turn_id: sub_id.to_string(),
+            message: message.clone(),
+        });
         let event = Event {
             id: sub_id.to_string(),
-            msg: EventMsg::StreamError(StreamErrorEvent {
-                message: message.into(),
-            }),
+            msg: EventMsg::StreamError(StreamErrorEvent { message }),
         };
         let _ = self.tx_event.send(event).await;
     }
 
+    /// The user instructions and environment context that open every
+    /// conversation.
+    fn build_initial_context(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
+        let mut items = Vec::<ResponseItem>::with_capacity(2);
+        if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
+            items.push(Prompt::format_user_instructions_message(user_instructions));
+        }
+        items.push(ResponseItem::from(EnvironmentContext::new(
+            Some(turn_context.cwd.clone()),
+            Some(turn_context.approval_policy),
+            Some(turn_context.sandbox_policy.clone()),
+            Some(self.user_shell.clone()),
+        )));
+        items
+    }
+
     /// Build the full turn input by concatenating the current conversation
     /// history with additional items for this turn.
     pub fn turn_input_with_history(&self, extra: Vec<ResponseItem>) -> Vec<ResponseItem> {
@@ -890,22 +1482,34 @@ impl Session {
     pub fn inject_input(&self, input: Vec<InputItem>) -> Result<(), Vec<InputItem>> {
         let mut state = self.state.lock_unchecked();
         if state.current_task.is_some() {
-            state.pending_input.push(input.into());
+            state.pending_input.push(input);
             Ok(())
         } else {
             Err(input)
         }
     }
 
-    pub fn get_pending_input(&self) -> Vec<ResponseInputItem> {
-        let mut state = self.state.lock_unchecked();
-        if state.pending_input.is_empty() {
-            Vec::with_capacity(0)
-        } else {
-            let mut ret = Vec::new();
-            std::mem::swap(&mut ret, &mut state.pending_input);
-            ret
+    /// Replaces the MCP resources and prompts of `input` with their contents,
+    /// reporting the ones that could not be resolved as background events.
+    async fn resolve_mcp_input(&self, sub_id: &str, input: Vec<InputItem>) -> Vec<InputItem> {
+        let (input, errors) = resolve_mcp_input(&self.mcp_connection_manager, input).await;
+        for error in errors {
+            warn!("{error}");
+            self.notify_background_event(sub_id, error).await;
         }
+        input
+    }
+
+    pub async fn get_pending_input(&self, sub_id: &str) -> Vec<ResponseInputItem> {
+        let pending = std::mem::take(&mut self.state.lock_unchecked().pending_input);
+        let mut ret = Vec::with_capacity(pending.len());
+        for input in pending {
+            let input = self.resolve_mcp_input(sub_id, input).await;
+            if !input.is_empty() {
+                ret.push(input.into());
+            }
+        }
+        ret
     }
 
     pub async fn call_tool(
@@ -931,12 +1535,16 @@ impl Session {
     }
 
     /// Spawn the configured notifier (if any) with the given JSON payload as
-    /// the last argument. Failures are logged but otherwise ignored so that
-    /// notification issues do not interfere with the main workflow.
+    /// the last argument, if `notify_on` selects the notification's type.
+    /// Failures are logged but otherwise ignored so that notification issues
+    /// do not interfere with the main workflow.
     fn maybe_notify(&self, notification: UserNotification) {
         let Some(notify_command) = &self.notify else {
             return;
         };
+        if !self.notify_on.contains(&notification.kind()) {
+            return;
+        }
 
         if notify_command.is_empty() {
             return;
@@ -1071,6 +1679,16 @@ async fn submission_loop(
                 effort,
                 summary,
             } => {
+                sess.audit(AuditEvent::ConfigOverride {
+                    cwd: cwd.clone(),
+                    approval_policy,
+                    sandbox_policy: sandbox_policy.clone(),
+                    model: model.clone(),
+                    effort,
+                    summary,
+                })
+                .await;
+
                 // Recalculate the persistent turn context with provided overrides.
                 let prev = Arc::clone(&turn_context);
                 let provider = prev.client.get_provider();
@@ -1118,6 +1736,7 @@ async fn submission_loop(
                     approval_policy: new_approval_policy,
                     sandbox_policy: new_sandbox_policy.clone(),
                     include_plan_tool: config.include_plan_tool,
+                    include_finding_tool: config.include_finding_tool,
                     include_apply_patch_tool: config.include_apply_patch_tool,
                     include_web_search_request: config.tools_web_search_request,
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
@@ -1134,6 +1753,7 @@ async fn submission_loop(
                     shell_environment_policy: prev.shell_environment_policy.clone(),
                     cwd: new_cwd.clone(),
                     disable_response_storage: prev.disable_response_storage,
+                    task_budget: prev.task_budget,
                 };
 
                 // Install the new persistent context for subsequent tasks/turns.
@@ -1150,6 +1770,9 @@ async fn submission_loop(
                 }
             }
             Op::UserInput { items } => {
+                if items.is_empty() {
+                    continue;
+                }
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
                     // no current task, spawn a new one
@@ -1166,7 +1789,11 @@ async fn submission_loop(
                 model,
                 effort,
                 summary,
+                budget,
             } => {
+                if items.is_empty() {
+                    continue;
+                }
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
                     // Derive a fresh TurnContext for this turn using the provided overrides.
@@ -1203,6 +1830,7 @@ async fn submission_loop(
                             approval_policy,
                             sandbox_policy: sandbox_policy.clone(),
                             include_plan_tool: config.include_plan_tool,
+                            include_finding_tool: config.include_finding_tool,
                             include_apply_patch_tool: config.include_apply_patch_tool,
                             include_web_search_request: config.tools_web_search_request,
                             use_streamable_shell_tool: config
@@ -1216,6 +1844,7 @@ async fn submission_loop(
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
                         cwd,
                         disable_response_storage: turn_context.disable_response_storage,
+                        task_budget: budget.unwrap_or_default().or(turn_context.task_budget),
                     };
                     // TODO: record the new environment context in the conversation history
                     // no current task, spawn a new one with the per‑turn context
@@ -1224,18 +1853,41 @@ async fn submission_loop(
                     sess.set_task(task);
                 }
             }
-            Op::ExecApproval { id, decision } => match decision {
-                ReviewDecision::Abort => {
-                    sess.interrupt_task();
-                }
-                other => sess.notify_approval(&id, other),
-            },
-            Op::PatchApproval { id, decision } => match decision {
-                ReviewDecision::Abort => {
-                    sess.interrupt_task();
+            Op::ExecApproval { id, decision }
+            | Op::PatchApproval { id, decision }
+            | Op::McpToolApproval { id, decision } => {
+                sess.resolve_approval(&id, decision).await;
+            }
+            Op::TrustMcpTools { server } => {
+                match sess.mcp_connection_manager.trust_tools(&server).await {
+                    Ok(tools) if tools.is_empty() => {
+                        sess.notify_background_event(
+                            &sub.id,
+                            format!("MCP server `{server}` has no changed tools to trust"),
+                        )
+                        .await;
+                    }
+                    Ok(tools) => {
+                        sess.notify_background_event(
+                            &sub.id,
+                            format!(
+                                "Trusted the new definitions of {} from MCP server `{server}`",
+                                tools.join(", ")
+                            ),
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        sess.send_event(Event {
+                            id: sub.id.clone(),
+                            msg: EventMsg::Error(ErrorEvent {
+                                message: format!("{e:#}"),
+                            }),
+                        })
+                        .await;
+                    }
                 }
-                other => sess.notify_approval(&id, other),
-            },
+            }
             Op::AddToHistory { text } => {
                 let id = sess.session_id;
                 let config = config.clone();
@@ -1288,16 +1940,45 @@ async fn submission_loop(
 
                 // This is a cheap lookup from the connection manager's cache.
                 let tools = sess.mcp_connection_manager.list_all_tools();
+                let servers = sess.mcp_connection_manager.server_statuses();
                 let event = Event {
                     id: sub_id,
                     msg: EventMsg::McpListToolsResponse(
-                        crate::protocol::McpListToolsResponseEvent { tools },
+                        crate::protocol::McpListToolsResponseEvent { tools, servers },
                     ),
                 };
                 if let Err(e) = tx_event.send(event).await {
                     warn!("failed to send McpListToolsResponse event: {e}");
                 }
             }
+            Op::ListMcpResources => {
+                let resources = sess.mcp_connection_manager.list_all_resources().await;
+                sess.send_event(Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::McpListResourcesResponse(McpListResourcesResponseEvent {
+                        resources,
+                    }),
+                })
+                .await;
+            }
+            Op::ListMcpPrompts => {
+                let prompts = sess.mcp_connection_manager.list_all_prompts().await;
+                sess.send_event(Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::McpListPromptsResponse(McpListPromptsResponseEvent { prompts }),
+                })
+                .await;
+            }
+            Op::ListApprovalGrants => {
+                let grants = sess.state.lock_unchecked().approval_grants.active();
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::ListApprovalGrantsResponse(ListApprovalGrantsResponseEvent {
+                        grants,
+                    }),
+                };
+                sess.send_event(event).await;
+            }
             Op::ListCustomPrompts => {
                 let tx_event = sess.tx_event.clone();
                 let sub_id = sub.id.clone();
@@ -1320,9 +2001,6 @@ async fn submission_loop(
                 }
             }
             Op::Compact => {
-                // Create a summarization request as user input
-                const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
-
                 // Attempt to inject input into current task
                 if let Err(items) = sess.inject_input(vec![InputItem::Text {
                     text: "Start Summarization".to_string(),
@@ -1337,8 +2015,14 @@ async fn submission_loop(
                     sess.set_task(task);
                 }
             }
+            Op::UndoTurn { turns } => {
+                sess.undo_turns(&sub.id, turns).await;
+            }
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
+                sess.hooks
+                    .notify(HookEvent::SessionEnd, &turn_context.cwd)
+                    .await;
 
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
@@ -1390,6 +2074,8 @@ async fn submission_loop(
     debug!("Agent loop exited");
 }
 
+const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
+
 /// Takes a user message as input and runs a loop where, at each turn, the model
 /// replies with either:
 ///
@@ -1422,9 +2108,50 @@ async fn run_task(
         return;
     }
 
-    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
-    sess.record_conversation_items(&[initial_input_for_turn.clone().into()])
+    // Reading MCP resources and prompts can take a while, so it happens here
+    // rather than in the submission loop, where it would hold up interrupts
+    // and approvals.
+    let input = sess.resolve_mcp_input(&sub_id, input).await;
+    if input.is_empty() {
+        sess.remove_task(&sub_id);
+        sess.send_event(Event {
+            id: sub_id,
+            msg: EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: None,
+            }),
+        })
         .await;
+        return;
+    }
+    sess.maybe_notify(UserNotification::TaskStarted {
+        turn_id: sub_id.clone(),
+        input_messages: input
+            .iter()
+            .filter_map(|item| match item {
+                InputItem::Text { text } => Some(text.clone()),
+                _ => None,
+            })
+            .collect(),
+    });
+
+    let mut budget = BudgetTracker::new(turn_context.task_budget);
+    // Compact before recording the new input so that it is never folded into
+    // the summary. Compaction counts against the task's time budget.
+    let remaining_time = budget.remaining_time();
+    let compaction = maybe_auto_compact(&sess, turn_context, &sub_id, &mut budget);
+    if within_time_budget(remaining_time, compaction)
+        .await
+        .is_none()
+    {
+        abort_task_over_budget(&sess, &sub_id, budget.duration_exceeded()).await;
+        return;
+    }
+    sess.begin_checkpoint(&sub_id);
+
+    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
+    let mut initial_items = [initial_input_for_turn.into()];
+    sess.redact_secrets(&sub_id, &mut initial_items).await;
+    sess.record_conversation_items(&initial_items).await;
 
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
@@ -1435,11 +2162,13 @@ async fn run_task(
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
-        let pending_input = sess
-            .get_pending_input()
+        let mut pending_input = sess
+            .get_pending_input(&sub_id)
+            .await
             .into_iter()
             .map(ResponseItem::from)
             .collect::<Vec<ResponseItem>>();
+        sess.redact_secrets(&sub_id, &mut pending_input).await;
         sess.record_conversation_items(&pending_input).await;
 
         // Construct the input that we will send to the model. When using the
@@ -1462,15 +2191,20 @@ async fn run_task(
                 })
             })
             .collect();
-        match run_turn(
+        let remaining_time = budget.remaining_time();
+        let turn = run_turn(
             &sess,
             turn_context,
             &mut turn_diff_tracker,
+            &mut budget,
             sub_id.clone(),
             turn_input,
-        )
-        .await
-        {
+        );
+        let Some(turn_result) = within_time_budget(remaining_time, turn).await else {
+            abort_task_over_budget(&sess, &sub_id, budget.duration_exceeded()).await;
+            return;
+        };
+        match turn_result {
             Ok(turn_output) => {
                 let mut items_to_record_in_conversation_history = Vec::<ResponseItem>::new();
                 let mut responses = Vec::<ResponseInputItem>::new();
@@ -1567,6 +2301,8 @@ async fn run_task(
 
                 // Only attempt to take the lock if there is something to record.
                 if !items_to_record_in_conversation_history.is_empty() {
+                    sess.redact_secrets(&sub_id, &mut items_to_record_in_conversation_history)
+                        .await;
                     sess.record_conversation_items(&items_to_record_in_conversation_history)
                         .await;
                 }
@@ -1581,11 +2317,39 @@ async fn run_task(
                         input_messages: turn_input_messages,
                         last_assistant_message: last_agent_message.clone(),
                     });
+                    sess.hooks
+                        .notify(
+                            HookEvent::TurnComplete {
+                                turn_id: sub_id.clone(),
+                                last_assistant_message: last_agent_message.clone(),
+                            },
+                            &turn_context.cwd,
+                        )
+                        .await;
                     break;
                 }
+
+                if let Some(exceeded) = budget.exceeded() {
+                    abort_task_over_budget(&sess, &sub_id, exceeded).await;
+                    return;
+                }
+
+                let remaining_time = budget.remaining_time();
+                let compaction = maybe_auto_compact(&sess, turn_context, &sub_id, &mut budget);
+                if within_time_budget(remaining_time, compaction)
+                    .await
+                    .is_none()
+                {
+                    abort_task_over_budget(&sess, &sub_id, budget.duration_exceeded()).await;
+                    return;
+                }
             }
             Err(e) => {
                 info!("Turn error: {e:#}");
+                sess.maybe_notify(UserNotification::TaskError {
+                    turn_id: sub_id.clone(),
+                    message: e.to_string(),
+                });
                 let event = Event {
                     id: sub_id.clone(),
                     msg: EventMsg::Error(ErrorEvent {
@@ -1606,10 +2370,43 @@ async fn run_task(
     sess.tx_event.send(event).await.ok();
 }
 
+/// Runs `future` for at most `remaining`, the time left in the task's budget.
+/// `None` when the time ran out first.
+async fn within_time_budget<T>(
+    remaining: Option<Duration>,
+    future: impl Future<Output = T>,
+) -> Option<T> {
+    match remaining {
+        Some(remaining) => tokio::time::timeout(remaining, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Stops a task that used up part of its budget. The tool calls it made so
+/// far, and their outputs, stay in the conversation history; approvals it was
+/// still waiting for are dropped.
+async fn abort_task_over_budget(sess: &Session, sub_id: &str, exceeded: BudgetExceeded) {
+    info!("task {sub_id} stopped: {exceeded}");
+    sess.state.lock_unchecked().pending_approvals.clear();
+    sess.remove_task(sub_id);
+    sess.maybe_notify(UserNotification::BudgetExceeded {
+        turn_id: sub_id.to_string(),
+        message: exceeded.to_string(),
+    });
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            reason: TurnAbortReason::BudgetExceeded(exceeded),
+        }),
+    })
+    .await;
+}
+
 async fn run_turn(
     sess: &Session,
     turn_context: &TurnContext,
     turn_diff_tracker: &mut TurnDiffTracker,
+    budget: &mut BudgetTracker,
     sub_id: String,
     input: Vec<ResponseItem>,
 ) -> CodexResult<Vec<ProcessedResponseItem>> {
@@ -1627,16 +2424,54 @@ async fn run_turn(
 
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
-            Ok(output) => return Ok(output),
+        let client = sess.model_client(turn_context);
+        match try_run_turn(
+            sess,
+            turn_context,
+            &client,
+            turn_diff_tracker,
+            budget,
+            &sub_id,
+            &prompt,
+        )
+        .await
+        {
+            Ok(output) => {
+                sess.record_turn_provider(&client).await;
+                return Ok(output);
+            }
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(e @ (CodexErr::UsageLimitReached(_) | CodexErr::UsageNotIncluded)) => {
+            Err(
+                e @ (CodexErr::UsageLimitReached(_)
+                | CodexErr::UsageNotIncluded
+                | CodexErr::UnsupportedTool(_)),
+            ) => {
                 return Err(e);
             }
             Err(e) => {
+                let switch = if is_provider_failure(&e) {
+                    sess.provider_failover.lock_unchecked().record_failure()
+                } else {
+                    None
+                };
+                if let Some(ProviderSwitch { from, to, failures }) = switch {
+                    warn!("model provider `{from}` failed {failures} times in a row: {e}");
+                    sess.notify_background_event(
+                        &sub_id,
+                        format!(
+                            "model provider `{from}` failed {failures} times in a row; switching to `{}` ({})",
+                            to.model_provider_id, to.model
+                        ),
+                    )
+                    .await;
+                    // The fallback gets its own retry budget.
+                    retries = 0;
+                    continue;
+                }
+
                 // Use the configured provider-specific stream retry budget.
-                let max_retries = turn_context.client.get_provider().stream_max_retries();
+                let max_retries = client.get_provider().stream_max_retries();
                 if retries < max_retries {
                     retries += 1;
                     let delay = match e {
@@ -1680,7 +2515,9 @@ struct ProcessedResponseItem {
 async fn try_run_turn(
     sess: &Session,
     turn_context: &TurnContext,
+    client: &ModelClient,
     turn_diff_tracker: &mut TurnDiffTracker,
+    budget: &mut BudgetTracker,
     sub_id: &str,
     prompt: &Prompt,
 ) -> CodexResult<Vec<ProcessedResponseItem>> {
@@ -1739,7 +2576,7 @@ async fn try_run_turn(
         })
     };
 
-    let mut stream = turn_context.client.clone().stream(&prompt).await?;
+    let mut stream = client.stream(&prompt).await?;
 
     let mut output = Vec::new();
 
@@ -1769,14 +2606,24 @@ async fn try_run_turn(
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
-                let response = handle_response_item(
-                    sess,
-                    turn_context,
-                    turn_diff_tracker,
-                    sub_id,
-                    item.clone(),
-                )
-                .await?;
+                // Tool calls past the task's limit are answered without being
+                // run; the task stops once the turn completes.
+                let response = match (is_tool_call(&item), budget.tool_calls_exceeded()) {
+                    (true, Some(exceeded)) => tool_call_over_budget_output(&item, &exceeded),
+                    (is_tool_call, _) => {
+                        if is_tool_call {
+                            budget.record_tool_call();
+                        }
+                        handle_response_item(
+                            sess,
+                            turn_context,
+                            turn_diff_tracker,
+                            sub_id,
+                            item.clone(),
+                        )
+                        .await?
+                    }
+                };
                 output.push(ProcessedResponseItem { item, response });
             }
             ResponseEvent::WebSearchCallBegin { call_id } => {
@@ -1792,14 +2639,18 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
+                sess.state.lock_unchecked().last_token_usage = token_usage.clone();
                 if let Some(token_usage) = token_usage {
+                    budget.record_usage(&token_usage);
                     sess.tx_event
                         .send(Event {
                             id: sub_id.to_string(),
-                            msg: EventMsg::TokenCount(token_usage),
+                            msg: EventMsg::TokenCount(token_usage.clone()),
                         })
                         .await
                         .ok();
+                    sess.record_token_usage(sub_id, &client.get_model(), &token_usage)
+                        .await;
                 }
 
                 let unified_diff = turn_diff_tracker.get_unified_diff();
@@ -1883,10 +2734,16 @@ async fn run_compact_task(
     let mut retries = 0;
 
     loop {
-        let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt).await;
+        let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt, None).await;
 
         match attempt_result {
-            Ok(()) => break,
+            Ok(items) => {
+                sess.state
+                    .lock_unchecked()
+                    .history
+                    .record_items(items.iter());
+                break;
+            }
             Err(CodexErr::Interrupted) => return,
             Err(e) => {
                 if retries < max_retries {
@@ -1917,10 +2774,16 @@ async fn run_compact_task(
 
     sess.remove_task(&sub_id);
 
-    {
+    let (summarized_len, summary_len) = {
         let mut state = sess.state.lock_unchecked();
+        let summarized_len = state.history.len();
         state.history.keep_last_messages(1);
-    }
+        state.last_token_usage = None;
+        (summarized_len, state.history.len())
+    };
+    sess.checkpoints
+        .lock_unchecked()
+        .history_compacted(summarized_len, summary_len);
 
     let event = Event {
         id: sub_id.clone(),
@@ -1938,6 +2801,156 @@ async fn run_compact_task(
     sess.send_event(event).await;
 }
 
+/// Summarizes older conversation history when the previous request came close
+/// to filling the model's context window, so that the next request does not
+/// overflow it. The most recent turns are kept verbatim.
+async fn maybe_auto_compact(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    budget: &mut BudgetTracker,
+) {
+    let auto_compact = &sess.auto_compact;
+    if !auto_compact.enabled {
+        return;
+    }
+    let Some(model_context_window) = turn_context.client.get_model_context_window() else {
+        return;
+    };
+    let Some(tokens_before) = sess
+        .state
+        .lock_unchecked()
+        .last_token_usage
+        .as_ref()
+        .map(TokenUsage::tokens_in_context_window)
+    else {
+        return;
+    };
+    let threshold =
+        model_context_window.saturating_mul(u64::from(auto_compact.threshold_percent)) / 100;
+    if tokens_before < threshold {
+        return;
+    }
+
+    info!("auto-compacting conversation: {tokens_before} of {model_context_window} tokens used");
+    let instructions = auto_compact
+        .prompt
+        .clone()
+        .unwrap_or_else(|| SUMMARIZATION_PROMPT.to_string());
+    let request = ResponseItem::from(ResponseInputItem::from(vec![InputItem::Text {
+        text: "Start Summarization".to_string(),
+    }]));
+    let prompt = Prompt {
+        input: sess.turn_input_with_history(vec![request]),
+        store: !turn_context.disable_response_storage,
+        tools: Vec::new(),
+        base_instructions_override: Some(instructions),
+    };
+
+    let summary = match drain_to_completed(sess, turn_context, sub_id, &prompt, Some(budget)).await
+    {
+        Ok(items) => get_last_assistant_message_from_turn(&items),
+        Err(e) => {
+            warn!("auto-compaction failed: {e:#}");
+            None
+        }
+    };
+    let Some(summary) = summary else {
+        sess.notify_background_event(
+            sub_id,
+            "Context window is nearly full, but the conversation could not be summarized.",
+        )
+        .await;
+        return;
+    };
+
+    let mut replacement = sess.build_initial_context(turn_context);
+    replacement.push(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!("Summary of the conversation so far:\n\n{summary}"),
+        }],
+    });
+    let summary_len = replacement.len();
+    let (kept_turns, summarized_len) = {
+        let mut state = sess.state.lock_unchecked();
+        state.last_token_usage = None;
+        let len_before = state.history.len();
+        let kept_turns = state
+            .history
+            .compact_with_summary(replacement, auto_compact.keep_recent_turns);
+        (kept_turns, len_before + summary_len - state.history.len())
+    };
+    sess.checkpoints
+        .lock_unchecked()
+        .history_compacted(summarized_len, summary_len);
+
+    sess.maybe_notify(UserNotification::ContextCompacted {
+        turn_id: sub_id.to_string(),
+        tokens_before,
+        kept_turns,
+    });
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::ContextCompacted(ContextCompactedEvent {
+            tokens_before,
+            model_context_window,
+            kept_turns,
+        }),
+    })
+    .await;
+}
+
+fn is_tool_call(item: &ResponseItem) -> bool {
+    matches!(
+        item,
+        ResponseItem::FunctionCall { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::CustomToolCall { .. }
+    )
+}
+
+/// The output for a tool call that was not run because the task has made as
+/// many tool calls as its budget allows.
+fn tool_call_over_budget_output(
+    item: &ResponseItem,
+    exceeded: &BudgetExceeded,
+) -> Option<ResponseInputItem> {
+    let content = format!("tool call not run: {exceeded}");
+    match item {
+        ResponseItem::FunctionCall { call_id, .. }
+        | ResponseItem::LocalShellCall {
+            call_id: Some(call_id),
+            ..
+        } => Some(ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.clone(),
+            output: FunctionCallOutputPayload {
+                content,
+                success: Some(false),
+            },
+        }),
+        ResponseItem::LocalShellCall {
+            call_id: None,
+            id: Some(id),
+            ..
+        } => Some(ResponseInputItem::FunctionCallOutput {
+            call_id: id.clone(),
+            output: FunctionCallOutputPayload {
+                content,
+                success: Some(false),
+            },
+        }),
+        ResponseItem::CustomToolCall { call_id, .. } => {
+            Some(ResponseInputItem::CustomToolCallOutput {
+                call_id: call_id.clone(),
+                output: content,
+            })
+        }
+        _ => None,
+    }
+}
+
 async fn handle_response_item(
     sess: &Session,
     turn_context: &TurnContext,
@@ -2188,6 +3201,7 @@ async fn handle_function_call(
             .await
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
+        REPORT_FINDING_TOOL_NAME => handle_report_finding(sess, arguments, sub_id, call_id).await,
         EXEC_COMMAND_TOOL_NAME => {
             // TODO(mbolin): Sandbox check.
             let exec_params = match serde_json::from_str::<ExecCommandParams>(&arguments) {
@@ -2202,11 +3216,47 @@ async fn handle_function_call(
                     };
                 }
             };
+            let pre_exec = HookEvent::PreExec {
+                call_id: call_id.clone(),
+                command: vec![exec_params.cmd.clone()],
+                cwd: turn_context.cwd.clone(),
+            };
+            if let Err(failure) = sess.hooks.run(pre_exec, &turn_context.cwd).await {
+                return hook_blocked_output(sess, &sub_id, call_id, failure, "command").await;
+            }
+            sess.audit(AuditEvent::ExecSessionCommand {
+                call_id: call_id.clone(),
+                cmd: exec_params.cmd.clone(),
+            })
+            .await;
+            let command = vec![exec_params.cmd.clone()];
             let result = sess
                 .session_manager
                 .handle_exec_command_request(exec_params)
                 .await;
-            let function_call_output = crate::exec_command::result_into_payload(result);
+            let post_exec = match &result {
+                Ok(output) => output.exit_code().map(|exit_code| HookEvent::PostExec {
+                    call_id: call_id.clone(),
+                    command,
+                    cwd: turn_context.cwd.clone(),
+                    exit_code,
+                    output: output.output().to_string(),
+                }),
+                Err(_) => None,
+            };
+            let mut function_call_output = crate::exec_command::result_into_payload(result);
+            // A session that is still running has not finished yet, so there
+            // is nothing for `post_exec` to look at.
+            if let Some(post_exec) = post_exec
+                && let Err(failure) = sess.hooks.run(post_exec, &turn_context.cwd).await
+            {
+                sess.notify_background_event(&sub_id, failure.to_string())
+                    .await;
+                function_call_output.success = Some(false);
+                function_call_output
+                    .content
+                    .push_str(&format!("\n\n{failure}"));
+            }
             ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: function_call_output,
@@ -2239,10 +3289,19 @@ async fn handle_function_call(
         _ => {
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
-                    // TODO(mbolin): Determine appropriate timeout for tool call.
-                    let timeout = None;
+                    let timeout = Some(
+                        sess.mcp_connection_manager
+                            .tool_timeout(&server, &tool_name),
+                    );
                     handle_mcp_tool_call(
-                        sess, &sub_id, call_id, server, tool_name, arguments, timeout,
+                        sess,
+                        &sub_id,
+                        call_id,
+                        server,
+                        tool_name,
+                        arguments,
+                        timeout,
+                        turn_context,
                     )
                     .await
                 }
@@ -2383,6 +3442,13 @@ async fn handle_container_exec_with_params(
     // check if this was a patch, and apply it if so
     let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
         MaybeApplyPatchVerified::Body(changes) => {
+            let pre_patch = HookEvent::PrePatch {
+                call_id: call_id.clone(),
+                changes: convert_apply_patch_to_protocol(&changes),
+            };
+            if let Err(failure) = sess.hooks.run(pre_patch, &turn_context.cwd).await {
+                return hook_blocked_output(sess, &sub_id, call_id, failure, "patch").await;
+            }
             match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
                 InternalApplyPatchInvocation::Output(item) => return item,
                 InternalApplyPatchInvocation::DelegateToExec(apply_patch_exec) => {
@@ -2463,7 +3529,9 @@ async fn handle_container_exec_with_params(
                     &params.command,
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
-                    &state.approved_commands,
+                    &state.approval_grants,
+                    &sess.exec_policy,
+                    &params.cwd,
                     params.with_escalated_permissions.unwrap_or(false),
                 )
             };
@@ -2472,11 +3540,23 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    if apply_patch_exec.is_none() && !matches!(safety, SafetyCheck::Reject { .. }) {
+        let pre_exec = HookEvent::PreExec {
+            call_id: call_id.clone(),
+            command: params.command.clone(),
+            cwd: params.cwd.clone(),
+        };
+        if let Err(failure) = sess.hooks.run(pre_exec, &turn_context.cwd).await {
+            return hook_blocked_output(sess, &sub_id, call_id, failure, "command").await;
+        }
+    }
+
     let sandbox_type = match safety {
         SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
         SafetyCheck::AskUser => {
             let rx_approve = sess
                 .request_command_approval(
+                    turn_context,
                     sub_id.clone(),
                     call_id.clone(),
                     params.command.clone(),
@@ -2486,8 +3566,12 @@ async fn handle_container_exec_with_params(
                 .await;
             match rx_approve.await.unwrap_or_default() {
                 ReviewDecision::Approved => (),
-                ReviewDecision::ApprovedForSession => {
-                    sess.add_approved_command(params.command.clone());
+                decision @ (ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedPrefixForSession
+                | ReviewDecision::ApprovedProgramInDirectory
+                | ReviewDecision::ApprovedTemporarily) => {
+                    sess.grant_approval(decision, params.command.clone(), &params.cwd)
+                        .await;
                 }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     return ResponseInputItem::FunctionCallOutput {
@@ -2506,6 +3590,15 @@ async fn handle_container_exec_with_params(
             SandboxType::None
         }
         SafetyCheck::Reject { reason } => {
+            sess.send_event(Event {
+                id: sub_id.clone(),
+                msg: EventMsg::ExecCommandRejected(ExecCommandRejectedEvent {
+                    call_id: call_id.clone(),
+                    command: params.command.clone(),
+                    reason: reason.clone(),
+                }),
+            })
+            .await;
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: FunctionCallOutputPayload {
@@ -2557,17 +3650,7 @@ async fn handle_container_exec_with_params(
 
     match output_result {
         Ok(output) => {
-            let ExecToolCallOutput { exit_code, .. } = &output;
-
-            let is_success = *exit_code == 0;
-            let content = format_exec_output(&output);
-            ResponseInputItem::FunctionCallOutput {
-                call_id: call_id.clone(),
-                output: FunctionCallOutputPayload {
-                    content,
-                    success: Some(is_success),
-                },
-            }
+            exec_output_with_post_hooks(sess, turn_context, &exec_command_context, &output).await
         }
         Err(CodexErr::Sandbox(error)) => {
             handle_sandbox_error(
@@ -2591,6 +3674,68 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Reports an action blocked by a `pre_exec` or `pre_patch` hook to the user
+/// and the model.
+async fn hook_blocked_output(
+    sess: &Session,
+    sub_id: &str,
+    call_id: String,
+    failure: HookFailure,
+    action: &str,
+) -> ResponseInputItem {
+    sess.notify_background_event(sub_id, format!("{action} blocked: {failure}"))
+        .await;
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content: format!("{action} blocked by hook: {failure}"),
+            success: Some(false),
+        },
+    }
+}
+
+/// Runs the `post_exec` or `post_patch` hooks for a finished command and
+/// builds the output for the model, including the stderr of a failing hook.
+async fn exec_output_with_post_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    exec_command_context: &ExecCommandContext,
+    output: &ExecToolCallOutput,
+) -> ResponseInputItem {
+    let call_id = exec_command_context.call_id.clone();
+    let mut is_success = output.exit_code == 0;
+    let mut content = format_exec_output(output);
+
+    let event = match &exec_command_context.apply_patch {
+        Some(ApplyPatchCommandContext { changes, .. }) => HookEvent::PostPatch {
+            call_id: call_id.clone(),
+            changes: changes.clone(),
+            success: is_success,
+        },
+        None => HookEvent::PostExec {
+            call_id: call_id.clone(),
+            command: exec_command_context.command_for_display.clone(),
+            cwd: exec_command_context.cwd.clone(),
+            exit_code: output.exit_code,
+            output: output.aggregated_output.text.clone(),
+        },
+    };
+    if let Err(failure) = sess.hooks.run(event, &turn_context.cwd).await {
+        sess.notify_background_event(&exec_command_context.sub_id, failure.to_string())
+            .await;
+        is_success = false;
+        content.push_str(&format!("\n\n{failure}"));
+    }
+
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(is_success),
+        },
+    }
+}
+
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
     params: ExecParams,
@@ -2649,6 +3794,7 @@ async fn handle_sandbox_error(
 
     let rx_approve = sess
         .request_command_approval(
+            turn_context,
             sub_id.clone(),
             call_id.clone(),
             params.command.clone(),
@@ -2658,12 +3804,21 @@ async fn handle_sandbox_error(
         .await;
 
     match rx_approve.await.unwrap_or_default() {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+        decision @ (ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedPrefixForSession
+        | ReviewDecision::ApprovedProgramInDirectory
+        | ReviewDecision::ApprovedTemporarily) => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
             // TODO(ragona): Isn't this a bug? It always saves the command in an | fork?
-            sess.add_approved_command(params.command.clone());
+            let decision = match decision {
+                ReviewDecision::Approved => ReviewDecision::ApprovedForSession,
+                decision => decision,
+            };
+            sess.grant_approval(decision, params.command.clone(), &cwd)
+                .await;
             // Inform UI we are retrying without sandbox.
             sess.notify_background_event(&sub_id, "retrying command without sandbox")
                 .await;
@@ -2694,18 +3849,13 @@ async fn handle_sandbox_error(
 
             match retry_output_result {
                 Ok(retry_output) => {
-                    let ExecToolCallOutput { exit_code, .. } = &retry_output;
-
-                    let is_success = *exit_code == 0;
-                    let content = format_exec_output(&retry_output);
-
-                    ResponseInputItem::FunctionCallOutput {
-                        call_id: call_id.clone(),
-                        output: FunctionCallOutputPayload {
-                            content,
-                            success: Some(is_success),
-                        },
-                    }
+                    exec_output_with_post_hooks(
+                        sess,
+                        turn_context,
+                        &exec_command_context,
+                        &retry_output,
+                    )
+                    .await
                 }
                 Err(e) => ResponseInputItem::FunctionCallOutput {
                     call_id: call_id.clone(),
@@ -2887,13 +4037,19 @@ fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<St
     })
 }
 
+/// Streams `prompt` to completion and returns the output items without
+/// recording them in the conversation history. The request's usage counts
+/// against `budget` when the request belongs to a task that has one.
 async fn drain_to_completed(
     sess: &Session,
     turn_context: &TurnContext,
     sub_id: &str,
     prompt: &Prompt,
-) -> CodexResult<()> {
-    let mut stream = turn_context.client.clone().stream(prompt).await?;
+    budget: Option<&mut BudgetTracker>,
+) -> CodexResult<Vec<ResponseItem>> {
+    let client = sess.model_client(turn_context);
+    let mut stream = client.stream(prompt).await?;
+    let mut output = Vec::new();
     loop {
         let maybe_event = stream.next().await;
         let Some(event) = maybe_event else {
@@ -2903,11 +4059,7 @@ async fn drain_to_completed(
             ));
         };
         match event {
-            Ok(ResponseEvent::OutputItemDone(item)) => {
-                // Record only to in-memory conversation history; avoid state snapshot.
-                let mut state = sess.state.lock_unchecked();
-                state.history.record_items(std::slice::from_ref(&item));
-            }
+            Ok(ResponseEvent::OutputItemDone(item)) => output.push(item),
             Ok(ResponseEvent::Completed {
                 response_id: _,
                 token_usage,
@@ -2918,12 +4070,17 @@ async fn drain_to_completed(
                 sess.tx_event
                     .send(Event {
                         id: sub_id.to_string(),
-                        msg: EventMsg::TokenCount(token_usage),
+                        msg: EventMsg::TokenCount(token_usage.clone()),
                     })
                     .await
                     .ok();
+                sess.record_token_usage(sub_id, &client.get_model(), &token_usage)
+                    .await;
+                if let Some(budget) = budget {
+                    budget.record_usage(&token_usage);
+                }
 
-                return Ok(());
+                return Ok(output);
             }
             Ok(_) => continue,
             Err(e) => return Err(e),
@@ -1,6 +1,16 @@
+use crate::audit::AUDIT_FILENAME;
 use crate::config_profile::ConfigProfile;
+use crate::config_types::AuditToml;
+use crate::config_types::AutoCompact;
+use crate::config_types::AutoCompactToml;
+use crate::config_types::FallbackProviderToml;
 use crate::config_types::History;
+use crate::config_types::Hooks;
 use crate::config_types::McpServerConfig;
+use crate::config_types::NotificationKind;
+use crate::config_types::Redaction;
+use crate::config_types::RedactionToml;
+use crate::config_types::SandboxReadOnly;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
@@ -15,6 +25,11 @@ use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::TaskBudget;
+use crate::protocol::default_deny_read_paths;
+use crate::provider_failover::DEFAULT_FALLBACK_FAILURE_THRESHOLD;
+use crate::provider_failover::FallbackProvider;
+use crate::usage::ModelPricing;
 use codex_login::AuthMode;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
@@ -59,6 +74,13 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Providers to switch to, in order, when `model_provider` keeps failing.
+    pub fallback_providers: Vec<FallbackProvider>,
+
+    /// Consecutive failed turns after which the session moves on to the next
+    /// entry in `fallback_providers`.
+    pub fallback_failure_threshold: u64,
+
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
 
@@ -87,8 +109,9 @@ pub struct Config {
     pub base_instructions: Option<String>,
 
     /// Optional external notifier command. When set, Codex will spawn this
-    /// program after each completed *turn* (i.e. when the agent finishes
-    /// processing a user submission). The value must be the full command
+    /// program for every notification selected by `notify_on`, by default
+    /// after each completed *turn* (i.e. when the agent finishes processing a
+    /// user submission). The value must be the full command
     /// broken into argv tokens **without** the trailing JSON argument - Codex
     /// appends one extra argument containing a JSON payload describing the
     /// event.
@@ -108,6 +131,10 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Kinds of notifications sent to `notify`. Defaults to
+    /// `agent-turn-complete` only.
+    pub notify_on: Vec<NotificationKind>,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -181,10 +208,36 @@ pub struct Config {
 
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
+
+    /// Include the `report_finding` tool that lets the agent report issues it
+    /// finds as structured `EventMsg::Finding` events.
+    pub include_finding_tool: bool,
     /// When true, disables burst-paste detection for typed input entirely.
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
     pub disable_paste_burst: bool,
+
+    /// Settings for automatically compacting the conversation when the
+    /// context window fills up.
+    pub auto_compact: AutoCompact,
+
+    /// Settings for masking secrets before they are sent to the model.
+    pub redaction: Redaction,
+
+    /// Where to append the tamper-evident audit log of agent actions; `None`
+    /// when auditing is disabled.
+    pub audit_log: Option<PathBuf>,
+
+    /// Limits applied to every task unless `Op::UserTurn` overrides them.
+    pub task_budget: TaskBudget,
+
+    /// Prices keyed by model slug prefix, taking precedence over the built-in
+    /// price list when computing session cost.
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// User commands run before and after commands, patches and other
+    /// lifecycle events.
+    pub hooks: Hooks,
 }
 
 impl Config {
@@ -264,6 +317,16 @@ pub fn load_config_as_toml(codex_home: &Path) -> std::io::Result<TomlValue> {
     }
 }
 
+/// Whether `cwd` (or the main repository of the git worktree it lives in) is
+/// marked as a trusted project in `CODEX_HOME/config.toml`. A config that
+/// cannot be read or parsed trusts nothing.
+pub fn is_project_trusted(codex_home: &Path, cwd: &Path) -> bool {
+    load_config_as_toml(codex_home)
+        .ok()
+        .and_then(|value| value.try_into::<ConfigToml>().ok())
+        .is_some_and(|cfg| cfg.is_cwd_trusted(cwd))
+}
+
 /// Patch `CODEX_HOME/config.toml` project state.
 /// Use with caution.
 pub fn set_project_trusted(codex_home: &Path, project_path: &Path) -> anyhow::Result<()> {
@@ -396,6 +459,13 @@ pub struct ConfigToml {
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
+    /// Providers to switch to, in order, when `model_provider` keeps failing.
+    pub fallback_providers: Option<Vec<FallbackProviderToml>>,
+
+    /// Consecutive failed turns after which the session moves on to the next
+    /// entry in `fallback_providers`.
+    pub fallback_failure_threshold: Option<u64>,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -411,6 +481,9 @@ pub struct ConfigToml {
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
+    /// Sandbox configuration to apply if `sandbox` is `ReadOnly`.
+    pub sandbox_read_only: Option<SandboxReadOnly>,
+
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
@@ -423,6 +496,9 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Kinds of notifications sent to `notify`.
+    pub notify_on: Option<Vec<NotificationKind>>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -497,6 +573,31 @@ pub struct ConfigToml {
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
     pub disable_paste_burst: Option<bool>,
+
+    /// Settings for automatic context compaction.
+    #[serde(default)]
+    pub auto_compact: AutoCompactToml,
+
+    /// Settings for secret redaction.
+    #[serde(default)]
+    pub redaction: RedactionToml,
+
+    /// Settings for the audit log.
+    #[serde(default)]
+    pub audit: AuditToml,
+
+    /// Limits on the tool calls, time and tokens a single task may use.
+    #[serde(default)]
+    pub task_budget: TaskBudget,
+
+    /// Per-model prices, in USD per million tokens, for models missing from
+    /// the built-in price list or priced differently by the provider.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Lifecycle hooks.
+    #[serde(default)]
+    pub hooks: Hooks,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -512,6 +613,11 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Enable the `report_finding` tool that lets the agent report issues it
+    /// finds in a structured form.
+    #[serde(default)]
+    pub report_finding: Option<bool>,
 }
 
 impl ConfigToml {
@@ -520,21 +626,39 @@ impl ConfigToml {
         let resolved_sandbox_mode = sandbox_mode_override
             .or(self.sandbox_mode)
             .unwrap_or_default();
+        let deny_read = |paths: Option<&Vec<PathBuf>>| match paths {
+            Some(paths) => paths.iter().map(|path| expand_home_dir(path)).collect(),
+            None => default_deny_read_paths(),
+        };
         match resolved_sandbox_mode {
-            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::ReadOnly => SandboxPolicy::ReadOnly {
+                deny_read: deny_read(
+                    self.sandbox_read_only
+                        .as_ref()
+                        .and_then(|cfg| cfg.deny_read.as_ref()),
+                ),
+            },
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
+                    deny_read: deny_read_paths,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    deny_read: deny_read(deny_read_paths.as_ref()),
+                },
+                None => SandboxPolicy::WorkspaceWrite {
+                    writable_roots: vec![],
+                    network_access: false,
+                    exclude_tmpdir_env_var: false,
+                    exclude_slash_tmp: false,
+                    deny_read: deny_read(None),
                 },
-                None => SandboxPolicy::new_workspace_write_policy(),
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
         }
@@ -702,22 +826,52 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
+        let include_finding_tool = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.report_finding)
+            .unwrap_or(true);
+
         let model = model
             .or(config_profile.model)
             .or(cfg.model)
             .unwrap_or_else(default_model);
-        let model_family = find_family_for_model(&model).unwrap_or_else(|| {
-            let supports_reasoning_summaries =
-                cfg.model_supports_reasoning_summaries.unwrap_or(false);
-            ModelFamily {
-                slug: model.clone(),
-                family: model.clone(),
-                needs_special_apply_patch_instructions: false,
-                supports_reasoning_summaries,
-                uses_local_shell_tool: false,
-                apply_patch_tool_type: None,
-            }
-        });
+        let supports_reasoning_summaries = cfg.model_supports_reasoning_summaries.unwrap_or(false);
+        let model_family = resolve_model_family(&model, supports_reasoning_summaries);
+
+        let fallback_providers = config_profile
+            .fallback_providers
+            .or(cfg.fallback_providers)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fallback| {
+                let model_provider = model_providers
+                    .get(&fallback.model_provider)
+                    .ok_or_else(|| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!(
+                                "Fallback model provider `{}` not found",
+                                fallback.model_provider
+                            ),
+                        )
+                    })?
+                    .clone();
+                Ok(FallbackProvider {
+                    model_family: resolve_model_family(
+                        &fallback.model,
+                        supports_reasoning_summaries,
+                    ),
+                    model_provider_id: fallback.model_provider,
+                    model_provider,
+                    model: fallback.model,
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let fallback_failure_threshold = config_profile
+            .fallback_failure_threshold
+            .or(cfg.fallback_failure_threshold)
+            .unwrap_or(DEFAULT_FALLBACK_FAILURE_THRESHOLD);
 
         let openai_model_info = get_model_info(&model_family);
         let model_context_window = cfg
@@ -731,6 +885,14 @@ impl Config {
 
         let experimental_resume = cfg.experimental_resume;
 
+        let audit_log = cfg.audit.enabled.unwrap_or(false).then(|| {
+            cfg.audit
+                .path
+                .as_deref()
+                .map(expand_home_dir)
+                .unwrap_or_else(|| codex_home.join(AUDIT_FILENAME))
+        });
+
         // Load base instructions override from a file if specified. If the
         // path is relative, resolve it against the effective cwd so the
         // behaviour matches other path-like config values.
@@ -753,6 +915,8 @@ impl Config {
             model_max_output_tokens,
             model_provider_id,
             model_provider,
+            fallback_providers,
+            fallback_failure_threshold,
             cwd: resolved_cwd,
             approval_policy: approval_policy
                 .or(config_profile.approval_policy)
@@ -766,6 +930,9 @@ impl Config {
                 .or(disable_response_storage)
                 .unwrap_or(false),
             notify: cfg.notify,
+            notify_on: cfg
+                .notify_on
+                .unwrap_or_else(|| vec![NotificationKind::AgentTurnComplete]),
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
@@ -806,7 +973,14 @@ impl Config {
                 .experimental_use_exec_command_tool
                 .unwrap_or(false),
             include_view_image_tool,
+            include_finding_tool,
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
+            auto_compact: cfg.auto_compact.into(),
+            redaction: cfg.redaction.into(),
+            audit_log,
+            task_budget: cfg.task_budget,
+            model_pricing: cfg.model_pricing,
+            hooks: cfg.hooks,
         };
         Ok(config)
     }
@@ -875,6 +1049,19 @@ fn default_model() -> String {
     OPENAI_DEFAULT_MODEL.to_string()
 }
 
+/// Family for `model`, falling back to a generic one for models this crate
+/// does not know about.
+fn resolve_model_family(model: &str, supports_reasoning_summaries: bool) -> ModelFamily {
+    find_family_for_model(model).unwrap_or_else(|| ModelFamily {
+        slug: model.to_string(),
+        family: model.to_string(),
+        needs_special_apply_patch_instructions: false,
+        supports_reasoning_summaries,
+        uses_local_shell_tool: false,
+        apply_patch_tool_type: None,
+    })
+}
+
 /// Returns the path to the Codex configuration directory, which can be
 /// specified by the `CODEX_HOME` environment variable. If not set, defaults to
 /// `~/.codex`.
@@ -902,6 +1089,15 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Expands a leading `~` in `path` to the home directory. Other paths are
+/// returned unchanged.
+fn expand_home_dir(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
 /// Returns the path to the folder where Codex logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -913,6 +1109,7 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::NotificationMethod;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -950,6 +1147,35 @@ persistence = "none"
         );
     }
 
+    #[test]
+    fn test_notification_config_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+notify_on = ["approval-requested", "task-error"]
+
+[tui]
+notifications = ["agent-turn-complete"]
+notification_method = "bell"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            Some(vec![
+                NotificationKind::ApprovalRequested,
+                NotificationKind::TaskError
+            ]),
+            cfg.notify_on
+        );
+        assert_eq!(
+            Some(Tui {
+                notifications: vec![NotificationKind::AgentTurnComplete],
+                notification_method: NotificationMethod::Bell,
+            }),
+            cfg.tui
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -977,19 +1203,23 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
 
         let sandbox_workspace_write = r#"
 sandbox_mode = "workspace-write"
 
+[sandbox_read_only]
+deny_read = []  # This should be ignored.
+
 [sandbox_workspace_write]
 writable_roots = [
     "/my/workspace",
 ]
 exclude_tmpdir_env_var = true
 exclude_slash_tmp = true
+deny_read = ["~/.ssh", "/etc/secrets"]
 "#;
 
         let sandbox_workspace_write_cfg = toml::from_str::<ConfigToml>(sandbox_workspace_write)
@@ -1001,9 +1231,28 @@ exclude_slash_tmp = true
                 network_access: false,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                deny_read: vec![
+                    home_dir().unwrap().join(".ssh"),
+                    PathBuf::from("/etc/secrets"),
+                ],
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
+
+        let sandbox_read_only_without_deny_read = r#"
+sandbox_mode = "read-only"
+
+[sandbox_read_only]
+deny_read = []
+"#;
+
+        let sandbox_read_only_cfg =
+            toml::from_str::<ConfigToml>(sandbox_read_only_without_deny_read)
+                .expect("TOML deserialization should succeed");
+        assert_eq!(
+            SandboxPolicy::ReadOnly { deny_read: vec![] },
+            sandbox_read_only_cfg.derive_sandbox_policy(None)
+        );
     }
 
     struct PrecedenceTestFixture {
@@ -1146,12 +1395,15 @@ disable_response_storage = true
                 model_max_output_tokens: Some(100_000),
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
+                fallback_providers: Vec::new(),
+                fallback_failure_threshold: 3,
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 disable_response_storage: false,
                 user_instructions: None,
                 notify: None,
+                notify_on: vec![NotificationKind::AgentTurnComplete],
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
                 model_providers: fixture.model_provider_map.clone(),
@@ -1176,7 +1428,14 @@ disable_response_storage = true
                 preferred_auth_method: AuthMode::ChatGPT,
                 use_experimental_streamable_shell_tool: false,
                 include_view_image_tool: true,
+                include_finding_tool: true,
                 disable_paste_burst: false,
+                auto_compact: AutoCompact::default(),
+                redaction: Redaction::default(),
+                audit_log: None,
+                task_budget: TaskBudget::default(),
+                model_pricing: HashMap::new(),
+                hooks: Hooks::default(),
             },
             o3_profile_config
         );
@@ -1204,12 +1463,15 @@ disable_response_storage = true
             model_max_output_tokens: Some(4_096),
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
+            fallback_providers: Vec::new(),
+            fallback_failure_threshold: 3,
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             disable_response_storage: false,
             user_instructions: None,
             notify: None,
+            notify_on: vec![NotificationKind::AgentTurnComplete],
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
@@ -1234,7 +1496,14 @@ disable_response_storage = true
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_finding_tool: true,
             disable_paste_burst: false,
+            auto_compact: AutoCompact::default(),
+            redaction: Redaction::default(),
+            audit_log: None,
+            task_budget: TaskBudget::default(),
+            model_pricing: HashMap::new(),
+            hooks: Hooks::default(),
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -1277,12 +1546,15 @@ disable_response_storage = true
             model_max_output_tokens: Some(100_000),
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            fallback_providers: Vec::new(),
+            fallback_failure_threshold: 3,
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             disable_response_storage: true,
             user_instructions: None,
             notify: None,
+            notify_on: vec![NotificationKind::AgentTurnComplete],
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
@@ -1307,7 +1579,14 @@ disable_response_storage = true
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_finding_tool: true,
             disable_paste_burst: false,
+            auto_compact: AutoCompact::default(),
+            redaction: Redaction::default(),
+            audit_log: None,
+            task_budget: TaskBudget::default(),
+            model_pricing: HashMap::new(),
+            hooks: Hooks::default(),
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
+use crate::config_types::FallbackProviderToml;
 use crate::config_types::Verbosity;
 use crate::protocol::AskForApproval;
 use codex_protocol::config_types::ReasoningEffort;
@@ -14,6 +15,11 @@ pub struct ConfigProfile {
     /// The key in the `model_providers` map identifying the
     /// [`ModelProviderInfo`] to use.
     pub model_provider: Option<String>,
+    /// Providers to switch to, in order, when `model_provider` keeps failing.
+    pub fallback_providers: Option<Vec<FallbackProviderToml>>,
+    /// Consecutive failed requests after which the session moves on to the
+    /// next entry in `fallback_providers`.
+    pub fallback_failure_threshold: Option<u64>,
     pub approval_policy: Option<AskForApproval>,
     pub disable_response_storage: Option<bool>,
     pub model_reasoning_effort: Option<ReasoningEffort>,
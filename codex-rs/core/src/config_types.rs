@@ -13,6 +13,9 @@ use strum_macros::Display;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
+    /// Program that launches a server communicating over stdio. Leave unset
+    /// when connecting to a remote server via `url`.
+    #[serde(default)]
     pub command: String,
 
     #[serde(default)]
@@ -20,6 +23,72 @@ pub struct McpServerConfig {
 
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
+
+    /// URL of a remote server that speaks the streamable HTTP transport (or
+    /// the legacy HTTP+SSE transport).
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Extra HTTP headers sent with every request to `url`.
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+
+    /// Name of the environment variable holding a bearer token that is sent
+    /// with every request to `url`.
+    #[serde(default)]
+    pub bearer_token_env_var: Option<String>,
+
+    /// How long the server may take to start and list its tools, in
+    /// milliseconds. Defaults to 10 seconds.
+    #[serde(default)]
+    pub startup_timeout_ms: Option<u64>,
+
+    /// How long a call to one of the server's tools may take before it is
+    /// cancelled, in milliseconds. Defaults to 60 seconds.
+    #[serde(default)]
+    pub tool_timeout_ms: Option<u64>,
+
+    /// Per-tool overrides of `tool_timeout_ms`, keyed by tool name.
+    #[serde(default)]
+    pub tool_timeouts_ms: Option<HashMap<String, u64>>,
+
+    /// Glob patterns (`*` and `?`) of the tools offered to the model. All
+    /// tools are offered when unset.
+    #[serde(default)]
+    pub enabled_tools: Option<Vec<String>>,
+
+    /// Glob patterns of tools that are never offered to the model, even if
+    /// they match `enabled_tools`.
+    #[serde(default)]
+    pub disabled_tools: Option<Vec<String>>,
+
+    /// Whether calls to the server's tools need the user's approval. Defaults
+    /// to `never`.
+    #[serde(default)]
+    pub approval: Option<McpToolApproval>,
+
+    /// Per-tool overrides of `approval`, keyed by glob pattern of tool names.
+    /// If several patterns match a tool, the strictest setting wins.
+    #[serde(default)]
+    pub tool_approvals: Option<HashMap<String, McpToolApproval>>,
+
+    /// Glob patterns of the tools that only read. With `approval = "on-write"`
+    /// these are the only tools that run without asking; the server's own
+    /// `readOnlyHint` annotations are not trusted.
+    #[serde(default)]
+    pub read_only_tools: Option<Vec<String>>,
+}
+
+/// When a call to an MCP tool needs the user's approval. Ordered from the
+/// most to the least permissive.
+#[derive(Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpToolApproval {
+    #[default]
+    Never,
+    /// Unless the tool matches the server's `read_only_tools`.
+    OnWrite,
+    Always,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -76,7 +145,48 @@ pub enum HistoryPersistence {
 
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+pub struct Tui {
+    /// Events that raise a terminal notification. Empty by default.
+    #[serde(default)]
+    pub notifications: Vec<NotificationKind>,
+
+    /// How terminal notifications are raised.
+    #[serde(default)]
+    pub notification_method: NotificationMethod,
+}
+
+/// Kinds of events a user can be notified about, both through the `notify`
+/// program and through terminal notifications in the TUI.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    /// The agent finished its turn and is waiting for the user.
+    AgentTurnComplete,
+    /// The agent started working on a task.
+    TaskStarted,
+    /// The agent is blocked until the user approves a command or patch.
+    ApprovalRequested,
+    /// A task failed with an error.
+    TaskError,
+    /// The connection to the model dropped and the request is being retried.
+    StreamError,
+    /// A task was stopped because it used up its `task_budget`.
+    BudgetExceeded,
+    /// The conversation was summarized to free up the context window.
+    ContextCompacted,
+}
+
+/// How the TUI raises a terminal notification.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationMethod {
+    /// A desktop notification through the OSC 9 escape sequence, supported by
+    /// iTerm2, WezTerm, kitty, Ghostty and Windows Terminal.
+    #[default]
+    Osc9,
+    /// The terminal bell.
+    Bell,
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -88,6 +198,21 @@ pub struct SandboxWorkspaceWrite {
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
+    /// Paths that sandboxed commands must not be able to read. A leading `~/`
+    /// is expanded to the home directory. Replaces the built-in list of
+    /// credential stores when set.
+    #[serde(default)]
+    pub deny_read: Option<Vec<PathBuf>>,
+}
+
+/// Sandbox configuration that applies when `sandbox_mode` is `read-only`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SandboxReadOnly {
+    /// Paths that sandboxed commands must not be able to read. A leading `~/`
+    /// is expanded to the home directory. Replaces the built-in list of
+    /// credential stores when set.
+    #[serde(default)]
+    pub deny_read: Option<Vec<PathBuf>>,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
@@ -186,6 +311,171 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
     }
 }
 
+/// Settings for automatic context compaction, as written in `config.toml`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AutoCompactToml {
+    pub enabled: Option<bool>,
+
+    /// Percentage of the model context window that may be used before the
+    /// conversation is compacted.
+    pub threshold_percent: Option<u8>,
+
+    /// Number of most recent model turns to keep verbatim after compaction.
+    pub keep_recent_turns: Option<usize>,
+
+    /// Instructions used when asking the model to summarize the conversation.
+    pub prompt: Option<String>,
+}
+
+/// Automatic compaction summarizes older conversation items once the tokens
+/// in the context window cross `threshold_percent` of the model's context
+/// window. Everything except the last `keep_recent_turns` turns is replaced
+/// by the summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoCompact {
+    pub enabled: bool,
+    pub threshold_percent: u8,
+    pub keep_recent_turns: usize,
+
+    /// When `None`, the built-in summarization prompt is used.
+    pub prompt: Option<String>,
+}
+
+impl Default for AutoCompact {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_percent: 90,
+            keep_recent_turns: 2,
+            prompt: None,
+        }
+    }
+}
+
+impl From<AutoCompactToml> for AutoCompact {
+    fn from(toml: AutoCompactToml) -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: toml.enabled.unwrap_or(defaults.enabled),
+            threshold_percent: toml
+                .threshold_percent
+                .unwrap_or(defaults.threshold_percent)
+                .clamp(1, 100),
+            keep_recent_turns: toml.keep_recent_turns.unwrap_or(defaults.keep_recent_turns),
+            prompt: toml.prompt.filter(|p| !p.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RedactionToml {
+    pub enabled: Option<bool>,
+
+    /// Whether to mask long random-looking tokens that no other detector
+    /// recognizes.
+    pub high_entropy: Option<bool>,
+
+    /// Additional regular expressions whose matches are masked.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// Secrets found in tool outputs, MCP results and user messages are masked
+/// before they are recorded in the conversation history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redaction {
+    pub enabled: bool,
+    pub high_entropy: bool,
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            high_entropy: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl From<RedactionToml> for Redaction {
+    fn from(toml: RedactionToml) -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: toml.enabled.unwrap_or(defaults.enabled),
+            high_entropy: toml.high_entropy.unwrap_or(defaults.high_entropy),
+            patterns: toml.patterns,
+        }
+    }
+}
+
+/// Settings for the audit log, as written in `config.toml`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AuditToml {
+    pub enabled: Option<bool>,
+
+    /// Where to write the log. Defaults to `$CODEX_HOME/audit.jsonl`.
+    pub path: Option<PathBuf>,
+}
+
+/// A user command run by a lifecycle hook.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct HookCommand {
+    /// Program and arguments. The hook's JSON payload is written to its stdin.
+    pub command: Vec<String>,
+
+    /// Kill the hook and treat it as failed after this long. Defaults to 60
+    /// seconds.
+    pub timeout_ms: Option<u64>,
+}
+
+/// Commands run at points of the session lifecycle, in the order listed.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Hooks {
+    #[serde(default)]
+    pub session_start: Vec<HookCommand>,
+
+    #[serde(default)]
+    pub session_end: Vec<HookCommand>,
+
+    /// Run before a shell command; a failing hook blocks the command.
+    #[serde(default)]
+    pub pre_exec: Vec<HookCommand>,
+
+    /// Run after a shell command; a failing hook reports its stderr to the
+    /// model.
+    #[serde(default)]
+    pub post_exec: Vec<HookCommand>,
+
+    /// Run before a patch is applied; a failing hook blocks the patch.
+    #[serde(default)]
+    pub pre_patch: Vec<HookCommand>,
+
+    /// Run after a patch is applied; a failing hook reports its stderr to the
+    /// model.
+    #[serde(default)]
+    pub post_patch: Vec<HookCommand>,
+
+    /// Run when the user is asked to approve a command or patch.
+    #[serde(default)]
+    pub approval_request: Vec<HookCommand>,
+
+    /// Run when the agent finishes a turn.
+    #[serde(default)]
+    pub turn_complete: Vec<HookCommand>,
+}
+
+/// An entry in the `fallback_providers` chain, as written in `config.toml`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FallbackProviderToml {
+    /// Key in the `model_providers` map.
+    pub model_provider: String,
+
+    /// Model to request from this provider.
+    pub model: String,
+}
+
 /// See https://platform.openai.com/docs/guides/reasoning?api-mode=responses#get-started-with-reasoning
 #[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display)]
 #[serde(rename_all = "lowercase")]
@@ -32,6 +32,15 @@ impl ConversationHistory {
         }
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Drops every item recorded after the first `len` items.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.items.truncate(len);
+    }
+
     pub(crate) fn keep_last_messages(&mut self, n: usize) {
         if n == 0 {
             self.items.clear();
@@ -59,6 +68,46 @@ impl ConversationHistory {
         kept.reverse();
         self.items = kept;
     }
+
+    /// Replaces everything except the last `n` model turns with `summary`
+    /// (which should also carry any context the model must not lose, such as
+    /// user instructions). A turn starts at the first model output that
+    /// follows user input or a tool result. The oldest turn is always
+    /// summarized so that compaction makes progress. Returns the number of
+    /// turns that were kept.
+    pub(crate) fn compact_with_summary(&mut self, summary: Vec<ResponseItem>, n: usize) -> usize {
+        let turn_starts: Vec<usize> = (0..self.items.len())
+            .filter(|&i| {
+                is_model_output(&self.items[i]) && (i == 0 || !is_model_output(&self.items[i - 1]))
+            })
+            .collect();
+        let kept = n.min(turn_starts.len().saturating_sub(1));
+        let start = if kept == 0 {
+            self.items.len()
+        } else {
+            turn_starts[turn_starts.len() - kept]
+        };
+
+        let mut items = summary;
+        items.extend(self.items.drain(start..));
+        self.items = items;
+        kept
+    }
+}
+
+/// Items produced by the model, as opposed to user input and tool results.
+fn is_model_output(item: &ResponseItem) -> bool {
+    match item {
+        ResponseItem::Message { role, .. } => role == "assistant",
+        ResponseItem::Reasoning { .. }
+        | ResponseItem::FunctionCall { .. }
+        | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::LocalShellCall { .. } => true,
+        ResponseItem::FunctionCallOutput { .. }
+        | ResponseItem::CustomToolCallOutput { .. }
+        | ResponseItem::WebSearchCall { .. }
+        | ResponseItem::Other => false,
+    }
 }
 
 /// Anything that is not a system message or "reasoning" message is considered
@@ -80,6 +129,7 @@ fn is_api_message(message: &ResponseItem) -> bool {
 mod tests {
     use super::*;
     use codex_protocol::models::ContentItem;
+    use codex_protocol::models::FunctionCallOutputPayload;
 
     fn assistant_msg(text: &str) -> ResponseItem {
         ResponseItem::Message {
@@ -140,4 +190,64 @@ mod tests {
             ]
         );
     }
+
+    fn function_call(call_id: &str) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    fn function_output(call_id: &str) -> ResponseItem {
+        ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content: "ok".to_string(),
+                success: Some(true),
+            },
+        }
+    }
+
+    #[test]
+    fn compact_with_summary_keeps_recent_turns() {
+        let mut h = ConversationHistory::default();
+        let items = vec![
+            user_msg("audit the repo"),
+            function_call("1"),
+            function_output("1"),
+            function_call("2"),
+            function_output("2"),
+            assistant_msg("done"),
+        ];
+        h.record_items(items.iter());
+
+        let summary = user_msg("summary");
+        let kept = h.compact_with_summary(vec![summary.clone()], 2);
+
+        assert_eq!(kept, 2);
+        assert_eq!(
+            h.contents(),
+            vec![
+                summary,
+                function_call("2"),
+                function_output("2"),
+                assistant_msg("done"),
+            ]
+        );
+    }
+
+    #[test]
+    fn compact_with_summary_always_summarizes_oldest_turn() {
+        let mut h = ConversationHistory::default();
+        let items = vec![user_msg("hi"), assistant_msg("hello")];
+        h.record_items(items.iter());
+
+        let summary = user_msg("summary");
+        let kept = h.compact_with_summary(vec![summary.clone()], 5);
+
+        assert_eq!(kept, 0);
+        assert_eq!(h.contents(), vec![summary]);
+    }
 }
@@ -123,6 +123,10 @@ pub enum CodexErr {
 
     #[error("{0}")]
     EnvVar(EnvVarError),
+
+    /// A tool that the provider's wire API has no way to express.
+    #[error("the `{0}` tool is not supported by this model provider")]
+    UnsupportedTool(String),
 }
 
 #[derive(Debug)]
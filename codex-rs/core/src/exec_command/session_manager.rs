@@ -36,6 +36,19 @@ pub struct ExecCommandOutput {
 }
 
 impl ExecCommandOutput {
+    /// The exit code of the command, if it exited before the call returned.
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        match self.exit_status {
+            ExitStatus::Exited(code) => Some(code),
+            ExitStatus::Ongoing(_) => None,
+        }
+    }
+
+    /// The output collected while the call ran.
+    pub(crate) fn output(&self) -> &str {
+        &self.output
+    }
+
     fn to_text_output(&self) -> String {
         let wall_time_secs = self.wall_time.as_secs_f32();
         let termination_status = match self.exit_status {
@@ -0,0 +1,1272 @@
+//! Integration with `codex-execpolicy`.
+//!
+//! The bundled `default.policy` is always loaded. On top of it, every
+//! `*.policy` file in `$CODEX_HOME/policy/` and in `<cwd>/.codex/policy/` is
+//! parsed (in lexicographic order) so that teams can ship a reviewed allowlist
+//! without patching Rust. A command that matches a `forbidden`,
+//! `deny_program()` or `forbid_args()` rule in *any* policy is rejected, with
+//! the rule's reason; a command that matches an allow rule is auto-approved
+//! (inside the sandbox) as long as every file it may write lives in a writable
+//! root, otherwise the user is asked.
+//!
+//! The repository controls `<cwd>/.codex/policy/`, so the allow rules found
+//! there are only honored once the project has been marked as trusted; until
+//! then those files can only forbid commands. A shell script (`bash -lc`,
+//! `sh -c`, ...) that cannot be split into plain commands cannot be checked
+//! rule by rule either, so it is sent to the user whenever a deny rule might
+//! apply to part of it.
+//!
+//! Deny rules also apply to the command run by a wrapper such as `env`,
+//! `sudo` or `timeout`. Allow rules do not: they must match the command as
+//! it is run.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::ArgType;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::NegativeExamplePassedCheck;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::PositiveExampleFailedCheck;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::get_default_policy;
+use serde::Serialize;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::bash::try_parse_word_only_pipelines;
+use crate::config::is_project_trusted;
+use crate::protocol::SandboxPolicy;
+
+/// Name of the directory (under `CODEX_HOME` or `<cwd>/.codex`) that holds
+/// user-authored policy files.
+const POLICY_DIR: &str = "policy";
+const POLICY_FILE_EXTENSION: &str = "policy";
+
+/// Name under which the bundled policy is reported.
+const DEFAULT_POLICY_SOURCE: &str = "default.policy";
+
+/// Shells whose `-c` script is split into the commands it runs.
+const SHELLS: &[&str] = &["bash", "sh", "zsh", "dash", "ksh"];
+
+/// A program that runs the command given in its arguments, after options
+/// of its own.
+struct Wrapper {
+    /// Matched against the file name of the program.
+    name: &'static str,
+    flags: &'static [&'static str],
+    /// Options followed by a value, which may also be attached (`-n5`,
+    /// `--signal=KILL`).
+    options_with_value: &'static [&'static str],
+    /// Arguments between the options and the command, e.g. the duration of
+    /// `timeout`.
+    operands: usize,
+}
+
+const WRAPPERS: &[Wrapper] = &[
+    Wrapper {
+        name: "command",
+        flags: &["-p"],
+        options_with_value: &[],
+        operands: 0,
+    },
+    Wrapper {
+        name: "doas",
+        flags: &["-n", "-s"],
+        options_with_value: &["-u", "-C"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "env",
+        flags: &[
+            "-i",
+            "--ignore-environment",
+            "-0",
+            "--null",
+            "-v",
+            "--debug",
+        ],
+        options_with_value: &["-u", "--unset", "-C", "--chdir"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "exec",
+        flags: &["-c", "-l"],
+        options_with_value: &["-a"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "nice",
+        flags: &[],
+        options_with_value: &["-n", "--adjustment"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "nohup",
+        flags: &[],
+        options_with_value: &[],
+        operands: 0,
+    },
+    Wrapper {
+        name: "stdbuf",
+        flags: &[],
+        options_with_value: &["-i", "--input", "-o", "--output", "-e", "--error"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "sudo",
+        flags: &[
+            "-A",
+            "--askpass",
+            "-b",
+            "--background",
+            "-E",
+            "--preserve-env",
+            "-H",
+            "--set-home",
+            "-i",
+            "--login",
+            "-k",
+            "--reset-timestamp",
+            "-K",
+            "--remove-timestamp",
+            "-n",
+            "--non-interactive",
+            "-P",
+            "--preserve-groups",
+            "-S",
+            "--stdin",
+            "-s",
+            "--shell",
+        ],
+        options_with_value: &[
+            "-u",
+            "--user",
+            "-g",
+            "--group",
+            "-C",
+            "--close-from",
+            "-D",
+            "--chdir",
+            "-h",
+            "--host",
+            "-p",
+            "--prompt",
+            "-R",
+            "--chroot",
+            "-r",
+            "--role",
+            "-t",
+            "--type",
+            "-T",
+            "--command-timeout",
+            "-U",
+            "--other-user",
+        ],
+        operands: 0,
+    },
+    Wrapper {
+        name: "time",
+        flags: &["-p", "-a", "--append", "-q", "--quiet", "-v", "--verbose"],
+        options_with_value: &["-f", "--format", "-o", "--output"],
+        operands: 0,
+    },
+    Wrapper {
+        name: "timeout",
+        flags: &["--foreground", "--preserve-status", "-v", "--verbose"],
+        options_with_value: &["-s", "--signal", "-k", "--kill-after"],
+        operands: 1,
+    },
+    Wrapper {
+        name: "xargs",
+        flags: &[
+            "-0",
+            "--null",
+            "-r",
+            "--no-run-if-empty",
+            "-t",
+            "--verbose",
+            "-p",
+            "--interactive",
+            "-x",
+            "--exit",
+            "-o",
+            "--open-tty",
+        ],
+        options_with_value: &[
+            "-a",
+            "--arg-file",
+            "-d",
+            "--delimiter",
+            "-E",
+            "-I",
+            "-L",
+            "-n",
+            "--max-args",
+            "-P",
+            "--max-procs",
+            "-s",
+            "--max-chars",
+            "--process-slot-var",
+        ],
+        operands: 0,
+    },
+];
+
+/// Options of `find` that run the command following them, up to `;` or `+`.
+const FIND_EXEC_OPTIONS: &[&str] = &["-exec", "-execdir", "-ok", "-okdir"];
+
+/// Outcome of checking a command against the loaded policies.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ExecPolicyDecision {
+    /// The command matched an allow rule and only writes inside the sandbox's
+    /// writable roots.
+    Allow,
+    /// The command matched an allow rule, but it may write files outside the
+    /// writable roots.
+    Ask { reason: String },
+    /// The command matched a `forbidden` rule.
+    Forbidden { reason: String },
+    /// No policy has an opinion about this command.
+    NoMatch,
+}
+
+/// The set of execpolicy files that apply to a session.
+#[derive(Default)]
+pub struct ExecPolicy {
+    policies: Vec<LoadedPolicy>,
+}
+
+struct LoadedPolicy {
+    /// Path of the policy file, or [`DEFAULT_POLICY_SOURCE`].
+    source: String,
+    policy: Policy,
+    /// Set for repo-local files of an untrusted project: only their
+    /// forbidden outcomes are honored.
+    deny_only: bool,
+}
+
+/// How one command of a (possibly compound) shell command was decided.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommandExplanation {
+    pub command: Vec<String>,
+    #[serde(flatten)]
+    pub decision: ExecPolicyDecision,
+    /// Every rule that matched the command, in the order the policies were
+    /// loaded.
+    pub matches: Vec<RuleMatch>,
+}
+
+/// A rule of one policy file that matched a command.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub policy: String,
+    /// The program's arguments as resolved by the rule, or why the rule
+    /// forbids the command.
+    pub result: MatchedExec,
+}
+
+/// Result of running the `should_match` and `should_not_match` examples of a
+/// policy file.
+#[derive(Serialize, Debug)]
+pub struct PolicyFileTest {
+    pub path: PathBuf,
+    /// Set when the file could not be parsed; no examples were run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub failed_should_match: Vec<PositiveExampleFailedCheck>,
+    pub passed_should_not_match: Vec<NegativeExamplePassedCheck>,
+}
+
+impl PolicyFileTest {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+            && self.failed_should_match.is_empty()
+            && self.passed_should_not_match.is_empty()
+    }
+}
+
+impl ExecPolicy {
+    /// Loads the default policy plus any user and repo-local policy files.
+    /// Repo-local files are deny-only unless `cwd` is a trusted project.
+    /// Files that fail to parse are skipped; a human-readable message for each
+    /// failure is returned alongside the policy so the caller can surface it.
+    pub fn load(codex_home: &Path, cwd: &Path) -> (Self, Vec<String>) {
+        let mut policies = Vec::new();
+        let mut errors = Vec::new();
+
+        match get_default_policy() {
+            Ok(policy) => policies.push(LoadedPolicy {
+                source: DEFAULT_POLICY_SOURCE.to_string(),
+                policy,
+                deny_only: false,
+            }),
+            Err(e) => errors.push(format!("failed to parse default exec policy: {e}")),
+        }
+
+        let user_dir = codex_home.join(POLICY_DIR);
+        let repo_trusted = is_project_trusted(codex_home, cwd);
+        for dir in policy_dirs(codex_home, cwd) {
+            let deny_only = dir != user_dir && !repo_trusted;
+            for path in policy_files_in(&dir) {
+                match parse_policy_file(&path) {
+                    Ok(policy) => policies.push(LoadedPolicy {
+                        source: path.display().to_string(),
+                        policy,
+                        deny_only,
+                    }),
+                    Err(e) => errors.push(format!(
+                        "failed to load exec policy {}: {e}",
+                        path.display()
+                    )),
+                }
+            }
+        }
+
+        (Self { policies }, errors)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_sources(sources: &[&str]) -> Self {
+        let policies = sources
+            .iter()
+            .map(|source| LoadedPolicy {
+                source: "#test".to_string(),
+                policy: PolicyParser::new("#test", source).parse().unwrap(),
+                deny_only: false,
+            })
+            .collect();
+        Self { policies }
+    }
+
+    /// Checks `command` against every loaded policy. Shell scripts
+    /// consisting solely of plain commands are checked command by command,
+    /// and their pipelines against the `deny_pipeline()` rules.
+    pub fn evaluate(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> ExecPolicyDecision {
+        if self.policies.is_empty() {
+            return ExecPolicyDecision::NoMatch;
+        }
+
+        split_commands(command)
+            .iter()
+            .map(|cmd| self.evaluate_single(cmd, sandbox_policy, cwd).0)
+            .chain(
+                self.explain_pipelines(command)
+                    .into_iter()
+                    .map(|explanation| explanation.decision),
+            )
+            .reduce(combine_decisions)
+            .unwrap_or(ExecPolicyDecision::NoMatch)
+    }
+
+    /// Like [`ExecPolicy::evaluate`], but reports the decision for each
+    /// command of a script together with the rules that matched it. Each
+    /// pipeline refused by a `deny_pipeline()` rule is reported after the
+    /// commands, with `|` between its commands.
+    pub fn explain(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> Vec<CommandExplanation> {
+        split_commands(command)
+            .into_iter()
+            .map(|cmd| {
+                let (decision, matches) = self.evaluate_single(&cmd, sandbox_policy, cwd);
+                CommandExplanation {
+                    command: cmd,
+                    decision,
+                    matches,
+                }
+            })
+            .chain(self.explain_pipelines(command))
+            .collect()
+    }
+
+    /// The pipelines of `command` that a `deny_pipeline()` rule refuses. The
+    /// commands run by wrappers are checked too, so `curl ... | sudo sh` is
+    /// refused like `curl ... | sh`.
+    fn explain_pipelines(&self, command: &[String]) -> Vec<CommandExplanation> {
+        pipelines(command)
+            .into_iter()
+            .filter_map(|pipeline| {
+                let exec_calls: Vec<ExecCall> = pipeline
+                    .iter()
+                    .flat_map(|command| unwrapped(command))
+                    .filter_map(|command| {
+                        let (program, args) = command.split_first()?;
+                        Some(ExecCall {
+                            program: program.clone(),
+                            args: args.to_vec(),
+                        })
+                    })
+                    .collect();
+                let matches: Vec<RuleMatch> = self
+                    .policies
+                    .iter()
+                    .filter_map(|LoadedPolicy { source, policy, .. }| {
+                        Some(RuleMatch {
+                            policy: source.clone(),
+                            result: policy.check_pipeline(&exec_calls)?,
+                        })
+                    })
+                    .collect();
+                let MatchedExec::Forbidden { reason, .. } = &matches.first()?.result else {
+                    return None;
+                };
+                Some(CommandExplanation {
+                    command: pipeline.join(&["|".to_string()][..]),
+                    decision: ExecPolicyDecision::Forbidden {
+                        reason: reason.clone(),
+                    },
+                    matches,
+                })
+            })
+            .collect()
+    }
+
+    fn evaluate_single(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> (ExecPolicyDecision, Vec<RuleMatch>) {
+        let Some((program, args)) = command.split_first() else {
+            return (ExecPolicyDecision::NoMatch, Vec::new());
+        };
+        let exec_call = ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        };
+
+        // Also consult the bare program name so that `/usr/bin/curl` cannot be
+        // used to sidestep a rule that forbids `curl`. Only forbidden outcomes
+        // are honored for the basename: allow rules must match exactly.
+        let basename_call = Some(program_name(program))
+            .filter(|name| name != program)
+            .map(|name| ExecCall {
+                program: name.to_string(),
+                args: args.to_vec(),
+            });
+
+        let mut matches: Vec<RuleMatch> = Vec::new();
+        let mut forbidden: Option<String> = None;
+        for LoadedPolicy {
+            source,
+            policy,
+            deny_only,
+        } in &self.policies
+        {
+            let mut result = policy.check(&exec_call).ok();
+            if !matches!(result, Some(MatchedExec::Forbidden { .. }))
+                && let Some(basename_call) = &basename_call
+                && let Ok(basename_result @ MatchedExec::Forbidden { .. }) =
+                    policy.check(basename_call)
+            {
+                result = Some(basename_result);
+            }
+            if let Some(result) = result {
+                match &result {
+                    MatchedExec::Forbidden { reason, .. } => {
+                        forbidden.get_or_insert_with(|| reason.clone());
+                    }
+                    MatchedExec::Match { .. } if *deny_only => continue,
+                    MatchedExec::Match { .. } => {}
+                }
+                matches.push(RuleMatch {
+                    policy: source.clone(),
+                    result,
+                });
+            }
+        }
+        // Only the deny outcomes of a wrapped command count, see above.
+        let wrapped_commands = wrapped_commands(command);
+        let mut wrapped_ask = None;
+        for wrapped in wrapped_commands.iter().flatten() {
+            let (decision, wrapped_matches) = self.evaluate_wrapped(wrapped, sandbox_policy, cwd);
+            match decision {
+                ExecPolicyDecision::Forbidden { reason } => {
+                    forbidden.get_or_insert(reason);
+                    matches.extend(wrapped_matches.into_iter().filter(|rule_match| {
+                        matches!(rule_match.result, MatchedExec::Forbidden { .. })
+                    }));
+                }
+                ExecPolicyDecision::Ask { reason } => {
+                    wrapped_ask.get_or_insert(reason);
+                }
+                ExecPolicyDecision::Allow | ExecPolicyDecision::NoMatch => {}
+            }
+        }
+        if let Some(reason) = forbidden {
+            return (ExecPolicyDecision::Forbidden { reason }, matches);
+        }
+        if let Some(script) = shell_script(command)
+            && self.may_forbid_script(script)
+        {
+            let reason = "the script could not be split into plain commands to check \
+                          against the exec policy's deny rules"
+                .to_string();
+            return (ExecPolicyDecision::Ask { reason }, matches);
+        }
+        if (wrapped_commands.is_none() || appends_arguments(command))
+            && self.may_forbid_script(&command.join(" "))
+        {
+            let reason = format!(
+                "the command `{program}` runs could not be worked out to check against the \
+                 exec policy's deny rules"
+            );
+            return (ExecPolicyDecision::Ask { reason }, matches);
+        }
+        if let Some(reason) = wrapped_ask {
+            return (ExecPolicyDecision::Ask { reason }, matches);
+        }
+
+        let mut decision = ExecPolicyDecision::NoMatch;
+        let matched = matches
+            .iter()
+            .filter_map(|rule_match| match &rule_match.result {
+                MatchedExec::Match { exec } => Some(exec),
+                MatchedExec::Forbidden { .. } => None,
+            });
+        for exec in matched {
+            match unwritable_path(exec, sandbox_policy, cwd) {
+                None => {
+                    decision = ExecPolicyDecision::Allow;
+                    break;
+                }
+                Some(path) => {
+                    decision = ExecPolicyDecision::Ask {
+                        reason: format!(
+                            "`{program}` may write to {}, which is outside the writable roots",
+                            path.display()
+                        ),
+                    };
+                }
+            }
+        }
+        (decision, matches)
+    }
+
+    /// Checks a command run by a wrapper program, splitting it if it is a
+    /// shell script.
+    fn evaluate_wrapped(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> (ExecPolicyDecision, Vec<RuleMatch>) {
+        split_commands(command)
+            .iter()
+            .map(|cmd| self.evaluate_single(cmd, sandbox_policy, cwd))
+            .reduce(|(a, mut a_matches), (b, b_matches)| {
+                a_matches.extend(b_matches);
+                (combine_decisions(a, b), a_matches)
+            })
+            .unwrap_or((ExecPolicyDecision::NoMatch, Vec::new()))
+    }
+
+    fn may_forbid_script(&self, script: &str) -> bool {
+        self.policies
+            .iter()
+            .any(|loaded| loaded.policy.may_forbid_script(script))
+    }
+}
+
+/// Splits shell scripts (`bash -lc "..."`, `sh -c "..."`, ...) consisting
+/// solely of plain commands into those commands, recursively; any other
+/// command is returned as is.
+pub(crate) fn split_commands(command: &[String]) -> Vec<Vec<String>> {
+    if let Some(script) = shell_script(command)
+        && let Some(tree) = try_parse_bash(script)
+        && let Some(mut all_commands) = try_parse_word_only_commands_sequence(&tree, script)
+        && !all_commands.is_empty()
+    {
+        // The parser yields the commands last to first.
+        all_commands.reverse();
+        return all_commands
+            .iter()
+            .flat_map(|command| split_commands(command))
+            .collect();
+    }
+    vec![command.to_vec()]
+}
+
+/// The pipelines of two or more commands of the shell scripts `command` runs,
+/// directly, through a wrapper or in a nested shell, each as its commands in
+/// order.
+fn pipelines(command: &[String]) -> Vec<Vec<Vec<String>>> {
+    let mut found = Vec::new();
+    if let Some(script) = shell_script(command)
+        && let Some(tree) = try_parse_bash(script)
+        && let Some(parsed) = try_parse_word_only_pipelines(&tree, script)
+    {
+        for pipeline in parsed {
+            found.extend(pipeline.iter().flat_map(|command| pipelines(command)));
+            if pipeline.len() > 1 {
+                found.push(pipeline);
+            }
+        }
+    }
+    for wrapped in wrapped_commands(command).into_iter().flatten() {
+        found.extend(pipelines(wrapped));
+    }
+    found
+}
+
+/// `command` followed by the commands it runs as a [wrapper](WRAPPERS),
+/// recursively, e.g. `sudo env sh`, `env sh` and `sh` for `sudo env sh`.
+fn unwrapped(command: &[String]) -> Vec<Vec<String>> {
+    let mut commands = vec![command.to_vec()];
+    for wrapped in wrapped_commands(command).into_iter().flatten() {
+        commands.extend(unwrapped(wrapped));
+    }
+    commands
+}
+
+/// The script of a shell invocation such as `bash -lc "..."`, `sh -c "..."`
+/// or `/bin/zsh -e -c "..."`.
+pub(crate) fn shell_script(command: &[String]) -> Option<&str> {
+    let (program, args) = command.split_first()?;
+    if !SHELLS.contains(&program_name(program)) {
+        return None;
+    }
+    let flag = args.iter().position(|arg| {
+        arg.strip_prefix('-').is_some_and(|flags| {
+            flags.contains('c') && flags.chars().all(|c| c.is_ascii_alphabetic())
+        })
+    })?;
+    args.get(flag + 1).map(String::as_str)
+}
+
+/// The commands run by a [wrapper](WRAPPERS) or by `find -exec`; empty for
+/// any other program. `None` when the wrapper's arguments include an option
+/// it is not known to take, so the command cannot be told apart from the
+/// option's value.
+fn wrapped_commands(command: &[String]) -> Option<Vec<&[String]>> {
+    let Some((program, args)) = command.split_first() else {
+        return Some(Vec::new());
+    };
+    let program = program_name(program);
+    if program == "find" {
+        let mut commands = Vec::new();
+        let mut rest = args;
+        while let Some(start) = rest
+            .iter()
+            .position(|arg| FIND_EXEC_OPTIONS.contains(&arg.as_str()))
+        {
+            rest = &rest[start + 1..];
+            let end = rest
+                .iter()
+                .position(|arg| arg == ";" || arg == "+")
+                .unwrap_or(rest.len());
+            commands.push(&rest[..end]);
+        }
+        return Some(commands);
+    }
+    let Some(wrapper) = WRAPPERS.iter().find(|wrapper| wrapper.name == program) else {
+        return Some(Vec::new());
+    };
+
+    let mut idx = 0;
+    while let Some(arg) = args.get(idx) {
+        let option = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        if arg == "--" {
+            idx += 1;
+            break;
+        } else if wrapper.name == "env" && !arg.starts_with('-') && arg.contains('=') {
+            // `env NAME=VALUE command`
+            idx += 1;
+        } else if !arg.starts_with('-') || arg == "-" {
+            break;
+        } else if wrapper.flags.contains(&arg.as_str())
+            // `nice -10`
+            || (wrapper.name == "nice" && arg[1..].chars().all(|c| c.is_ascii_digit()))
+        {
+            idx += 1;
+        } else if wrapper.options_with_value.contains(&arg.as_str()) {
+            idx += 2;
+        } else if wrapper.options_with_value.contains(&option)
+            || wrapper
+                .options_with_value
+                .iter()
+                .any(|opt| opt.len() == 2 && arg.len() > 2 && arg.starts_with(opt))
+        {
+            idx += 1;
+        } else {
+            return None;
+        }
+    }
+    idx += wrapper.operands;
+    Some(
+        args.get(idx..)
+            .filter(|command| !command.is_empty())
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// Whether the wrapped command gets more arguments when it runs, as with
+/// `xargs` and `find -exec`, so that a deny rule may apply to it although it
+/// does not match the arguments that can be seen.
+fn appends_arguments(command: &[String]) -> bool {
+    match command.split_first() {
+        Some((program, _)) if program_name(program) == "xargs" => true,
+        Some((program, args)) if program_name(program) == "find" => args
+            .iter()
+            .any(|arg| FIND_EXEC_OPTIONS.contains(&arg.as_str())),
+        _ => false,
+    }
+}
+
+/// The file name of `program`, e.g. `bash` for `/bin/bash`.
+fn program_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program)
+}
+
+/// Directories searched for user-authored policy files: `$CODEX_HOME/policy`
+/// and `<cwd>/.codex/policy`.
+pub fn policy_dirs(codex_home: &Path, cwd: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![codex_home.join(POLICY_DIR)];
+    let repo_dir = cwd.join(".codex").join(POLICY_DIR);
+    if !dirs.contains(&repo_dir) {
+        dirs.push(repo_dir);
+    }
+    dirs
+}
+
+/// Runs the `should_match` and `should_not_match` examples of every policy
+/// file in `dir`.
+pub fn test_policy_dir(dir: &Path) -> Vec<PolicyFileTest> {
+    policy_files_in(dir)
+        .into_iter()
+        .map(|path| match parse_policy_file(&path) {
+            Ok(policy) => PolicyFileTest {
+                path,
+                error: None,
+                failed_should_match: policy.check_each_good_list_individually(),
+                passed_should_not_match: policy.check_each_bad_list_individually(),
+            },
+            Err(e) => PolicyFileTest {
+                path,
+                error: Some(format!("{e:#}")),
+                failed_should_match: Vec::new(),
+                passed_should_not_match: Vec::new(),
+            },
+        })
+        .collect()
+}
+
+/// Combines the decisions for the individual commands of a shell script: a
+/// single forbidden command forbids the whole script, and the script is only
+/// allowed if every command is.
+fn combine_decisions(a: ExecPolicyDecision, b: ExecPolicyDecision) -> ExecPolicyDecision {
+    use ExecPolicyDecision::*;
+    match (a, b) {
+        (f @ Forbidden { .. }, _) | (_, f @ Forbidden { .. }) => f,
+        (ask @ Ask { .. }, _) | (_, ask @ Ask { .. }) => ask,
+        (NoMatch, _) | (_, NoMatch) => NoMatch,
+        (Allow, Allow) => Allow,
+    }
+}
+
+/// Returns the first file that `exec` may write which is not writable under
+/// `sandbox_policy`, if any.
+fn unwritable_path(
+    exec: &ValidExec,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Option<PathBuf> {
+    if matches!(sandbox_policy, SandboxPolicy::DangerFullAccess) {
+        return None;
+    }
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    exec.args
+        .iter()
+        .map(|arg| (&arg.r#type, &arg.value))
+        .chain(exec.opts.iter().map(|opt| (&opt.r#type, &opt.value)))
+        .filter(|(arg_type, _)| matches!(arg_type, ArgType::WriteableFile))
+        .map(|(_, value)| normalize(&cwd.join(value)))
+        .find(|path| {
+            !writable_roots
+                .iter()
+                .any(|root| root.is_path_writable(path))
+        })
+}
+
+/// Removes `.` and resolves `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn policy_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == POLICY_FILE_EXTENSION)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn parse_policy_file(path: &Path) -> anyhow::Result<Policy> {
+    let unparsed_policy = std::fs::read_to_string(path)?;
+    let policy_source = path.to_string_lossy().to_string();
+    PolicyParser::new(&policy_source, &unparsed_policy)
+        .parse()
+        .map_err(|e| e.into_anyhow())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const TEST_POLICY: &str = r#"
+define_program(
+    program="cp",
+    args=[ARG_RFILE, ARG_WFILE],
+)
+
+define_program(
+    program="shred",
+    args=[ARG_RFILES],
+    forbidden="shred destroys data",
+)
+"#;
+
+    fn cmd(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn workspace_write() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            deny_read: vec![],
+        }
+    }
+
+    #[test]
+    fn write_inside_workspace_is_allowed() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        assert_eq!(
+            ExecPolicyDecision::Allow,
+            policy.evaluate(
+                &cmd(&["cp", "a.txt", "b.txt"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn write_outside_workspace_asks() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        let decision = policy.evaluate(
+            &cmd(&["cp", "a.txt", "../b.txt"]),
+            &workspace_write(),
+            cwd.path(),
+        );
+        assert!(
+            matches!(decision, ExecPolicyDecision::Ask { .. }),
+            "{decision:?}"
+        );
+    }
+
+    #[test]
+    fn forbidden_rule_applies_to_full_path_and_bash_scripts() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        let forbidden = ExecPolicyDecision::Forbidden {
+            reason: "shred destroys data".to_string(),
+        };
+        assert_eq!(
+            forbidden,
+            policy.evaluate(
+                &cmd(&["/usr/bin/shred", "x"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+        assert_eq!(
+            forbidden,
+            policy.evaluate(
+                &cmd(&["bash", "-lc", "cp a b && shred a"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn deny_rule_overrides_allow_rule_from_another_policy() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[
+            TEST_POLICY,
+            r#"forbid_args(program="cp", args=["/etc/passwd"], reason="do not touch passwd")"#,
+        ]);
+        assert_eq!(
+            ExecPolicyDecision::Forbidden {
+                reason: "do not touch passwd".to_string(),
+            },
+            policy.evaluate(
+                &cmd(&["bash", "-lc", "cp /etc/passwd passwd.bak"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+        assert_eq!(
+            ExecPolicyDecision::Allow,
+            policy.evaluate(
+                &cmd(&["cp", "a.txt", "b.txt"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn explain_reports_each_command_and_resolved_args() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        let explanations = policy.explain(
+            &cmd(&["bash", "-lc", "cp a.txt b.txt && make"]),
+            &workspace_write(),
+            cwd.path(),
+        );
+
+        assert_eq!(explanations.len(), 2);
+        assert_eq!(explanations[0].command, cmd(&["cp", "a.txt", "b.txt"]));
+        assert_eq!(explanations[0].decision, ExecPolicyDecision::Allow);
+        let [
+            RuleMatch {
+                policy,
+                result: MatchedExec::Match { exec },
+            },
+        ] = explanations[0].matches.as_slice()
+        else {
+            panic!("unexpected matches: {:?}", explanations[0].matches);
+        };
+        assert_eq!(policy, "#test");
+        assert_eq!(
+            exec.args
+                .iter()
+                .map(|arg| (arg.value.as_str(), arg.r#type.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a.txt", ArgType::ReadableFile),
+                ("b.txt", ArgType::WriteableFile)
+            ]
+        );
+
+        assert_eq!(explanations[1].command, cmd(&["make"]));
+        assert_eq!(explanations[1].decision, ExecPolicyDecision::NoMatch);
+        assert!(explanations[1].matches.is_empty());
+    }
+
+    #[test]
+    fn test_policy_dir_runs_examples() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("team.policy"),
+            r#"
+define_program(
+    program="cp",
+    args=[ARG_RFILE, ARG_WFILE],
+    should_match=[["a", "b"], ["a"]],
+    should_not_match=[["a", "b", "c"]],
+)
+"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.policy"), "define_program(").unwrap();
+
+        let results = test_policy_dir(dir.path());
+        assert_eq!(results.len(), 2);
+        assert!(results[0].path.ends_with("broken.policy"));
+        assert!(results[0].error.is_some());
+        assert!(!results[0].passed());
+        assert_eq!(results[1].failed_should_match.len(), 1);
+        assert_eq!(results[1].failed_should_match[0].args, vec!["a"]);
+        assert!(results[1].passed_should_not_match.is_empty());
+    }
+
+    #[test]
+    fn unknown_program_has_no_match() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        assert_eq!(
+            ExecPolicyDecision::NoMatch,
+            policy.evaluate(&cmd(&["make"]), &workspace_write(), cwd.path())
+        );
+    }
+
+    #[test]
+    fn load_reports_unparseable_user_policy() {
+        let codex_home = TempDir::new().unwrap();
+        let cwd = TempDir::new().unwrap();
+        let policy_dir = codex_home.path().join(POLICY_DIR);
+        std::fs::create_dir_all(&policy_dir).unwrap();
+        std::fs::write(policy_dir.join("team.policy"), TEST_POLICY).unwrap();
+        std::fs::write(policy_dir.join("broken.policy"), "define_program(").unwrap();
+        std::fs::write(policy_dir.join("notes.txt"), "ignored").unwrap();
+
+        let (policy, errors) = ExecPolicy::load(codex_home.path(), cwd.path());
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].contains("broken.policy"));
+        // default.policy + team.policy
+        assert_eq!(policy.policies.len(), 2);
+        assert!(matches!(
+            policy.evaluate(&cmd(&["shred", "x"]), &workspace_write(), cwd.path()),
+            ExecPolicyDecision::Forbidden { .. }
+        ));
+    }
+
+    #[test]
+    fn unsplittable_script_asks_when_a_deny_rule_may_apply() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[TEST_POLICY]);
+        let decision = policy.evaluate(
+            &cmd(&["bash", "-lc", "f=$(ls); shred \"$f\""]),
+            &workspace_write(),
+            cwd.path(),
+        );
+        assert!(
+            matches!(decision, ExecPolicyDecision::Ask { .. }),
+            "{decision:?}"
+        );
+        assert_eq!(
+            ExecPolicyDecision::NoMatch,
+            policy.evaluate(
+                &cmd(&["bash", "-lc", "cargo test 2>&1 | tail"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn deny_rules_apply_to_other_shells_and_wrapped_commands() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[
+            TEST_POLICY,
+            r#"forbid_args(program="rm", args=["-rf", "/"], reason="no")"#,
+        ]);
+        let forbidden = ExecPolicyDecision::Forbidden {
+            reason: "no".to_string(),
+        };
+        for command in [
+            &["sh", "-c", "rm -rf /"][..],
+            &["bash", "-c", "rm -rf /"],
+            &["zsh", "-lc", "rm -rf /"],
+            &["/bin/bash", "-lc", "rm -rf /"],
+            &["bash", "-e", "-c", "ls && rm -rf /"],
+            &["env", "FOO=1", "rm", "-rf", "/"],
+            &["/usr/bin/env", "-i", "rm", "-rf", "/"],
+            &["sudo", "-u", "root", "rm", "-rf", "/"],
+            &["xargs", "-0", "rm", "-rf", "/"],
+            &["timeout", "--signal=KILL", "10", "rm", "-rf", "/"],
+            &["nice", "-n", "5", "rm", "-rf", "/"],
+            &["nohup", "rm", "-rf", "/"],
+            &["find", ".", "-exec", "rm", "-rf", "/", ";"],
+            &["sudo", "sh", "-c", "timeout 5 rm -rf /"],
+            &["bash", "-lc", "sudo env FOO=1 rm -rf /"],
+        ] {
+            assert_eq!(
+                forbidden,
+                policy.evaluate(&cmd(command), &workspace_write(), cwd.path()),
+                "{command:?}"
+            );
+        }
+
+        for command in [
+            &["env", "FOO=1", "cargo", "test"][..],
+            &["time", "grep", "-r", "rm", "."],
+            &["find", ".", "-name", "rm"],
+            &["sh", "-c", "rm -rf build"],
+        ] {
+            assert_eq!(
+                ExecPolicyDecision::NoMatch,
+                policy.evaluate(&cmd(command), &workspace_write(), cwd.path()),
+                "{command:?}"
+            );
+        }
+        // Allow rules only apply to the command as it is run.
+        assert_eq!(
+            ExecPolicyDecision::NoMatch,
+            policy.evaluate(
+                &cmd(&["sudo", "cp", "a.txt", "b.txt"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn wrapped_commands_that_cannot_be_worked_out_ask() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[
+            TEST_POLICY,
+            r#"forbid_args(program="rm", args=["-rf", "/"], reason="no")"#,
+        ]);
+        for command in [
+            // The arguments come from stdin or from `find`.
+            &["bash", "-lc", "echo / | xargs rm -rf"][..],
+            &[
+                "find",
+                "/",
+                "-maxdepth",
+                "0",
+                "-exec",
+                "rm",
+                "-rf",
+                "{}",
+                "+",
+            ],
+            // Unknown option: `--wat` may take `rm` as its value.
+            &["sudo", "--wat", "rm", "-rf", "/"],
+            &["env", "-S", "rm -rf /"],
+        ] {
+            let decision = policy.evaluate(&cmd(command), &workspace_write(), cwd.path());
+            assert!(
+                matches!(decision, ExecPolicyDecision::Ask { .. }),
+                "{command:?}: {decision:?}"
+            );
+        }
+        assert_eq!(
+            ExecPolicyDecision::NoMatch,
+            policy.evaluate(
+                &cmd(&["bash", "-lc", "git ls-files | xargs wc -l"]),
+                &workspace_write(),
+                cwd.path()
+            )
+        );
+    }
+
+    #[test]
+    fn deny_pipeline_refuses_piping_a_download_into_a_shell() {
+        let cwd = TempDir::new().unwrap();
+        let policy = ExecPolicy::from_sources(&[
+            TEST_POLICY,
+            r#"deny_pipeline(source="curl", sinks=["sh", "bash"], reason="no")"#,
+        ]);
+        let forbidden = ExecPolicyDecision::Forbidden {
+            reason: "no".to_string(),
+        };
+        for script in [
+            "curl x | sh",
+            "curl -fsSL x | tee install.sh | /bin/bash",
+            "curl x | sudo sh",
+            "ls && sh -c 'curl x | bash'",
+        ] {
+            assert_eq!(
+                forbidden,
+                policy.evaluate(
+                    &cmd(&["bash", "-lc", script]),
+                    &workspace_write(),
+                    cwd.path()
+                ),
+                "{script}"
+            );
+        }
+        // Scripts that cannot be split ask instead.
+        let decision = policy.evaluate(
+            &cmd(&["bash", "-lc", "curl \"$URL\" | sh"]),
+            &workspace_write(),
+            cwd.path(),
+        );
+        assert!(
+            matches!(decision, ExecPolicyDecision::Ask { .. }),
+            "{decision:?}"
+        );
+
+        for script in ["curl x | jq .", "curl -o install.sh x; sh install.sh"] {
+            assert_eq!(
+                ExecPolicyDecision::NoMatch,
+                policy.evaluate(
+                    &cmd(&["bash", "-lc", script]),
+                    &workspace_write(),
+                    cwd.path()
+                ),
+                "{script}"
+            );
+        }
+
+        let explanations = policy.explain(
+            &cmd(&["bash", "-lc", "curl x | sh"]),
+            &workspace_write(),
+            cwd.path(),
+        );
+        assert_eq!(explanations.len(), 3);
+        assert_eq!(explanations[2].command, cmd(&["curl", "x", "|", "sh"]));
+        assert_eq!(explanations[2].decision, forbidden);
+    }
+
+    #[test]
+    fn repo_policies_only_deny_until_the_project_is_trusted() {
+        let codex_home = TempDir::new().unwrap();
+        let cwd = TempDir::new().unwrap();
+        let policy_dir = cwd.path().join(".codex").join(POLICY_DIR);
+        std::fs::create_dir_all(&policy_dir).unwrap();
+        std::fs::write(
+            policy_dir.join("repo.policy"),
+            format!("{TEST_POLICY}\ndefine_program(program=\"make\", args=[])\n"),
+        )
+        .unwrap();
+        let make = cmd(&["make"]);
+
+        let (policy, errors) = ExecPolicy::load(codex_home.path(), cwd.path());
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(
+            ExecPolicyDecision::NoMatch,
+            policy.evaluate(&make, &workspace_write(), cwd.path())
+        );
+        assert!(matches!(
+            policy.evaluate(&cmd(&["shred", "x"]), &workspace_write(), cwd.path()),
+            ExecPolicyDecision::Forbidden { .. }
+        ));
+
+        crate::config::set_project_trusted(codex_home.path(), cwd.path()).unwrap();
+        let (policy, _) = ExecPolicy::load(codex_home.path(), cwd.path());
+        assert_eq!(
+            ExecPolicyDecision::Allow,
+            policy.evaluate(&make, &workspace_write(), cwd.path())
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Advisory locks on the files under `~/.codex` that several Codex processes
+//! may write at the same time (message history, audit log, approval history,
+//! MCP tool pins).
+//!
+//! A lock held by another process is retried a few times rather than waited
+//! for indefinitely, so that a stuck process cannot hang every other session.
+//! The retries sleep on the current thread: call these from blocking code.
+
+use std::fs::File;
+use std::fs::TryLockError;
+use std::time::Duration;
+
+const MAX_RETRIES: usize = 10;
+const RETRY_SLEEP: Duration = Duration::from_millis(100);
+
+/// Acquire an exclusive lock on `file`. `what` names the file in the error
+/// returned when the lock stays held, e.g. "audit log".
+pub(crate) fn lock_exclusive(file: &File, what: &str) -> std::io::Result<()> {
+    with_retry(|| file.try_lock(), "exclusive", what)
+}
+
+/// Acquire a shared lock on `file`, see [`lock_exclusive`].
+#[cfg(unix)]
+pub(crate) fn lock_shared(file: &File, what: &str) -> std::io::Result<()> {
+    with_retry(|| file.try_lock_shared(), "shared", what)
+}
+
+fn with_retry(
+    try_lock: impl Fn() -> Result<(), TryLockError>,
+    kind: &str,
+    what: &str,
+) -> std::io::Result<()> {
+    for _ in 0..MAX_RETRIES {
+        match try_lock() {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) => std::thread::sleep(RETRY_SLEEP),
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::WouldBlock,
+        format!("could not acquire {kind} lock on {what} after multiple attempts"),
+    ))
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use crate::codex::Session;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::FindingEvent;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+
+pub use codex_protocol::finding_tool::Finding;
+pub use codex_protocol::finding_tool::FindingSeverity;
+
+pub(crate) const REPORT_FINDING_TOOL_NAME: &str = "report_finding";
+
+pub(crate) static FINDING_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "rule_id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Short, stable identifier for the kind of issue, e.g. sql-injection".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "severity".to_string(),
+        JsonSchema::String {
+            description: Some("One of: critical, high, medium, low, info".to_string()),
+        },
+    );
+    properties.insert(
+        "cwe".to_string(),
+        JsonSchema::String {
+            description: Some("CWE identifier, e.g. CWE-89, if one applies".to_string()),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "File containing the issue, relative to the working directory".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "start_line".to_string(),
+        JsonSchema::Number {
+            description: Some("First affected line (1-based)".to_string()),
+        },
+    );
+    properties.insert(
+        "end_line".to_string(),
+        JsonSchema::Number {
+            description: Some("Last affected line (1-based, inclusive)".to_string()),
+        },
+    );
+    properties.insert(
+        "description".to_string(),
+        JsonSchema::String {
+            description: Some("What the issue is and why it matters".to_string()),
+        },
+    );
+    properties.insert(
+        "remediation".to_string(),
+        JsonSchema::String {
+            description: Some("How to fix the issue".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: REPORT_FINDING_TOOL_NAME.to_string(),
+        description: r#"Reports a security or correctness issue found in the code under review.
+Call it once per distinct issue, as soon as you have confirmed it. Reported findings are shown to the user and exported to code-scanning tools.
+"#
+        .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![
+                "rule_id".to_string(),
+                "severity".to_string(),
+                "path".to_string(),
+                "description".to_string(),
+            ]),
+            additional_properties: Some(false),
+        },
+    })
+});
+
+/// Records a finding for the session and forwards it to clients as
+/// `EventMsg::Finding`. Reporting the same rule at the same location again is
+/// acknowledged without emitting a second event.
+pub(crate) async fn handle_report_finding(
+    session: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let finding = match parse_report_finding_arguments(&arguments) {
+        Ok(finding) => finding,
+        Err(message) => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: message,
+                    success: None,
+                },
+            };
+        }
+    };
+
+    let content = match session.record_finding(finding.clone()) {
+        Ok(number) => {
+            session
+                .send_event(Event {
+                    id: sub_id,
+                    msg: EventMsg::Finding(FindingEvent { number, finding }),
+                })
+                .await;
+            format!("Recorded finding #{number}")
+        }
+        Err(number) => format!("Already reported as finding #{number}"),
+    };
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(true),
+        },
+    }
+}
+
+fn parse_report_finding_arguments(arguments: &str) -> Result<Finding, String> {
+    let finding = serde_json::from_str::<Finding>(arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))?;
+    if finding.rule_id.trim().is_empty() || finding.path.trim().is_empty() {
+        return Err("rule_id and path must not be empty".to_string());
+    }
+    match (finding.start_line, finding.end_line) {
+        (Some(0), _) | (_, Some(0)) => Err("line numbers are 1-based".to_string()),
+        (None, Some(_)) => Err("end_line requires start_line".to_string()),
+        (Some(start), Some(end)) if end < start => {
+            Err(format!("end_line {end} is before start_line {start}"))
+        }
+        _ => Ok(finding),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_and_validates_line_ranges() {
+        let finding = parse_report_finding_arguments(
+            r#"{"rule_id":"sql-injection","severity":"high","cwe":"CWE-89","path":"src/db.rs","start_line":10,"end_line":12,"description":"query built with format!"}"#,
+        )
+        .unwrap();
+        assert_eq!(finding.severity, FindingSeverity::High);
+        assert_eq!(finding.cwe.as_deref(), Some("CWE-89"));
+        assert_eq!((finding.start_line, finding.end_line), (Some(10), Some(12)));
+
+        assert_eq!(
+            parse_report_finding_arguments(
+                r#"{"rule_id":"x","severity":"low","path":"a","start_line":5,"end_line":4,"description":"d"}"#,
+            ),
+            Err("end_line 4 is before start_line 5".to_string())
+        );
+        assert!(
+            parse_report_finding_arguments(
+                r#"{"rule_id":"x","severity":"urgent","path":"a","description":"d"}"#
+            )
+            .is_err()
+        );
+    }
+}
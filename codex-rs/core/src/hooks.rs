@@ -0,0 +1,353 @@
+//! User commands run at points of the session lifecycle.
+//!
+//! Each hook configured under `[hooks]` is started in the working directory
+//! of the current turn with a JSON description of the event written to its stdin:
+//!
+//! ```text
+//! {"session_id":"<uuid>","event":"pre_exec","call_id":"call_1","command":["cargo","test"],"cwd":"/repo"}
+//! ```
+//!
+//! Hooks for an event run one after the other and stop at the first that
+//! exits unsuccessfully. A failing `pre_exec` or `pre_patch` hook blocks the
+//! action, and a failing `post_exec` or `post_patch` hook marks it as failed;
+//! either way the hook's stderr is reported to the model. Failures of the
+//! other hooks are only logged.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config_types::HookCommand;
+use crate::config_types::Hooks;
+use crate::protocol::FileChange;
+
+/// Used when a hook does not set `timeout_ms`.
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest stderr excerpt reported for a failing hook.
+const HOOK_STDERR_MAX_BYTES: usize = 8 * 1024;
+
+/// An event hooks can be attached to, serialized as the hook's payload.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum HookEvent {
+    SessionStart {
+        model: String,
+        cwd: PathBuf,
+    },
+    SessionEnd,
+    PreExec {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+    },
+    PostExec {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+        exit_code: i32,
+        /// Interleaved stdout and stderr of the command.
+        output: String,
+    },
+    PrePatch {
+        call_id: String,
+        changes: HashMap<PathBuf, FileChange>,
+    },
+    PostPatch {
+        call_id: String,
+        changes: HashMap<PathBuf, FileChange>,
+        success: bool,
+    },
+    ApprovalRequest {
+        call_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        changes: Option<HashMap<PathBuf, FileChange>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    TurnComplete {
+        turn_id: String,
+        last_assistant_message: Option<String>,
+    },
+}
+
+impl HookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            HookEvent::SessionStart { .. } => "session_start",
+            HookEvent::SessionEnd => "session_end",
+            HookEvent::PreExec { .. } => "pre_exec",
+            HookEvent::PostExec { .. } => "post_exec",
+            HookEvent::PrePatch { .. } => "pre_patch",
+            HookEvent::PostPatch { .. } => "post_patch",
+            HookEvent::ApprovalRequest { .. } => "approval_request",
+            HookEvent::TurnComplete { .. } => "turn_complete",
+        }
+    }
+
+    fn hooks<'a>(&self, hooks: &'a Hooks) -> &'a [HookCommand] {
+        match self {
+            HookEvent::SessionStart { .. } => &hooks.session_start,
+            HookEvent::SessionEnd => &hooks.session_end,
+            HookEvent::PreExec { .. } => &hooks.pre_exec,
+            HookEvent::PostExec { .. } => &hooks.post_exec,
+            HookEvent::PrePatch { .. } => &hooks.pre_patch,
+            HookEvent::PostPatch { .. } => &hooks.post_patch,
+            HookEvent::ApprovalRequest { .. } => &hooks.approval_request,
+            HookEvent::TurnComplete { .. } => &hooks.turn_complete,
+        }
+    }
+}
+
+/// A hook that did not exit successfully.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HookFailure {
+    event: &'static str,
+    program: String,
+    /// The hook's stderr, or why it could not be run to completion.
+    reason: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hook `{}` failed: {}",
+            self.event, self.program, self.reason
+        )
+    }
+}
+
+/// Runs the hooks configured for a session.
+#[derive(Debug)]
+pub(crate) struct HookRunner {
+    hooks: Hooks,
+    session_id: Uuid,
+}
+
+impl HookRunner {
+    pub(crate) fn new(hooks: Hooks, session_id: Uuid) -> Self {
+        Self { hooks, session_id }
+    }
+
+    /// Runs the hooks for `event` in `cwd` in order, stopping at the first
+    /// that fails.
+    pub(crate) async fn run(&self, event: HookEvent, cwd: &Path) -> Result<(), HookFailure> {
+        let hooks = event.hooks(&self.hooks);
+        if hooks.is_empty() {
+            return Ok(());
+        }
+
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            session_id: Uuid,
+            #[serde(flatten)]
+            event: &'a HookEvent,
+        }
+        let payload = serde_json::to_vec(&Payload {
+            session_id: self.session_id,
+            event: &event,
+        })
+        .unwrap_or_default();
+
+        for hook in hooks {
+            if let Err(reason) = self.run_hook(hook, &payload, cwd).await {
+                return Err(HookFailure {
+                    event: event.name(),
+                    program: hook.command.join(" "),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`HookRunner::run`], for events that cannot be blocked: failures
+    /// are logged and otherwise ignored.
+    pub(crate) async fn notify(&self, event: HookEvent, cwd: &Path) {
+        if let Err(failure) = self.run(event, cwd).await {
+            warn!("{failure}");
+        }
+    }
+
+    async fn run_hook(&self, hook: &HookCommand, payload: &[u8], cwd: &Path) -> Result<(), String> {
+        let Some((program, args)) = hook.command.split_first() else {
+            return Err("empty command".to_string());
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("failed to start: {e}"))?;
+
+        // The payload is written from its own task: a hook is free to ignore
+        // it, and must not block us on a full pipe past its timeout.
+        if let Some(mut stdin) = child.stdin.take() {
+            let payload = payload.to_vec();
+            tokio::spawn(async move {
+                let _ = stdin.write_all(&payload).await;
+            });
+        }
+
+        let timeout = hook
+            .timeout_ms
+            .map_or(DEFAULT_HOOK_TIMEOUT, Duration::from_millis);
+        let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Err(format!("failed to wait for hook: {e}")),
+            Err(_) => return Err(format!("timed out after {}ms", timeout.as_millis())),
+        };
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        if stderr.is_empty() {
+            return Err(format!("exited with {}", output.status));
+        }
+        let mut end = stderr.len().min(HOOK_STDERR_MAX_BYTES);
+        while !stderr.is_char_boundary(end) {
+            end -= 1;
+        }
+        Err(stderr[..end].to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn hook(script: &str) -> HookCommand {
+        HookCommand {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout_ms: None,
+        }
+    }
+
+    fn pre_exec() -> HookEvent {
+        HookEvent::PreExec {
+            call_id: "call-1".to_string(),
+            command: vec!["rm".to_string(), "-rf".to_string(), "target".to_string()],
+            cwd: PathBuf::from("/repo"),
+        }
+    }
+
+    #[tokio::test]
+    async fn hook_receives_payload_on_stdin() {
+        let dir = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let runner = HookRunner::new(
+            Hooks {
+                pre_exec: vec![hook("cat > payload.json")],
+                ..Default::default()
+            },
+            session_id,
+        );
+
+        assert_eq!(runner.run(pre_exec(), dir.path()).await, Ok(()));
+        let payload: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("payload.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "session_id": session_id,
+                "event": "pre_exec",
+                "call_id": "call-1",
+                "command": ["rm", "-rf", "target"],
+                "cwd": "/repo",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn first_failing_hook_reports_its_stderr() {
+        let dir = TempDir::new().unwrap();
+        let runner = HookRunner::new(
+            Hooks {
+                pre_exec: vec![
+                    hook("echo 'rm -rf is not allowed' >&2; exit 2"),
+                    hook("touch second-hook-ran"),
+                ],
+                ..Default::default()
+            },
+            Uuid::new_v4(),
+        );
+
+        let failure = runner.run(pre_exec(), dir.path()).await.unwrap_err();
+        assert_eq!(
+            failure.to_string(),
+            "pre_exec hook `sh -c echo 'rm -rf is not allowed' >&2; exit 2` failed: rm -rf is not allowed"
+        );
+        assert!(!dir.path().join("second-hook-ran").exists());
+    }
+
+    #[tokio::test]
+    async fn slow_hook_times_out() {
+        let runner = HookRunner::new(
+            Hooks {
+                session_end: vec![HookCommand {
+                    timeout_ms: Some(100),
+                    ..hook("sleep 5")
+                }],
+                ..Default::default()
+            },
+            Uuid::new_v4(),
+        );
+
+        let failure = runner
+            .run(HookEvent::SessionEnd, &std::env::temp_dir())
+            .await
+            .unwrap_err();
+        assert_eq!(failure.reason, "timed out after 100ms");
+    }
+
+    #[tokio::test]
+    async fn hook_that_ignores_a_large_payload_still_times_out() {
+        let runner = HookRunner::new(
+            Hooks {
+                post_exec: vec![HookCommand {
+                    timeout_ms: Some(100),
+                    ..hook("sleep 5")
+                }],
+                ..Default::default()
+            },
+            Uuid::new_v4(),
+        );
+        // Far more than a pipe buffer holds.
+        let event = HookEvent::PostExec {
+            call_id: "call-1".to_string(),
+            command: vec!["cat".to_string(), "big.log".to_string()],
+            cwd: PathBuf::from("/repo"),
+            exit_code: 0,
+            output: "x".repeat(4 * 1024 * 1024),
+        };
+
+        let failure = tokio::time::timeout(
+            Duration::from_secs(2),
+            runner.run(event, &std::env::temp_dir()),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+        assert_eq!(failure.reason, "timed out after 100ms");
+    }
+}
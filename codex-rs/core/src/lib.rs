@@ -6,8 +6,12 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
 mod apply_patch;
+pub mod approval_grants;
+pub mod approval_history;
+pub mod audit;
 mod bash;
 mod chat_completions;
+mod checkpoints;
 mod client;
 mod client_common;
 pub mod codex;
@@ -23,13 +27,19 @@ pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+pub mod exec_policy;
+mod file_lock;
 mod flags;
 pub mod git_info;
+mod hooks;
 mod is_safe_command;
 pub mod landlock;
 mod mcp_connection_manager;
+mod mcp_input;
 mod mcp_tool_call;
+pub mod mcp_tool_pins;
 mod message_history;
+mod messages_api;
 mod model_provider_info;
 pub mod parse_command;
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
@@ -40,19 +50,28 @@ pub use model_provider_info::create_oss_provider_with_base_url;
 mod conversation_manager;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::NewConversation;
+pub mod finding_tool;
 pub mod model_family;
 mod openai_model_info;
 mod openai_tools;
 pub mod plan_tool;
 pub mod project_doc;
+pub mod provider_failover;
+mod redaction;
 mod rollout;
+pub use rollout::SessionSummary;
+pub use rollout::find_session;
+pub use rollout::list_sessions;
+pub use rollout::read_session_usage;
 pub(crate) mod safety;
 pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
+mod task_budget;
 pub mod terminal;
 mod tool_apply_patch;
 pub mod turn_diff_tracker;
+pub mod usage;
 pub mod user_agent;
 mod user_notification;
 pub mod util;
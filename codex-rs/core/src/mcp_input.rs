@@ -0,0 +1,186 @@
+//! Resolution of the MCP items of user input.
+//!
+//! Front-ends reference MCP resources (`@server:uri` mentions) and prompts
+//! (`/server:prompt` commands) with [`InputItem::McpResource`] and
+//! [`InputItem::McpPrompt`]. Before the input reaches the model, the resource
+//! contents are read and the prompt is expanded into its messages.
+
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::ReadResourceResultContents;
+
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::protocol::InputItem;
+
+/// Replaces the MCP items of `items` with the text and images they refer to.
+/// Items that cannot be resolved are dropped and described in the returned
+/// error messages.
+pub(crate) async fn resolve_mcp_input(
+    manager: &McpConnectionManager,
+    items: Vec<InputItem>,
+) -> (Vec<InputItem>, Vec<String>) {
+    let mut resolved = Vec::with_capacity(items.len());
+    let mut errors = Vec::new();
+    for item in items {
+        match item {
+            InputItem::McpResource { server, uri } => {
+                match manager.read_resource(&server, &uri).await {
+                    Ok(result) => resolved.extend(
+                        result
+                            .contents
+                            .into_iter()
+                            .map(|contents| resource_input(&server, contents)),
+                    ),
+                    Err(e) => errors.push(format!("{e:#}")),
+                }
+            }
+            InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => match manager.get_prompt(&server, &name, &arguments).await {
+                Ok(result) => resolved.extend(
+                    result
+                        .messages
+                        .into_iter()
+                        .filter_map(|message| content_input(&server, message.content)),
+                ),
+                Err(e) => errors.push(format!("{e:#}")),
+            },
+            item => resolved.push(item),
+        }
+    }
+    (resolved, errors)
+}
+
+/// Text resources are wrapped in a `<resource>` block naming their origin,
+/// images are attached as images and other binary contents are only named.
+fn resource_input(server: &str, contents: ReadResourceResultContents) -> InputItem {
+    match contents {
+        ReadResourceResultContents::TextResourceContents(text) => InputItem::Text {
+            text: format!(
+                "<resource server=\"{server}\" uri=\"{}\">\n{}\n</resource>",
+                text.uri, text.text
+            ),
+        },
+        ReadResourceResultContents::BlobResourceContents(blob) => match blob.mime_type {
+            Some(mime) if mime.starts_with("image/") => InputItem::Image {
+                image_url: format!("data:{mime};base64,{}", blob.blob),
+            },
+            mime => InputItem::Text {
+                text: format!(
+                    "<resource server=\"{server}\" uri=\"{}\" mime_type=\"{}\">\n(binary contents omitted)\n</resource>",
+                    blob.uri,
+                    mime.as_deref().unwrap_or("application/octet-stream")
+                ),
+            },
+        },
+    }
+}
+
+/// Converts one message of an expanded prompt. Audio is not supported by
+/// the model input and is skipped.
+fn content_input(server: &str, content: ContentBlock) -> Option<InputItem> {
+    match content {
+        ContentBlock::TextContent(text) => Some(InputItem::Text { text: text.text }),
+        ContentBlock::ImageContent(image) => Some(InputItem::Image {
+            image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+        }),
+        ContentBlock::AudioContent(_) => None,
+        ContentBlock::ResourceLink(link) => Some(InputItem::Text {
+            text: format!("<resource server=\"{server}\" uri=\"{}\" />", link.uri),
+        }),
+        ContentBlock::EmbeddedResource(embedded) => {
+            let contents = match embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => {
+                    ReadResourceResultContents::TextResourceContents(text)
+                }
+                EmbeddedResourceResource::BlobResourceContents(blob) => {
+                    ReadResourceResultContents::BlobResourceContents(blob)
+                }
+            };
+            Some(resource_input(server, contents))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::EmbeddedResource;
+    use mcp_types::TextContent;
+    use mcp_types::TextResourceContents;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn text_resources_are_wrapped_with_their_origin() {
+        let contents = ReadResourceResultContents::TextResourceContents(TextResourceContents {
+            mime_type: Some("text/markdown".to_string()),
+            text: "# Notes".to_string(),
+            uri: "file:///notes.md".to_string(),
+        });
+        assert_eq!(
+            resource_input("docs", contents),
+            InputItem::Text {
+                text: "<resource server=\"docs\" uri=\"file:///notes.md\">\n# Notes\n</resource>"
+                    .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn image_blobs_become_images() {
+        let blob = |mime_type: &str| {
+            ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                blob: "AAAA".to_string(),
+                mime_type: Some(mime_type.to_string()),
+                uri: "file:///logo".to_string(),
+            })
+        };
+        assert_eq!(
+            resource_input("docs", blob("image/png")),
+            InputItem::Image {
+                image_url: "data:image/png;base64,AAAA".to_string()
+            }
+        );
+        assert_eq!(
+            resource_input("docs", blob("application/pdf")),
+            InputItem::Text {
+                text: "<resource server=\"docs\" uri=\"file:///logo\" mime_type=\"application/pdf\">\n(binary contents omitted)\n</resource>"
+                    .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn prompt_messages_keep_text_and_embedded_resources() {
+        let text = ContentBlock::TextContent(TextContent {
+            annotations: None,
+            text: "Review this file".to_string(),
+            r#type: "text".to_string(),
+        });
+        let embedded = ContentBlock::EmbeddedResource(EmbeddedResource {
+            annotations: None,
+            resource: EmbeddedResourceResource::TextResourceContents(TextResourceContents {
+                mime_type: None,
+                text: "fn main() {}".to_string(),
+                uri: "file:///main.rs".to_string(),
+            }),
+            r#type: "resource".to_string(),
+        });
+        assert_eq!(
+            content_input("review", text),
+            Some(InputItem::Text {
+                text: "Review this file".to_string()
+            })
+        );
+        assert_eq!(
+            content_input("review", embedded),
+            Some(InputItem::Text {
+                text: "<resource server=\"review\" uri=\"file:///main.rs\">\nfn main() {}\n</resource>"
+                    .to_string()
+            })
+        );
+    }
+}
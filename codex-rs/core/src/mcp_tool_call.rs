@@ -1,19 +1,30 @@
 use std::time::Duration;
 use std::time::Instant;
 
+use codex_mcp_client::RequestTimedOut;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
+use serde_json::json;
 use tracing::error;
 
+use crate::audit::AuditEvent;
 use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::protocol::AskForApproval;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::ReviewDecision;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 
 /// Handles the specified tool call dispatches the appropriate
-/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`. If the
+/// configuration requires it, the user is asked to approve the call first.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -22,6 +33,7 @@ pub(crate) async fn handle_mcp_tool_call(
     tool_name: String,
     arguments: String,
     timeout: Option<Duration>,
+    turn_context: &TurnContext,
 ) -> ResponseInputItem {
     // Parse the `arguments` as JSON. An empty string is OK, but invalid JSON
     // is not.
@@ -49,6 +61,11 @@ pub(crate) async fn handle_mcp_tool_call(
         arguments: arguments_value.clone(),
     };
 
+    if let Some(rejected) = ensure_approved(sess, turn_context, sub_id, &call_id, &invocation).await
+    {
+        return rejected;
+    }
+
     let tool_call_begin_event = EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
         call_id: call_id.clone(),
         invocation: invocation.clone(),
@@ -60,7 +77,19 @@ pub(crate) async fn handle_mcp_tool_call(
     let result = sess
         .call_tool(&server, &tool_name, arguments_value.clone(), timeout)
         .await
-        .map_err(|e| format!("tool call error: {e}"));
+        .or_else(|e| match e.downcast_ref::<RequestTimedOut>() {
+            Some(timed_out) => Ok(timeout_result(timed_out.timeout)),
+            None => Err(format!("tool call error: {e}")),
+        });
+    sess.audit(AuditEvent::McpToolCall {
+        call_id: call_id.clone(),
+        server: server.clone(),
+        tool: tool_name.clone(),
+        arguments: arguments_value.clone(),
+        success: matches!(&result, Ok(result) if !result.is_error.unwrap_or(false)),
+        duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+    })
+    .await;
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         invocation,
@@ -73,6 +102,83 @@ pub(crate) async fn handle_mcp_tool_call(
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
+/// Ask the user to approve the call described by `invocation` if the
+/// configuration requires it. Returns the output reported to the model when
+/// the call must not run.
+async fn ensure_approved(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    invocation: &McpInvocation,
+) -> Option<ResponseInputItem> {
+    let McpInvocation { server, tool, .. } = invocation;
+    if !sess.mcp_tool_requires_approval(server, tool) {
+        return None;
+    }
+    let rejected = |content: String| {
+        Some(ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content,
+                success: Some(false),
+            },
+        })
+    };
+    if turn_context.approval_policy == AskForApproval::Never {
+        return rejected(format!(
+            "calling `{server}/{tool}` requires the user's approval, but approval requests are disabled"
+        ));
+    }
+
+    // The server's own annotation is shown, but does not exempt the call.
+    let reason = sess.mcp_tool_read_only_hint(server, tool).then(|| {
+        format!(
+            "`{server}` describes `{tool}` as read-only; list it in `read_only_tools` to run it without asking"
+        )
+    });
+    let rx_approve = sess
+        .request_mcp_tool_approval(
+            turn_context,
+            sub_id.to_string(),
+            call_id.to_string(),
+            invocation.clone(),
+            reason,
+        )
+        .await;
+    match rx_approve.await.unwrap_or_default() {
+        ReviewDecision::ApprovedForSession => {
+            sess.approve_mcp_tool_for_session(server, tool);
+            None
+        }
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedPrefixForSession
+        | ReviewDecision::ApprovedProgramInDirectory
+        | ReviewDecision::ApprovedTemporarily => None,
+        ReviewDecision::Denied | ReviewDecision::Abort => {
+            rejected("MCP tool call rejected by user".to_string())
+        }
+    }
+}
+
+/// The result reported to the model for a tool call that was cancelled
+/// because it did not finish within `timeout`.
+fn timeout_result(timeout: Duration) -> CallToolResult {
+    let timeout_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            annotations: None,
+            text: format!("tool call timed out after {timeout_ms}ms and was cancelled"),
+            r#type: "text".to_string(),
+        })],
+        is_error: Some(true),
+        structured_content: Some(json!({
+            "error": "timeout",
+            "timeout_ms": timeout_ms,
+        })),
+    }
+}
+
 async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMsg) {
     sess.send_event(Event {
         id: sub_id.to_string(),
@@ -80,3 +186,19 @@ async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMs
     })
     .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn timeouts_are_reported_as_structured_errors() {
+        let result = timeout_result(Duration::from_secs(30));
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(
+            result.structured_content,
+            Some(json!({ "error": "timeout", "timeout_ms": 30000 }))
+        );
+    }
+}
@@ -0,0 +1,747 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_tools_json_for_messages_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+
+/// `max_tokens` is mandatory in the Messages API; used when neither the config
+/// nor the model info provide a limit.
+const DEFAULT_MAX_TOKENS: u64 = 8_192;
+
+/// Smallest thinking budget the Messages API accepts.
+const MIN_THINKING_BUDGET: u64 = 1_024;
+
+/// `ResponseItem::Reasoning::id` of items that hold a `thinking` block. The
+/// block's signature is kept in `encrypted_content` so it can be sent back.
+const THINKING_ITEM_ID: &str = "thinking";
+
+/// `ResponseItem::Reasoning::id` of items that hold a `redacted_thinking`
+/// block, whose opaque payload is kept in `encrypted_content`.
+const REDACTED_THINKING_ITEM_ID: &str = "redacted_thinking";
+
+/// Implementation for the Anthropic Messages API.
+pub(crate) async fn stream_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    effort: ReasoningEffortConfig,
+    max_tokens: Option<u64>,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+) -> Result<ResponseStream> {
+    let full_instructions = prompt.get_full_instructions(model_family);
+    let messages = build_messages(&prompt.get_formatted_input());
+    let tools_json = create_tools_json_for_messages_api(&prompt.tools)?;
+
+    let max_tokens = max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut payload = json!({
+        "model": model_family.slug,
+        "system": full_instructions,
+        "messages": messages,
+        "max_tokens": max_tokens,
+        "stream": true,
+        "tools": tools_json,
+    });
+    if model_family.supports_reasoning_summaries
+        && let Some(budget_tokens) = thinking_budget(effort, max_tokens)
+    {
+        payload["thinking"] = json!({ "type": "enabled", "budget_tokens": budget_tokens });
+    }
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_full_url(&None),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    loop {
+        attempt += 1;
+
+        let req_builder = provider.create_request_builder(client, &None).await?;
+
+        let res = req_builder
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload)
+            .send()
+            .await;
+
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_messages_sse(
+                    stream,
+                    tx_event,
+                    provider.stream_idle_timeout(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                // Anthropic reports overload as 529, which `is_server_error` covers.
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = (res.text().await).unwrap_or_default();
+                    return Err(CodexErr::UnexpectedStatus(status, body));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(status));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+
+                let delay = retry_after_secs
+                    .map(|s| Duration::from_millis(s * 1_000))
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    return Err(e.into());
+                }
+                let delay = backoff(attempt);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Extended-thinking budget for the configured reasoning effort; `None`
+/// leaves thinking disabled. The budget counts towards `max_tokens`, so it is
+/// capped at half of it to leave room for the answer, and thinking is left
+/// off when that is below what the API accepts.
+fn thinking_budget(effort: ReasoningEffortConfig, max_tokens: u64) -> Option<u64> {
+    let budget = match effort {
+        ReasoningEffortConfig::Minimal => return None,
+        ReasoningEffortConfig::Low => 2_048,
+        ReasoningEffortConfig::Medium => 8_192,
+        ReasoningEffortConfig::High => 24_576,
+    };
+    Some(budget.min(max_tokens / 2)).filter(|budget| *budget >= MIN_THINKING_BUDGET)
+}
+
+/// Convert the conversation into Messages API `messages`. Consecutive items
+/// from the same side are merged into one message because the API requires
+/// user and assistant turns to alternate, and tool results must be sent as
+/// `tool_result` blocks of the following user message.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, block: Value| match messages.last_mut() {
+        Some((last_role, blocks)) if *last_role == role => blocks.push(block),
+        _ => messages.push((role, vec![block])),
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                for c in content {
+                    match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if !text.is_empty() {
+                                push(role, json!({ "type": "text", "text": text }));
+                            }
+                        }
+                        ContentItem::InputImage { image_url } => {
+                            push(role, image_block(image_url));
+                        }
+                    }
+                }
+            }
+            ResponseItem::Reasoning {
+                id,
+                content,
+                encrypted_content: Some(data),
+                ..
+            } => {
+                if id == REDACTED_THINKING_ITEM_ID {
+                    push(
+                        "assistant",
+                        json!({ "type": "redacted_thinking", "data": data }),
+                    );
+                } else if id == THINKING_ITEM_ID
+                    && let Some(content) = content
+                {
+                    // Thinking text is not persisted in rollouts, so resumed
+                    // sessions drop these blocks rather than send them empty.
+                    let thinking = content
+                        .iter()
+                        .map(|c| match c {
+                            ReasoningItemContent::ReasoningText { text }
+                            | ReasoningItemContent::Text { text } => text.as_str(),
+                        })
+                        .collect::<String>();
+                    push(
+                        "assistant",
+                        json!({ "type": "thinking", "thinking": thinking, "signature": data }),
+                    );
+                }
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({}));
+                push(
+                    "assistant",
+                    json!({ "type": "tool_use", "id": call_id, "name": name, "input": input }),
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                push(
+                    "assistant",
+                    json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": { "input": input },
+                    }),
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false) {
+                    block["is_error"] = json!(true);
+                }
+                push("user", block);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                push(
+                    "user",
+                    json!({ "type": "tool_result", "tool_use_id": call_id, "content": output }),
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+
+    messages
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": content }))
+        .collect()
+}
+
+/// Images attached by the user arrive as `data:` URLs; the Messages API wants
+/// those split into media type and base64 payload.
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((media_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": media_type, "data": data },
+        });
+    }
+    json!({ "type": "image", "source": { "type": "url", "url": image_url } })
+}
+
+/// A content block that is still being streamed.
+enum Block {
+    Text(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking(String),
+}
+
+impl Block {
+    fn into_item(self) -> Option<ResponseItem> {
+        match self {
+            Block::Text(text) if text.is_empty() => None,
+            Block::Text(text) => Some(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+            }),
+            Block::ToolUse {
+                id,
+                name,
+                input_json,
+            } => Some(ResponseItem::FunctionCall {
+                id: None,
+                name,
+                // A call without arguments streams no `input_json_delta`.
+                arguments: if input_json.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    input_json
+                },
+                call_id: id,
+            }),
+            Block::Thinking {
+                thinking,
+                signature,
+            } => Some(ResponseItem::Reasoning {
+                id: THINKING_ITEM_ID.to_string(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText { text: thinking }]),
+                encrypted_content: Some(signature),
+            }),
+            Block::RedactedThinking(data) => Some(ResponseItem::Reasoning {
+                id: REDACTED_THINKING_ITEM_ID.to_string(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some(data),
+            }),
+        }
+    }
+}
+
+/// SSE processor for the Messages streaming format. Every content block is
+/// forwarded as a single `OutputItemDone` once its `content_block_stop`
+/// arrives, with text and thinking also streamed as deltas.
+async fn process_messages_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut blocks: HashMap<u64, Block> = HashMap::new();
+    let mut response_id = String::new();
+    let mut usage = MessagesUsage::default();
+
+    loop {
+        let sse = match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let event: Value = match serde_json::from_str(&sse.data) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        trace!("messages received SSE event: {event:?}");
+
+        let index = event.get("index").and_then(Value::as_u64).unwrap_or(0);
+        match event
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+        {
+            "message_start" => {
+                let message = event.get("message");
+                if let Some(id) = message.and_then(|m| m.get("id")).and_then(Value::as_str) {
+                    response_id = id.to_string();
+                }
+                if let Some(u) = message.and_then(|m| m.get("usage")) {
+                    usage.update(u);
+                }
+                let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+            }
+            "content_block_start" => {
+                let Some(block) = event.get("content_block") else {
+                    continue;
+                };
+                let str_field = |name: &str| {
+                    block
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let block = match block.get("type").and_then(Value::as_str) {
+                    Some("text") => Block::Text(str_field("text")),
+                    Some("tool_use") => Block::ToolUse {
+                        id: str_field("id"),
+                        name: str_field("name"),
+                        input_json: String::new(),
+                    },
+                    Some("thinking") => Block::Thinking {
+                        thinking: str_field("thinking"),
+                        signature: str_field("signature"),
+                    },
+                    Some("redacted_thinking") => Block::RedactedThinking(str_field("data")),
+                    _ => continue,
+                };
+                blocks.insert(index, block);
+            }
+            "content_block_delta" => {
+                let Some(delta) = event.get("delta") else {
+                    continue;
+                };
+                let Some(block) = blocks.get_mut(&index) else {
+                    continue;
+                };
+                let text = delta
+                    .get("text")
+                    .or_else(|| delta.get("partial_json"))
+                    .or_else(|| delta.get("thinking"))
+                    .or_else(|| delta.get("signature"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                match (delta.get("type").and_then(Value::as_str), block) {
+                    (Some("text_delta"), Block::Text(buf)) => {
+                        buf.push_str(text);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTextDelta(text.to_string())))
+                            .await;
+                    }
+                    (Some("input_json_delta"), Block::ToolUse { input_json, .. }) => {
+                        input_json.push_str(text);
+                    }
+                    (Some("thinking_delta"), Block::Thinking { thinking, .. }) => {
+                        thinking.push_str(text);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::ReasoningContentDelta(text.to_string())))
+                            .await;
+                    }
+                    (Some("signature_delta"), Block::Thinking { signature, .. }) => {
+                        signature.push_str(text);
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(item) = blocks.remove(&index).and_then(Block::into_item) {
+                    let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                }
+            }
+            "message_delta" => {
+                if let Some(u) = event.get("usage") {
+                    usage.update(u);
+                }
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: Some(usage.into()),
+                    }))
+                    .await;
+                return;
+            }
+            "error" => {
+                let message = event
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string();
+                let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+                return;
+            }
+            // `ping` and future event types.
+            _ => {}
+        }
+    }
+}
+
+/// Token counts as reported in `message_start` and (cumulatively) in
+/// `message_delta`.
+#[derive(Default, Clone, Copy)]
+struct MessagesUsage {
+    input_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cache_read_input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl MessagesUsage {
+    fn update(&mut self, usage: &Value) {
+        let fields: [(&str, &mut u64); 4] = [
+            ("input_tokens", &mut self.input_tokens),
+            (
+                "cache_creation_input_tokens",
+                &mut self.cache_creation_input_tokens,
+            ),
+            ("cache_read_input_tokens", &mut self.cache_read_input_tokens),
+            ("output_tokens", &mut self.output_tokens),
+        ];
+        for (name, value) in fields {
+            if let Some(v) = usage.get(name).and_then(Value::as_u64) {
+                *value = v;
+            }
+        }
+    }
+}
+
+impl From<MessagesUsage> for TokenUsage {
+    fn from(usage: MessagesUsage) -> Self {
+        // Anthropic reports cached tokens separately from `input_tokens`;
+        // Codex counts them as part of the input like the Responses API does.
+        let input_tokens =
+            usage.input_tokens + usage.cache_creation_input_tokens + usage.cache_read_input_tokens;
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens: Some(usage.cache_read_input_tokens),
+            output_tokens: usage.output_tokens,
+            reasoning_output_tokens: None,
+            total_tokens: input_tokens + usage.output_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use tokio_util::io::ReaderStream;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    async fn run_sse(events: Vec<Value>) -> Vec<Result<ResponseEvent>> {
+        let mut body = String::new();
+        for e in events {
+            let kind = e["type"].as_str().unwrap_or_default().to_string();
+            body.push_str(&format!("event: {kind}\ndata: {e}\n\n"));
+        }
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(16);
+        let stream = ReaderStream::new(std::io::Cursor::new(body)).map_err(CodexErr::Io);
+        tokio::spawn(process_messages_sse(stream, tx, Duration::from_secs(5)));
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[test]
+    fn thinking_budget_stays_below_max_tokens() {
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::High, 64_000),
+            Some(24_576)
+        );
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::Medium, 4_096),
+            Some(2_048)
+        );
+        assert_eq!(thinking_budget(ReasoningEffortConfig::Low, 1_500), None);
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::Minimal, 64_000),
+            None
+        );
+    }
+
+    #[test]
+    fn tool_calls_and_results_alternate_between_roles() {
+        let input = vec![
+            message("user", "context"),
+            message("user", "list files"),
+            ResponseItem::Reasoning {
+                id: THINKING_ITEM_ID.to_string(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText {
+                    text: "use ls".to_string(),
+                }]),
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "denied".to_string(),
+                    success: Some(false),
+                },
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputImage {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                }],
+            },
+            // Reasoning from the Responses API cannot be replayed here.
+            ResponseItem::Reasoning {
+                id: "rs_1".to_string(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("opaque".to_string()),
+            },
+            message("assistant", "done"),
+        ];
+
+        assert_eq!(
+            build_messages(&input),
+            vec![
+                json!({"role": "user", "content": [
+                    {"type": "text", "text": "context"},
+                    {"type": "text", "text": "list files"},
+                ]}),
+                json!({"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "use ls", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {"command": ["ls"]}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "denied", "is_error": true},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}},
+                ]}),
+                json!({"role": "assistant", "content": [{"type": "text", "text": "done"}]}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_maps_blocks_and_usage() {
+        let events = run_sse(vec![
+            json!({"type": "message_start", "message": {"id": "msg_1", "usage": {"input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "hmm"}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Listing"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "ping"}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 25}}),
+            json!({"type": "message_stop"}),
+        ])
+        .await;
+        let events: Vec<ResponseEvent> = events.into_iter().map(|e| e.unwrap()).collect();
+
+        assert!(matches!(events[0], ResponseEvent::Created));
+        assert!(matches!(&events[1], ResponseEvent::ReasoningContentDelta(d) if d == "hmm"));
+        assert!(matches!(
+            &events[2],
+            ResponseEvent::OutputItemDone(ResponseItem::Reasoning { id, encrypted_content: Some(sig), .. })
+                if id == THINKING_ITEM_ID && sig == "sig"
+        ));
+        assert!(matches!(&events[3], ResponseEvent::OutputTextDelta(d) if d == "Listing"));
+        assert!(matches!(
+            &events[4],
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, .. }) if role == "assistant"
+        ));
+        assert!(matches!(
+            &events[5],
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { name, arguments, call_id, .. })
+                if name == "shell" && arguments == r#"{"command":["ls"]}"# && call_id == "toolu_1"
+        ));
+        let ResponseEvent::Completed {
+            response_id,
+            token_usage: Some(usage),
+        } = &events[6]
+        else {
+            panic!("expected Completed, got {:?}", events[6]);
+        };
+        assert_eq!(response_id, "msg_1");
+        assert_eq!(
+            (
+                usage.input_tokens,
+                usage.cached_input_tokens,
+                usage.output_tokens,
+                usage.total_tokens
+            ),
+            (100, Some(90), 25, 125)
+        );
+    }
+
+    #[tokio::test]
+    async fn error_event_and_early_close_are_errors() {
+        let events = run_sse(vec![
+            json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+        ])
+        .await;
+        assert!(matches!(&events[..], [Err(CodexErr::Stream(msg, None))] if msg == "Overloaded"));
+
+        let events = run_sse(vec![
+            json!({"type": "message_start", "message": {"id": "msg_1", "usage": {}}}),
+        ])
+        .await;
+        assert!(
+            matches!(events.last(), Some(Err(CodexErr::Stream(msg, None))) if msg.contains("message_stop"))
+        );
+    }
+}
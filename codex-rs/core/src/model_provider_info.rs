@@ -21,13 +21,18 @@ const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
+/// API version sent to `wire_api = "messages"` providers unless `http_headers`
+/// sets one.
+const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
 
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API, and Anthropic speaks its own *Messages* API. The
+/// protocols use different request/response shapes and *cannot* be
+/// auto-detected at runtime, therefore each provider entry must declare which
+/// one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -37,6 +42,9 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic Messages API exposed at `/v1/messages`.
+    Messages,
 }
 
 /// Serializable representation of a provider definition.
@@ -99,6 +107,10 @@ impl ModelProviderInfo {
         client: &'a reqwest::Client,
         auth: &Option<CodexAuth>,
     ) -> crate::error::Result<reqwest::RequestBuilder> {
+        if self.wire_api == WireApi::Messages {
+            return self.create_messages_request_builder(client);
+        }
+
         let effective_auth = match self.api_key() {
             Ok(Some(key)) => Some(CodexAuth::from_api_key(&key)),
             Ok(None) => auth.clone(),
@@ -122,6 +134,28 @@ impl ModelProviderInfo {
         Ok(self.apply_http_headers(builder))
     }
 
+    /// The Messages API authenticates with an `x-api-key` header rather than
+    /// a bearer token, and only ever with the provider's own key, so OpenAI
+    /// credentials are never sent to it.
+    fn create_messages_request_builder(
+        &self,
+        client: &reqwest::Client,
+    ) -> crate::error::Result<reqwest::RequestBuilder> {
+        let mut builder = client.post(self.get_full_url(&None));
+        if let Some(key) = self.api_key()? {
+            builder = builder.header("x-api-key", key);
+        }
+        let has_version_header = self.http_headers.as_ref().is_some_and(|headers| {
+            headers
+                .keys()
+                .any(|k| k.eq_ignore_ascii_case(ANTHROPIC_VERSION_HEADER))
+        });
+        if !has_version_header {
+            builder = builder.header(ANTHROPIC_VERSION_HEADER, DEFAULT_ANTHROPIC_VERSION);
+        }
+        Ok(self.apply_http_headers(builder))
+    }
+
     fn get_query_string(&self) -> String {
         self.query_params
             .as_ref()
@@ -156,6 +190,7 @@ impl ModelProviderInfo {
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Messages => format!("{base_url}/messages{query_string}"),
         }
     }
 
@@ -5,6 +5,8 @@ use serde_json::json;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
+use crate::error::CodexErr;
+use crate::finding_tool::FINDING_TOOL;
 use crate::model_family::ModelFamily;
 use crate::plan_tool::PLAN_TOOL;
 use crate::protocol::AskForApproval;
@@ -67,6 +69,7 @@ pub enum ConfigShellToolType {
 pub(crate) struct ToolsConfig {
     pub shell_type: ConfigShellToolType,
     pub plan_tool: bool,
+    pub finding_tool: bool,
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
@@ -77,6 +80,7 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) approval_policy: AskForApproval,
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) include_plan_tool: bool,
+    pub(crate) include_finding_tool: bool,
     pub(crate) include_apply_patch_tool: bool,
     pub(crate) include_web_search_request: bool,
     pub(crate) use_streamable_shell_tool: bool,
@@ -90,6 +94,7 @@ impl ToolsConfig {
             approval_policy,
             sandbox_policy,
             include_plan_tool,
+            include_finding_tool,
             include_apply_patch_tool,
             include_web_search_request,
             use_streamable_shell_tool,
@@ -123,6 +128,7 @@ impl ToolsConfig {
         Self {
             shell_type,
             plan_tool: *include_plan_tool,
+            finding_tool: *include_finding_tool,
             apply_patch_tool_type,
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
@@ -267,7 +273,7 @@ The shell tool is used to execute shell commands.
         SandboxPolicy::DangerFullAccess => {
             "Runs a shell command and returns its output.".to_string()
         }
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             r#"
 The shell tool is used to execute shell commands.
 - When invoking the shell tool, your call will be running in a landlock sandbox, and some shell commands (including apply_patch) will require escalated permissions:
@@ -374,6 +380,46 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
     Ok(tools_json)
 }
 
+/// Returns JSON values that are compatible with tool use in the Anthropic
+/// Messages API: https://docs.anthropic.com/en/docs/agents-and-tools/tool-use/overview
+///
+/// The Messages API only has function tools, so `local_shell` and the
+/// freeform `apply_patch` are sent as their function equivalents. Tools that
+/// have none are an error rather than being left out.
+pub(crate) fn create_tools_json_for_messages_api(
+    tools: &Vec<OpenAiTool>,
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let mut tools_json = Vec::new();
+    for tool in tools {
+        let function = match tool {
+            OpenAiTool::Function(function) => function.clone(),
+            OpenAiTool::LocalShell {} => as_function_tool(create_shell_tool())?,
+            OpenAiTool::Freeform(FreeformTool { name, .. }) if name == "apply_patch" => {
+                as_function_tool(create_apply_patch_json_tool())?
+            }
+            OpenAiTool::Freeform(FreeformTool { name, .. }) => {
+                return Err(CodexErr::UnsupportedTool(name.clone()));
+            }
+            OpenAiTool::WebSearch {} => {
+                return Err(CodexErr::UnsupportedTool("web_search".to_string()));
+            }
+        };
+        tools_json.push(json!({
+            "name": function.name,
+            "description": function.description,
+            "input_schema": serde_json::to_value(&function.parameters)?,
+        }));
+    }
+    Ok(tools_json)
+}
+
+fn as_function_tool(tool: OpenAiTool) -> crate::error::Result<ResponsesApiTool> {
+    match tool {
+        OpenAiTool::Function(function) => Ok(function),
+        _ => Err(CodexErr::UnsupportedTool(format!("{tool:?}"))),
+    }
+}
+
 pub(crate) fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
@@ -556,6 +602,10 @@ pub(crate) fn get_openai_tools(
         tools.push(PLAN_TOOL.clone());
     }
 
+    if config.finding_tool {
+        tools.push(FINDING_TOOL.clone());
+    }
+
     if let Some(apply_patch_tool_type) = &config.apply_patch_tool_type {
         match apply_patch_tool_type {
             ApplyPatchToolType::Freeform => {
@@ -634,8 +684,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: true,
+            include_finding_tool: true,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -645,7 +696,13 @@ mod tests {
 
         assert_eq_tool_names(
             &tools,
-            &["local_shell", "update_plan", "web_search", "view_image"],
+            &[
+                "local_shell",
+                "update_plan",
+                "report_finding",
+                "web_search",
+                "view_image",
+            ],
         );
     }
 
@@ -655,8 +712,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: true,
+            include_finding_tool: true,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -666,7 +724,13 @@ mod tests {
 
         assert_eq_tool_names(
             &tools,
-            &["shell", "update_plan", "web_search", "view_image"],
+            &[
+                "shell",
+                "update_plan",
+                "report_finding",
+                "web_search",
+                "view_image",
+            ],
         );
     }
 
@@ -676,8 +740,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -781,8 +846,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: false,
             use_streamable_shell_tool: false,
@@ -858,8 +924,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -920,8 +987,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -977,8 +1045,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -1034,8 +1103,9 @@ mod tests {
         let config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
+            include_finding_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
@@ -1081,4 +1151,26 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn messages_api_tools_use_function_equivalents() {
+        let tools = vec![
+            OpenAiTool::LocalShell {},
+            create_apply_patch_freeform_tool(),
+            PLAN_TOOL.clone(),
+        ];
+        let tools_json = create_tools_json_for_messages_api(&tools).unwrap();
+        let names = tools_json
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["shell", "apply_patch", "update_plan"]);
+        assert_eq!(tools_json[1]["input_schema"]["required"], json!(["input"]));
+
+        let err = create_tools_json_for_messages_api(&vec![OpenAiTool::WebSearch {}]).unwrap_err();
+        assert!(
+            matches!(&err, CodexErr::UnsupportedTool(name) if name == "web_search"),
+            "{err:?}"
+        );
+    }
 }
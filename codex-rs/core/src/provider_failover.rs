@@ -0,0 +1,176 @@
+//! Switching to fallback model providers when the configured one keeps
+//! failing.
+//!
+//! The session starts on its configured provider. Every turn that fails with
+//! a rate limit (429) or server error (5xx) counts against the provider that
+//! served it; once a provider has failed `fallback_failure_threshold` times in
+//! a row the session moves on to the next entry of `fallback_providers` and
+//! stays there. Other errors, such as a rejected request or a dropped stream,
+//! are retried on the same provider.
+
+use reqwest::StatusCode;
+
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::model_family::ModelFamily;
+use crate::model_provider_info::ModelProviderInfo;
+
+/// Used when `fallback_failure_threshold` is not set.
+pub(crate) const DEFAULT_FALLBACK_FAILURE_THRESHOLD: u64 = 3;
+
+/// A resolved entry of the `fallback_providers` chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallbackProvider {
+    /// Key into the `model_providers` map.
+    pub model_provider_id: String,
+    pub model_provider: ModelProviderInfo,
+    pub model: String,
+    pub model_family: ModelFamily,
+}
+
+/// Emitted when the session gives up on a provider.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProviderSwitch {
+    pub(crate) from: String,
+    pub(crate) to: FallbackProvider,
+    pub(crate) failures: u64,
+}
+
+/// Per-provider health for a session.
+#[derive(Debug)]
+pub(crate) struct ProviderFailover {
+    primary_id: String,
+    fallbacks: Vec<FallbackProvider>,
+    failure_threshold: u64,
+    /// Index into the chain, where 0 is the configured provider and `i` is
+    /// `fallbacks[i - 1]`.
+    active: usize,
+    /// Consecutive failures, indexed like `active`.
+    consecutive_failures: Vec<u64>,
+}
+
+impl ProviderFailover {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            primary_id: config.model_provider_id.clone(),
+            fallbacks: config.fallback_providers.clone(),
+            failure_threshold: config.fallback_failure_threshold.max(1),
+            active: 0,
+            consecutive_failures: vec![0; config.fallback_providers.len() + 1],
+        }
+    }
+
+    /// The fallback currently serving requests, or `None` while the
+    /// configured provider is still in use.
+    pub(crate) fn active_fallback(&self) -> Option<&FallbackProvider> {
+        self.active
+            .checked_sub(1)
+            .and_then(|idx| self.fallbacks.get(idx))
+    }
+
+    /// Key of the provider currently serving requests.
+    pub(crate) fn active_provider_id(&self) -> &str {
+        self.active_fallback()
+            .map_or(&self.primary_id, |fallback| &fallback.model_provider_id)
+    }
+
+    pub(crate) fn record_success(&mut self) {
+        self.consecutive_failures[self.active] = 0;
+    }
+
+    /// Counts a failed turn against the active provider and switches to the
+    /// next fallback once the threshold is reached.
+    pub(crate) fn record_failure(&mut self) -> Option<ProviderSwitch> {
+        self.consecutive_failures[self.active] += 1;
+        let failures = self.consecutive_failures[self.active];
+        if failures < self.failure_threshold || self.active >= self.fallbacks.len() {
+            return None;
+        }
+        let from = self.active_provider_id().to_string();
+        self.active += 1;
+        let to = self.active_fallback()?.clone();
+        Some(ProviderSwitch { from, to, failures })
+    }
+}
+
+/// Whether `err` says the provider is unhealthy rather than that the request
+/// was wrong, i.e. whether it should count towards switching providers.
+pub(crate) fn is_provider_failure(err: &CodexErr) -> bool {
+    let is_failure_status =
+        |status: &StatusCode| *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+    match err {
+        CodexErr::InternalServerError => true,
+        CodexErr::RetryLimit(status) | CodexErr::UnexpectedStatus(status, _) => {
+            is_failure_status(status)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_family::find_family_for_model;
+    use crate::model_provider_info::built_in_model_providers;
+    use pretty_assertions::assert_eq;
+
+    fn failover(threshold: u64) -> ProviderFailover {
+        let providers = built_in_model_providers();
+        let fallback = |id: &str, model: &str| FallbackProvider {
+            model_provider_id: id.to_string(),
+            model_provider: providers[id].clone(),
+            model: model.to_string(),
+            model_family: find_family_for_model(model).unwrap(),
+        };
+        ProviderFailover {
+            primary_id: "openai".to_string(),
+            fallbacks: vec![fallback("oss", "gpt-oss:20b"), fallback("openai", "o3")],
+            failure_threshold: threshold,
+            active: 0,
+            consecutive_failures: vec![0; 3],
+        }
+    }
+
+    #[test]
+    fn switches_after_consecutive_failures() {
+        let mut failover = failover(2);
+        assert_eq!(failover.record_failure(), None);
+        failover.record_success();
+        assert_eq!(failover.record_failure(), None);
+        assert_eq!(failover.active_provider_id(), "openai");
+
+        let switch = failover.record_failure().unwrap();
+        assert_eq!(switch.from, "openai");
+        assert_eq!(switch.to.model, "gpt-oss:20b");
+        assert_eq!(switch.failures, 2);
+        assert_eq!(failover.active_provider_id(), "oss");
+    }
+
+    #[test]
+    fn stays_on_last_provider_when_chain_is_exhausted() {
+        let mut failover = failover(1);
+        assert!(failover.record_failure().is_some());
+        assert_eq!(failover.record_failure().unwrap().to.model, "o3");
+        assert_eq!(failover.record_failure(), None);
+        assert_eq!(failover.active_fallback().unwrap().model, "o3");
+    }
+
+    #[test]
+    fn only_rate_limits_and_server_errors_count_as_failures() {
+        assert!(is_provider_failure(&CodexErr::RetryLimit(
+            StatusCode::TOO_MANY_REQUESTS
+        )));
+        assert!(is_provider_failure(&CodexErr::RetryLimit(
+            StatusCode::BAD_GATEWAY
+        )));
+        assert!(is_provider_failure(&CodexErr::InternalServerError));
+        assert!(!is_provider_failure(&CodexErr::UnexpectedStatus(
+            StatusCode::BAD_REQUEST,
+            String::new()
+        )));
+        assert!(!is_provider_failure(&CodexErr::Stream(
+            "stream closed before response.completed".to_string(),
+            None
+        )));
+    }
+}
@@ -2,8 +2,14 @@
 
 use std::fs::File;
 use std::fs::{self};
+use std::io::BufRead;
+use std::io::BufReader;
 use std::io::Error as IoError;
 use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -20,29 +26,58 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::environment_context::ENVIRONMENT_CONTEXT_START;
 use crate::git_info::GitInfo;
 use crate::git_info::collect_git_info;
+use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::SessionUsage;
 
 const SESSIONS_SUBDIR: &str = "sessions";
 
+/// Upper bound on the number of lines read from a rollout file when looking
+/// for the first user message of a session.
+const SUMMARY_SCAN_MAX_LINES: usize = 64;
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SessionMeta {
     pub id: Uuid,
     pub timestamp: String,
     pub instructions: Option<String>,
+    /// Working directory of the session. Missing in rollouts written by
+    /// older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct SessionMetaWithGit {
     #[serde(flatten)]
     meta: SessionMeta,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     git: Option<GitInfo>,
 }
 
+/// Summary of a recorded session, used to browse and resume past sessions.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    /// Path to the rollout file.
+    pub path: PathBuf,
+    pub timestamp: String,
+    pub cwd: Option<PathBuf>,
+    pub git_branch: Option<String>,
+    /// First message typed by the user, excluding injected instructions and
+    /// environment context.
+    pub first_user_message: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
-pub struct SessionStateSnapshot {}
+pub struct SessionStateSnapshot {
+    /// Running token and cost totals.
+    #[serde(default, skip_serializing_if = "SessionUsage::is_empty")]
+    pub usage: SessionUsage,
+}
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SavedSession {
@@ -66,12 +101,23 @@ pub struct SavedSession {
 #[derive(Clone)]
 pub(crate) struct RolloutRecorder {
     tx: Sender<RolloutCmd>,
+    /// Number of items in the rollout, as seen by [`RolloutRecorder::resume`].
+    items_recorded: Arc<AtomicUsize>,
 }
 
 enum RolloutCmd {
     AddItems(Vec<ResponseItem>),
     UpdateState(SessionStateSnapshot),
-    Shutdown { ack: oneshot::Sender<()> },
+    /// Drop every item after the first `n` when the rollout is resumed.
+    Truncate(usize),
+    /// Note which provider and model served a turn.
+    TurnProvider {
+        provider: String,
+        model: String,
+    },
+    Shutdown {
+        ack: oneshot::Sender<()>,
+    },
 }
 
 impl RolloutRecorder {
@@ -114,11 +160,15 @@ impl RolloutRecorder {
                 timestamp,
                 id: session_id,
                 instructions,
+                cwd: Some(cwd.clone()),
             }),
             cwd,
         ));
 
-        Ok(Self { tx })
+        Ok(Self {
+            tx,
+            items_recorded: Arc::new(AtomicUsize::new(0)),
+        })
     }
 
     pub(crate) async fn record_items(&self, items: &[ResponseItem]) -> std::io::Result<()> {
@@ -144,6 +194,8 @@ impl RolloutRecorder {
         if filtered.is_empty() {
             return Ok(());
         }
+        self.items_recorded
+            .fetch_add(filtered.len(), Ordering::SeqCst);
         self.tx
             .send(RolloutCmd::AddItems(filtered))
             .await
@@ -157,6 +209,37 @@ impl RolloutRecorder {
             .map_err(|e| IoError::other(format!("failed to queue rollout state: {e}")))
     }
 
+    /// Records that the model request for a turn was served by `provider`
+    /// (a key of the `model_providers` map) using `model`.
+    pub(crate) async fn record_turn_provider(
+        &self,
+        provider: &str,
+        model: &str,
+    ) -> std::io::Result<()> {
+        self.tx
+            .send(RolloutCmd::TurnProvider {
+                provider: provider.to_string(),
+                model: model.to_string(),
+            })
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue rollout turn provider: {e}")))
+    }
+
+    /// Number of items recorded so far, including those of a resumed session.
+    pub(crate) fn items_recorded(&self) -> usize {
+        self.items_recorded.load(Ordering::SeqCst)
+    }
+
+    /// Discard every item after the first `len`. The file is append-only, so
+    /// this records a marker that [`RolloutRecorder::resume`] honors.
+    pub(crate) async fn truncate(&self, len: usize) -> std::io::Result<()> {
+        self.items_recorded.fetch_min(len, Ordering::SeqCst);
+        self.tx
+            .send(RolloutCmd::Truncate(len))
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue rollout truncation: {e}")))
+    }
+
     pub async fn resume(
         path: &Path,
         cwd: std::path::PathBuf,
@@ -180,15 +263,21 @@ impl RolloutRecorder {
                 Ok(v) => v,
                 Err(_) => continue,
             };
-            if v.get("record_type")
-                .and_then(|rt| rt.as_str())
-                .map(|s| s == "state")
-                .unwrap_or(false)
-            {
-                if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
-                    state = s
+            match v.get("record_type").and_then(|rt| rt.as_str()) {
+                Some("state") => {
+                    if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
+                        state = s
+                    }
+                    continue;
                 }
-                continue;
+                Some("truncate") => {
+                    if let Some(n) = v.get("items").and_then(Value::as_u64) {
+                        items.truncate(n as usize);
+                    }
+                    continue;
+                }
+                Some("turn_provider") => continue,
+                _ => {}
             }
             match serde_json::from_value::<ResponseItem>(v.clone()) {
                 Ok(item) => match item {
@@ -227,7 +316,8 @@ impl RolloutRecorder {
             cwd,
         ));
         info!("Resumed rollout successfully from {path:?}");
-        Ok((Self { tx }, saved))
+        let items_recorded = Arc::new(AtomicUsize::new(saved.items.len()));
+        Ok((Self { tx, items_recorded }, saved))
     }
 
     pub async fn shutdown(&self) -> std::io::Result<()> {
@@ -246,6 +336,137 @@ impl RolloutRecorder {
     }
 }
 
+/// Lists the sessions recorded under `codex_home`, most recent first. Files
+/// that cannot be parsed are skipped.
+pub fn list_sessions(codex_home: &Path) -> std::io::Result<Vec<SessionSummary>> {
+    let mut paths = Vec::new();
+    collect_rollout_paths(&codex_home.join(SESSIONS_SUBDIR), &mut paths)?;
+    // File names start with the creation time, so sorting by name orders
+    // sessions chronologically.
+    paths.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| match read_session_summary(&path) {
+            Ok(summary) => Some(summary),
+            Err(e) => {
+                warn!("skipping unreadable rollout {path:?}: {e}");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Finds the session whose id is `id` or starts with `id`. Returns an error if
+/// the prefix matches more than one session.
+pub fn find_session(codex_home: &Path, id: &str) -> std::io::Result<Option<SessionSummary>> {
+    let needle = id.trim().to_ascii_lowercase();
+    if needle.is_empty() {
+        return Ok(None);
+    }
+    let mut matches: Vec<SessionSummary> = list_sessions(codex_home)?
+        .into_iter()
+        .filter(|s| s.id.to_string().starts_with(&needle))
+        .collect();
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(matches.pop()),
+        n => Err(IoError::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("session id prefix `{id}` is ambiguous ({n} sessions match)"),
+        )),
+    }
+}
+
+fn collect_rollout_paths(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_rollout_paths(&path, out)?;
+        } else if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("rollout-") && n.ends_with(".jsonl"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Token and cost totals last recorded in the rollout at `path`.
+pub fn read_session_usage(path: &Path) -> std::io::Result<SessionUsage> {
+    let mut usage = SessionUsage::default();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let Ok(v) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+        if v.get("record_type").and_then(Value::as_str) != Some("state") {
+            continue;
+        }
+        if let Ok(state) = serde_json::from_value::<SessionStateSnapshot>(v) {
+            usage = state.usage;
+        }
+    }
+    Ok(usage)
+}
+
+fn read_session_summary(path: &Path) -> std::io::Result<SessionSummary> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let meta_line = lines
+        .next()
+        .ok_or_else(|| IoError::other("empty session file"))??;
+    let SessionMetaWithGit { meta, git } = serde_json::from_str(&meta_line)
+        .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
+
+    let mut cwd = meta.cwd;
+    let mut first_user_message = None;
+    for line in lines.take(SUMMARY_SCAN_MAX_LINES) {
+        let Ok(ResponseItem::Message { role, content, .. }) =
+            serde_json::from_str::<ResponseItem>(&line?)
+        else {
+            continue;
+        };
+        if role != "user" {
+            continue;
+        }
+        let Some(text) = content.into_iter().find_map(|c| match c {
+            ContentItem::InputText { text } => Some(text),
+            _ => None,
+        }) else {
+            continue;
+        };
+        if text.starts_with(ENVIRONMENT_CONTEXT_START) {
+            // Rollouts written before `cwd` was recorded in the meta line
+            // still carry it in the environment context.
+            if cwd.is_none() {
+                cwd = text
+                    .split_once("<cwd>")
+                    .and_then(|(_, rest)| rest.split_once("</cwd>"))
+                    .map(|(cwd, _)| PathBuf::from(cwd));
+            }
+        } else if !text.starts_with("<user_instructions>") {
+            first_user_message = Some(text);
+            break;
+        }
+    }
+
+    Ok(SessionSummary {
+        id: meta.id,
+        path: path.to_path_buf(),
+        timestamp: meta.timestamp,
+        cwd,
+        git_branch: git.and_then(|g| g.branch),
+        first_user_message,
+    })
+}
+
 struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
@@ -344,6 +565,34 @@ async fn rollout_writer(
                     })
                     .await?;
             }
+            RolloutCmd::Truncate(items) => {
+                #[derive(Serialize)]
+                struct TruncateLine {
+                    record_type: &'static str,
+                    items: usize,
+                }
+                writer
+                    .write_line(&TruncateLine {
+                        record_type: "truncate",
+                        items,
+                    })
+                    .await?;
+            }
+            RolloutCmd::TurnProvider { provider, model } => {
+                #[derive(Serialize)]
+                struct TurnProviderLine {
+                    record_type: &'static str,
+                    provider: String,
+                    model: String,
+                }
+                writer
+                    .write_line(&TurnProviderLine {
+                        record_type: "turn_provider",
+                        provider,
+                        model,
+                    })
+                    .await?;
+            }
             RolloutCmd::Shutdown { ack } => {
                 let _ = ack.send(());
             }
@@ -366,3 +615,131 @@ impl JsonlWriter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write_rollout(codex_home: &Path, name: &str, lines: &[serde_json::Value]) -> PathBuf {
+        let dir = codex_home.join(SESSIONS_SUBDIR).join("2025/01/02");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let body: String = lines.iter().map(|l| format!("{l}\n")).collect();
+        fs::write(&path, body).unwrap();
+        path
+    }
+
+    fn user_message(text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "type": "message",
+            "role": "user",
+            "content": [{"type": "input_text", "text": text}],
+        })
+    }
+
+    #[test]
+    fn list_sessions_newest_first_with_summary_fields() {
+        let home = TempDir::new().unwrap();
+        let older_id = Uuid::new_v4();
+        let newer_id = Uuid::new_v4();
+        write_rollout(
+            home.path(),
+            &format!("rollout-2025-01-02T10-00-00-{older_id}.jsonl"),
+            &[
+                serde_json::json!({"id": older_id, "timestamp": "2025-01-02T10:00:00.000Z"}),
+                user_message(
+                    "<environment_context>\n  <cwd>/old/repo</cwd>\n</environment_context>",
+                ),
+                user_message("first question"),
+            ],
+        );
+        let newer_path = write_rollout(
+            home.path(),
+            &format!("rollout-2025-01-02T11-00-00-{newer_id}.jsonl"),
+            &[
+                serde_json::json!({
+                    "id": newer_id,
+                    "timestamp": "2025-01-02T11:00:00.000Z",
+                    "cwd": "/new/repo",
+                    "git": {"branch": "main"},
+                }),
+                user_message("<user_instructions>\n\nbe brief\n\n</user_instructions>"),
+                user_message("audit the parser"),
+            ],
+        );
+
+        let sessions = list_sessions(home.path()).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(
+            sessions[0],
+            SessionSummary {
+                id: newer_id,
+                path: newer_path,
+                timestamp: "2025-01-02T11:00:00.000Z".to_string(),
+                cwd: Some(PathBuf::from("/new/repo")),
+                git_branch: Some("main".to_string()),
+                first_user_message: Some("audit the parser".to_string()),
+            }
+        );
+        assert_eq!(sessions[1].id, older_id);
+        assert_eq!(sessions[1].cwd, Some(PathBuf::from("/old/repo")));
+        assert_eq!(
+            sessions[1].first_user_message.as_deref(),
+            Some("first question")
+        );
+    }
+
+    #[test]
+    fn find_session_by_prefix() {
+        let home = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        write_rollout(
+            home.path(),
+            &format!("rollout-2025-01-02T10-00-00-{id}.jsonl"),
+            &[serde_json::json!({"id": id, "timestamp": "2025-01-02T10:00:00.000Z"})],
+        );
+
+        let prefix = &id.to_string()[..8];
+        let found = find_session(home.path(), prefix).unwrap();
+        assert_eq!(found.map(|s| s.id), Some(id));
+        assert_eq!(find_session(home.path(), "zzzz").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn resume_honors_truncate_markers() {
+        let home = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+        let path = write_rollout(
+            home.path(),
+            &format!("rollout-2025-01-02T10-00-00-{id}.jsonl"),
+            &[
+                serde_json::json!({"id": id, "timestamp": "2025-01-02T10:00:00.000Z"}),
+                user_message("first"),
+                serde_json::json!({"record_type": "turn_provider", "provider": "openai", "model": "o3"}),
+                user_message("second"),
+                serde_json::json!({"record_type": "truncate", "items": 1}),
+                user_message("third"),
+            ],
+        );
+
+        let (recorder, saved) = RolloutRecorder::resume(&path, home.path().to_path_buf())
+            .await
+            .unwrap();
+
+        let texts: Vec<String> = saved
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ResponseItem::Message { content, .. } => match &content[0] {
+                    ContentItem::InputText { text } => Some(text.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec!["first".to_string(), "third".to_string()]);
+        assert_eq!(recorder.items_recorded(), 2);
+    }
+}
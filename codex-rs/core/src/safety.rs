@@ -1,4 +1,3 @@
-use std::collections::HashSet;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
@@ -6,7 +5,10 @@ use std::path::PathBuf;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::approval_grants::ApprovalGrants;
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::ExecPolicyDecision;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
@@ -65,39 +67,111 @@ pub fn assess_patch_safety(
     }
 }
 
+/// Commands that match a `forbidden` rule in the exec policy are always
+/// rejected, even if the user previously approved them.
+///
 /// For a command to be run _without_ a sandbox, one of the following must be
 /// true:
 ///
-/// - the user has explicitly approved the command
-/// - the command is on the "known safe" list
+/// - the user has approved exactly this command for the session
+/// - the command is on the "known safe" list and no paths are denied for
+///   reading
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// Commands that match an allow rule in the exec policy, or that are covered by
+/// a prefix, program or temporary approval, are auto-approved, but still run
+/// inside the sandbox.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
-    approved: &HashSet<Vec<String>>,
+    approved: &ApprovalGrants,
+    exec_policy: &ExecPolicy,
+    cwd: &Path,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
-    // A command is "trusted" because either:
+    let policy_decision = exec_policy.evaluate(command, sandbox_policy, cwd);
+    if let ExecPolicyDecision::Forbidden { reason } = &policy_decision {
+        return SafetyCheck::Reject {
+            reason: format!("forbidden by exec policy: {reason}"),
+        };
+    }
+
+    // A command may run outside the sandbox because either:
     // - it belongs to a set of commands we consider "safe" by default, or
-    // - the user has explicitly approved the command for this session
+    // - the user has approved exactly this command for the session
     //
-    // Currently, whether a command is "trusted" is a simple boolean, but we
-    // should include more metadata on this command test to indicate whether it
-    // should be run inside a sandbox or not. (This could be something the user
-    // defines as part of `execpolicy`.)
+    // When `is_known_safe_command(command)` returns `true`, it would probably
+    // be fine to run the command in a sandbox, but when
+    // `approved.covers_unsandboxed(command)` is `true`, the user may have
+    // approved it for the session _because_ they know it needs to run outside
+    // a sandbox.
     //
-    // For example, when `is_known_safe_command(command)` returns `true`, it
-    // would probably be fine to run the command in a sandbox, but when
-    // `approved.contains(command)` is `true`, the user may have approved it for
-    // the session _because_ they know it needs to run outside a sandbox.
-    if is_known_safe_command(command) || approved.contains(command) {
+    // Reads of `deny_read` paths are only blocked inside the sandbox, though,
+    // so while any are configured the known safe commands, which include
+    // `cat`, run in it. The user has seen the exact command they approved.
+    if approved.covers_unsandboxed(command) {
         return SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
         };
     }
+    if is_known_safe_command(command) {
+        let sandbox_type = if sandbox_policy.has_full_disk_read_access() {
+            SandboxType::None
+        } else {
+            get_platform_sandbox().unwrap_or(SandboxType::None)
+        };
+        return SafetyCheck::AutoApprove { sandbox_type };
+    }
 
-    assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
+    // Prefix, program and temporary grants say nothing about the arguments
+    // the user has seen, so the commands they cover stay in the sandbox, like
+    // commands allowed by the exec policy.
+    if !with_escalated_permissions
+        && (policy_decision == ExecPolicyDecision::Allow || approved.covers(command, cwd))
+    {
+        return auto_approve_in_sandbox(approval_policy, sandbox_policy, get_platform_sandbox());
+    }
+
+    match policy_decision {
+        ExecPolicyDecision::Ask { reason } => match approval_policy {
+            AskForApproval::Never => SafetyCheck::Reject {
+                reason: format!("{reason}; rejected by user approval settings"),
+            },
+            AskForApproval::UnlessTrusted
+            | AskForApproval::OnFailure
+            | AskForApproval::OnRequest => SafetyCheck::AskUser,
+        },
+        ExecPolicyDecision::Allow
+        | ExecPolicyDecision::Forbidden { .. }
+        | ExecPolicyDecision::NoMatch => assess_safety_for_untrusted_command(
+            approval_policy,
+            sandbox_policy,
+            with_escalated_permissions,
+        ),
+    }
+}
+
+/// Approves a command that may run in `platform_sandbox`. Without one, the
+/// user is asked, or under `Never`, the command is rejected, as
+/// [`assess_safety_for_untrusted_command`] does.
+fn auto_approve_in_sandbox(
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+    platform_sandbox: Option<SandboxType>,
+) -> SafetyCheck {
+    match (sandbox_policy, platform_sandbox) {
+        (SandboxPolicy::DangerFullAccess, _) => SafetyCheck::AutoApprove {
+            sandbox_type: SandboxType::None,
+        },
+        (_, Some(sandbox_type)) => SafetyCheck::AutoApprove { sandbox_type },
+        (_, None) if approval_policy == AskForApproval::Never => SafetyCheck::Reject {
+            reason:
+                "no sandbox is available to run the command in; rejected by user approval settings"
+                    .to_string(),
+        },
+        (_, None) => SafetyCheck::AskUser,
+    }
 }
 
 pub(crate) fn assess_safety_for_untrusted_command(
@@ -120,7 +194,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
         | (OnRequest, DangerFullAccess) => SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. }) | (OnRequest, WorkspaceWrite { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -132,9 +206,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
+        | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
@@ -176,7 +250,7 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
@@ -246,6 +320,7 @@ fn is_write_patch_constrained_to_writable_paths(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::ReviewDecision;
     use tempfile::TempDir;
 
     #[test]
@@ -269,6 +344,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: vec![],
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -290,6 +366,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: vec![],
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -303,8 +380,8 @@ mod tests {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
-        let approved: HashSet<Vec<String>> = HashSet::new();
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
+        let approved = ApprovalGrants::default();
         let request_escalated_privileges = true;
 
         let safety_check = assess_command_safety(
@@ -312,6 +389,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -322,8 +401,8 @@ mod tests {
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
-        let approved: HashSet<Vec<String>> = HashSet::new();
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
+        let approved = ApprovalGrants::default();
         let request_escalated_privileges = false;
 
         let safety_check = assess_command_safety(
@@ -331,6 +410,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -340,4 +421,159 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn test_exec_policy_forbidden_overrides_session_approval() {
+        let policy = ExecPolicy::from_sources(&[r#"
+define_program(
+    program="shred",
+    args=[ARG_RFILES],
+    forbidden="shred destroys data",
+)
+"#]);
+        let command = vec!["shred".to_string(), "secrets.txt".to_string()];
+        let mut approved = ApprovalGrants::default();
+        approved.grant(ReviewDecision::ApprovedForSession, &command, Path::new("/"));
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            &policy,
+            Path::new("/"),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::Reject {
+                reason: "forbidden by exec policy: shred destroys data".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_prefix_grant_runs_in_sandbox() {
+        let cwd = Path::new("/repo");
+        let approved_command = vec!["cargo".to_string(), "test".to_string()];
+        let command = vec![
+            "cargo".to_string(),
+            "test".to_string(),
+            "-p".to_string(),
+            "codex-core".to_string(),
+        ];
+        let mut approved = ApprovalGrants::default();
+        approved.grant(
+            ReviewDecision::ApprovedPrefixForSession,
+            &approved_command,
+            cwd,
+        );
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            &ExecPolicy::default(),
+            cwd,
+            false,
+        );
+
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(safety_check, expected);
+    }
+
+    #[test]
+    fn test_allowed_command_without_sandbox_is_rejected_under_never() {
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
+        assert!(matches!(
+            auto_approve_in_sandbox(AskForApproval::Never, &sandbox_policy, None),
+            SafetyCheck::Reject { .. }
+        ));
+        assert_eq!(
+            auto_approve_in_sandbox(AskForApproval::OnRequest, &sandbox_policy, None),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            auto_approve_in_sandbox(
+                AskForApproval::Never,
+                &sandbox_policy,
+                Some(SandboxType::LinuxSeccomp)
+            ),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::LinuxSeccomp
+            }
+        );
+        assert_eq!(
+            auto_approve_in_sandbox(
+                AskForApproval::Never,
+                &SandboxPolicy::DangerFullAccess,
+                None
+            ),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+    }
+
+    #[test]
+    fn test_known_safe_command_is_sandboxed_when_reads_are_denied() {
+        let command = vec!["cat".to_string(), "notes.txt".to_string()];
+        let assess = |sandbox_policy: &SandboxPolicy| {
+            assess_command_safety(
+                &command,
+                AskForApproval::OnRequest,
+                sandbox_policy,
+                &ApprovalGrants::default(),
+                &ExecPolicy::default(),
+                Path::new("/"),
+                false,
+            )
+        };
+
+        assert_eq!(
+            assess(&SandboxPolicy::ReadOnly { deny_read: vec![] }),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+        assert_eq!(
+            assess(&SandboxPolicy::ReadOnly {
+                deny_read: vec![PathBuf::from("/home/user/.ssh")]
+            }),
+            SafetyCheck::AutoApprove {
+                sandbox_type: get_platform_sandbox().unwrap_or(SandboxType::None)
+            }
+        );
+    }
+
+    #[test]
+    fn test_session_approved_command_runs_unsandboxed_when_reads_are_denied() {
+        let command = vec!["make".to_string(), "install".to_string()];
+        let mut approved = ApprovalGrants::default();
+        approved.grant(ReviewDecision::ApprovedForSession, &command, Path::new("/"));
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::ReadOnly {
+                deny_read: vec![PathBuf::from("/home/user/.ssh")],
+            },
+            &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+    }
 }
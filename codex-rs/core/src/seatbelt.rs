@@ -43,7 +43,7 @@ fn create_seatbelt_command_args(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
 ) -> Vec<String> {
-    let (file_write_policy, extra_cli_args) = {
+    let (file_write_policy, mut extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
             // Allegedly, this is more permissive than `(allow file-write*)`.
             (
@@ -96,11 +96,25 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
-    } else {
-        ""
-    };
+    // Reads are allowed everywhere except beneath the deny-read paths; later
+    // rules take precedence, so the denials must come after the allow.
+    let mut file_read_policy = "; allow read-only file operations\n(allow file-read*)".to_string();
+    let mut deny_read_policies: Vec<String> = Vec::new();
+    for (index, path) in sandbox_policy.get_deny_read_paths().iter().enumerate() {
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let deny_param = format!("DENY_READ_{index}");
+        extra_cli_args.push(format!(
+            "-D{deny_param}={}",
+            canonical_path.to_string_lossy()
+        ));
+        deny_read_policies.push(format!("(subpath (param \"{deny_param}\"))"));
+    }
+    if !deny_read_policies.is_empty() {
+        file_read_policy.push_str(&format!(
+            "\n(deny file-read*\n{}\n)",
+            deny_read_policies.join(" ")
+        ));
+    }
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
@@ -157,6 +171,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -232,6 +247,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            deny_read: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -300,6 +316,49 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_with_deny_read_paths() {
+        let tmp = TempDir::new().expect("tempdir");
+        let secrets = tmp.path().join("secrets");
+        fs::create_dir_all(&secrets).expect("create secrets");
+        let policy = SandboxPolicy::ReadOnly {
+            deny_read: vec![secrets.clone()],
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/echo".to_string(), "hello".to_string()],
+            &policy,
+            tmp.path(),
+        );
+
+        let expected_policy = format!(
+            r#"{MACOS_SEATBELT_BASE_POLICY}
+; allow read-only file operations
+(allow file-read*)
+(deny file-read*
+(subpath (param "DENY_READ_0"))
+)
+
+"#,
+        );
+        let expected_args = vec![
+            "-p".to_string(),
+            expected_policy,
+            format!(
+                "-DDENY_READ_0={}",
+                secrets
+                    .canonicalize()
+                    .expect("canonicalize secrets")
+                    .to_string_lossy()
+            ),
+            "--".to_string(),
+            "/bin/echo".to_string(),
+            "hello".to_string(),
+        ];
+
+        assert_eq!(expected_args, args);
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
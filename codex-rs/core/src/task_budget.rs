@@ -0,0 +1,161 @@
+//! Enforcement of the per-task [`TaskBudget`].
+
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::protocol::BudgetExceeded;
+use crate::protocol::TaskBudget;
+use crate::protocol::TaskBudgetKind;
+use crate::protocol::TokenUsage;
+
+/// What a task has used so far, compared against its budget.
+#[derive(Debug)]
+pub(crate) struct BudgetTracker {
+    budget: TaskBudget,
+    started: Instant,
+    tool_calls: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl BudgetTracker {
+    pub(crate) fn new(budget: TaskBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            tool_calls: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    /// Adds a tool call that is about to run.
+    pub(crate) fn record_tool_call(&mut self) {
+        self.tool_calls += 1;
+    }
+
+    /// The tool-call limit, when the task has already made as many calls as
+    /// it allows, so that another one must not run.
+    pub(crate) fn tool_calls_exceeded(&self) -> Option<BudgetExceeded> {
+        self.budget
+            .max_tool_calls
+            .filter(|limit| self.tool_calls >= *limit)
+            .map(|limit| BudgetExceeded {
+                budget: TaskBudgetKind::ToolCalls,
+                limit,
+                used: self.tool_calls,
+            })
+    }
+
+    /// Adds the token usage of one completed model request.
+    pub(crate) fn record_usage(&mut self, usage: &TokenUsage) {
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+    }
+
+    /// The first limit the task has reached, if any.
+    pub(crate) fn exceeded(&self) -> Option<BudgetExceeded> {
+        let elapsed_secs = self.started.elapsed().as_secs();
+        [
+            (
+                TaskBudgetKind::ToolCalls,
+                self.budget.max_tool_calls,
+                self.tool_calls,
+            ),
+            (
+                TaskBudgetKind::InputTokens,
+                self.budget.max_input_tokens,
+                self.input_tokens,
+            ),
+            (
+                TaskBudgetKind::OutputTokens,
+                self.budget.max_output_tokens,
+                self.output_tokens,
+            ),
+            (
+                TaskBudgetKind::Duration,
+                self.budget.max_duration_secs,
+                elapsed_secs,
+            ),
+        ]
+        .into_iter()
+        .find_map(|(budget, limit, used)| {
+            limit
+                .filter(|limit| used >= *limit)
+                .map(|limit| BudgetExceeded {
+                    budget,
+                    limit,
+                    used,
+                })
+        })
+    }
+
+    /// Time left before the duration limit is reached, or `None` when the
+    /// task may run for as long as it needs.
+    pub(crate) fn remaining_time(&self) -> Option<Duration> {
+        self.budget
+            .max_duration_secs
+            .map(|secs| Duration::from_secs(secs).saturating_sub(self.started.elapsed()))
+    }
+
+    /// The duration limit, reported as used up.
+    pub(crate) fn duration_exceeded(&self) -> BudgetExceeded {
+        BudgetExceeded {
+            budget: TaskBudgetKind::Duration,
+            limit: self.budget.max_duration_secs.unwrap_or_default(),
+            used: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn usage(input_tokens: u64, output_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            output_tokens,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_first_limit_reached() {
+        let mut tracker = BudgetTracker::new(TaskBudget {
+            max_tool_calls: Some(3),
+            max_output_tokens: Some(500),
+            ..Default::default()
+        });
+        tracker.record_tool_call();
+        tracker.record_tool_call();
+        tracker.record_usage(&usage(1_000, 200));
+        assert_eq!(tracker.exceeded(), None);
+        assert_eq!(tracker.tool_calls_exceeded(), None);
+
+        tracker.record_tool_call();
+        tracker.record_usage(&usage(1_200, 400));
+        assert_eq!(
+            tracker.exceeded(),
+            Some(BudgetExceeded {
+                budget: TaskBudgetKind::ToolCalls,
+                limit: 3,
+                used: 3,
+            })
+        );
+        assert_eq!(tracker.tool_calls_exceeded(), tracker.exceeded());
+    }
+
+    #[test]
+    fn unlimited_budget_is_never_exceeded() {
+        let mut tracker = BudgetTracker::new(TaskBudget::default());
+        for _ in 0..1_000 {
+            tracker.record_tool_call();
+        }
+        assert_eq!(tracker.tool_calls_exceeded(), None);
+        tracker.record_usage(&usage(u64::MAX / 2, u64::MAX / 2));
+        assert_eq!(tracker.exceeded(), None);
+        assert_eq!(tracker.remaining_time(), None);
+    }
+}
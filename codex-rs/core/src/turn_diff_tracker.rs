@@ -10,6 +10,7 @@ use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
 
+use crate::checkpoints::FileBaseline;
 use crate::protocol::FileChange;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
@@ -127,6 +128,45 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Snapshot of every tracked file as it was before the first patch of the
+    /// turn. Files that have since been moved also report their current
+    /// location as missing so that undoing the turn removes them there.
+    pub(crate) fn baselines(&self) -> Vec<FileBaseline> {
+        let mut baselines = Vec::new();
+        let mut moved_to = Vec::new();
+        for (internal, info) in &self.baseline_file_info {
+            let baseline = if info.oid == ZERO_OID {
+                FileBaseline::Missing {
+                    path: info.path.clone(),
+                }
+            } else {
+                match info.mode {
+                    FileMode::Symlink => FileBaseline::Symlink {
+                        path: info.path.clone(),
+                        target: symlink_target_from_blob(&info.content),
+                    },
+                    #[cfg(unix)]
+                    FileMode::Executable => {
+                        FileBaseline::file(info.path.clone(), &info.content, true)
+                    }
+                    FileMode::Regular => {
+                        FileBaseline::file(info.path.clone(), &info.content, false)
+                    }
+                }
+            };
+            baselines.push(baseline);
+            if let Some(current) = self.temp_name_to_current_path.get(internal)
+                && current != &info.path
+            {
+                moved_to.push(FileBaseline::Missing {
+                    path: current.clone(),
+                });
+            }
+        }
+        baselines.extend(moved_to);
+        baselines
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -454,6 +494,17 @@ fn symlink_blob_bytes(_path: &Path) -> Option<Vec<u8>> {
     None
 }
 
+#[cfg(unix)]
+fn symlink_target_from_blob(blob: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(blob))
+}
+
+#[cfg(not(unix))]
+fn symlink_target_from_blob(blob: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(blob).into_owned())
+}
+
 #[cfg(windows)]
 fn is_windows_drive_or_unc_root(p: &std::path::Path) -> bool {
     use std::path::Component;
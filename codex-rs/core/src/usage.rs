@@ -0,0 +1,264 @@
+//! Pricing of model requests and aggregation of recorded session usage.
+//!
+//! Every session keeps running token totals per model, together with their
+//! cost when the model's price is known, and writes them to its rollout.
+//! [`summarize_usage`] adds those totals up across sessions for `codex usage`.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::ModelUsage;
+use crate::protocol::SessionUsage;
+use crate::protocol::TokenUsage;
+use crate::rollout::SessionSummary;
+use crate::rollout::list_sessions;
+use crate::rollout::read_session_usage;
+
+const TOKENS_PER_MILLION: f64 = 1_000_000.0;
+
+/// Price of a model, in US dollars per million tokens.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    /// Price of input tokens served from the prompt cache. Defaults to
+    /// `input`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input: Option<f64>,
+    /// Price of output tokens, including reasoning tokens.
+    pub output: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, cached_input: f64, output: f64) -> Self {
+        Self {
+            input,
+            cached_input: Some(cached_input),
+            output,
+        }
+    }
+
+    /// Cost of one request, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached_input = self.cached_input.unwrap_or(self.input);
+        (usage.non_cached_input() as f64 * self.input
+            + usage.cached_input() as f64 * cached_input
+            + usage.output_tokens as f64 * self.output)
+            / TOKENS_PER_MILLION
+    }
+}
+
+/// Published list prices, keyed by model slug prefix.
+const BUILT_IN_PRICING: &[(&str, ModelPricing)] = &[
+    ("gpt-5", ModelPricing::new(1.25, 0.125, 10.0)),
+    ("gpt-5-mini", ModelPricing::new(0.25, 0.025, 2.0)),
+    ("gpt-5-nano", ModelPricing::new(0.05, 0.005, 0.4)),
+    ("gpt-4.1", ModelPricing::new(2.0, 0.5, 8.0)),
+    ("gpt-4.1-mini", ModelPricing::new(0.4, 0.1, 1.6)),
+    ("gpt-4.1-nano", ModelPricing::new(0.1, 0.025, 0.4)),
+    ("gpt-4o", ModelPricing::new(2.5, 1.25, 10.0)),
+    ("gpt-4o-mini", ModelPricing::new(0.15, 0.075, 0.6)),
+    ("o3", ModelPricing::new(2.0, 0.5, 8.0)),
+    ("o3-mini", ModelPricing::new(1.1, 0.55, 4.4)),
+    ("o3-pro", ModelPricing::new(20.0, 20.0, 80.0)),
+    ("o4-mini", ModelPricing::new(1.1, 0.275, 4.4)),
+    ("codex-mini-latest", ModelPricing::new(1.5, 0.375, 6.0)),
+];
+
+/// Price of `model`: the entry whose key is the longest prefix of the slug,
+/// with `overrides` from `config.toml` taking precedence over the built-in
+/// prices on equal length.
+pub fn pricing_for_model(
+    overrides: &HashMap<String, ModelPricing>,
+    model: &str,
+) -> Option<ModelPricing> {
+    let overrides = overrides
+        .iter()
+        .map(|(prefix, pricing)| (prefix.as_str(), *pricing));
+    let built_in = BUILT_IN_PRICING
+        .iter()
+        .map(|(prefix, pricing)| (*prefix, *pricing));
+    // `max_by_key` keeps the last maximum, so overrides go last to win ties.
+    built_in
+        .chain(overrides)
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, pricing)| pricing)
+}
+
+/// How `codex usage` groups sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageGroupBy {
+    /// The UTC day the session started on.
+    Day,
+    /// The session's working directory.
+    Project,
+    Model,
+}
+
+/// Usage of one group of sessions.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UsageSummaryRow {
+    pub key: String,
+    pub sessions: u64,
+    #[serde(flatten)]
+    pub usage: ModelUsage,
+}
+
+/// Adds up the usage recorded in every rollout under `codex_home` that was
+/// started on or after the day `since` (`YYYY-MM-DD`), ordered by key.
+/// Rollouts that cannot be read are skipped.
+pub fn summarize_usage(
+    codex_home: &Path,
+    group_by: UsageGroupBy,
+    since: Option<&str>,
+) -> std::io::Result<Vec<UsageSummaryRow>> {
+    let sessions = list_sessions(codex_home)?
+        .into_iter()
+        .filter(|session| since.is_none_or(|since| session_day(session) >= since))
+        .filter_map(|session| {
+            let usage = read_session_usage(&session.path).ok()?;
+            Some((session, usage))
+        });
+    Ok(group_usage(sessions, group_by))
+}
+
+fn group_usage(
+    sessions: impl IntoIterator<Item = (SessionSummary, SessionUsage)>,
+    group_by: UsageGroupBy,
+) -> Vec<UsageSummaryRow> {
+    let mut rows: BTreeMap<String, UsageSummaryRow> = BTreeMap::new();
+    let mut add = |key: String, usage: &ModelUsage| {
+        let row = rows.entry(key.clone()).or_insert_with(|| UsageSummaryRow {
+            key,
+            sessions: 0,
+            usage: ModelUsage::default(),
+        });
+        row.sessions += 1;
+        row.usage.add(usage);
+    };
+    for (session, usage) in sessions {
+        if usage.is_empty() {
+            continue;
+        }
+        match group_by {
+            UsageGroupBy::Day => add(session_day(&session).to_string(), &usage.total()),
+            UsageGroupBy::Project => {
+                let project = session
+                    .cwd
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), |cwd| cwd.display().to_string());
+                add(project, &usage.total());
+            }
+            UsageGroupBy::Model => {
+                for (model, model_usage) in &usage.models {
+                    add(model.clone(), model_usage);
+                }
+            }
+        }
+    }
+    rows.into_values().collect()
+}
+
+/// `YYYY-MM-DD` part of the session's start timestamp.
+fn session_day(session: &SessionSummary) -> &str {
+    session.timestamp.get(..10).unwrap_or(&session.timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn usage(input_tokens: u64, cached_input_tokens: u64, output_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens: Some(cached_input_tokens),
+            output_tokens,
+            reasoning_output_tokens: None,
+            total_tokens: input_tokens + output_tokens,
+        }
+    }
+
+    #[test]
+    fn longest_prefix_wins_and_overrides_take_precedence() {
+        let no_overrides = HashMap::new();
+        assert_eq!(
+            pricing_for_model(&no_overrides, "gpt-5-mini-2025-08-07"),
+            Some(ModelPricing::new(0.25, 0.025, 2.0))
+        );
+        assert_eq!(
+            pricing_for_model(&no_overrides, "o3-mini-2025-01-31"),
+            Some(ModelPricing::new(1.1, 0.55, 4.4))
+        );
+        assert_eq!(pricing_for_model(&no_overrides, "llama3"), None);
+
+        let overrides = HashMap::from([(
+            "gpt-5".to_string(),
+            ModelPricing {
+                input: 1.0,
+                cached_input: None,
+                output: 12.0,
+            },
+        )]);
+        let gpt5 = pricing_for_model(&overrides, "gpt-5").unwrap();
+        assert_eq!(gpt5.output, 12.0);
+        assert_eq!(
+            pricing_for_model(&overrides, "gpt-5-mini").unwrap().output,
+            2.0
+        );
+        // Cached input falls back to the input price.
+        assert_eq!(gpt5.cost(&usage(1_000_000, 500_000, 0)), 1.0);
+    }
+
+    #[test]
+    fn cost_prices_cached_input_separately() {
+        let pricing = ModelPricing::new(1.25, 0.125, 10.0);
+        let cost = pricing.cost(&usage(100_000, 80_000, 10_000));
+        assert!((cost - (0.025 + 0.01 + 0.1)).abs() < 1e-9, "{cost}");
+    }
+
+    #[test]
+    fn groups_sessions_by_day_and_model() {
+        let session = |timestamp: &str| SessionSummary {
+            id: Uuid::new_v4(),
+            path: PathBuf::new(),
+            timestamp: timestamp.to_string(),
+            cwd: Some(PathBuf::from("/repo")),
+            git_branch: None,
+            first_user_message: None,
+        };
+        let mut first = SessionUsage::default();
+        first.add_request("gpt-5", &usage(100, 0, 10), Some(0.5));
+        first.add_request("o3", &usage(200, 0, 20), Some(1.0));
+        let mut second = SessionUsage::default();
+        second.add_request("gpt-5", &usage(300, 100, 30), None);
+        let sessions = vec![
+            (session("2025-03-01T10:00:00.000Z"), first),
+            (session("2025-03-02T09:00:00.000Z"), second),
+        ];
+
+        let by_day = group_usage(sessions.clone(), UsageGroupBy::Day);
+        assert_eq!(
+            by_day
+                .iter()
+                .map(|row| (row.key.as_str(), row.usage.input_tokens, row.usage.cost_usd))
+                .collect::<Vec<_>>(),
+            vec![("2025-03-01", 300, Some(1.5)), ("2025-03-02", 300, None)]
+        );
+
+        let by_model = group_usage(sessions, UsageGroupBy::Model);
+        assert_eq!(
+            by_model
+                .iter()
+                .map(|row| (row.key.as_str(), row.sessions, row.usage.requests))
+                .collect::<Vec<_>>(),
+            vec![("gpt-5", 2, 2), ("o3", 1, 1)]
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::path::Path;
+
 use serde::Serialize;
 
+use crate::config_types::NotificationKind;
+
 /// User can configure a program that will receive notifications. Each
 /// notification is serialized as JSON and passed as an argument to the
 /// program.
@@ -16,6 +20,72 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    #[serde(rename_all = "kebab-case")]
+    TaskStarted {
+        turn_id: String,
+
+        /// Messages that the user sent to the agent to start the task.
+        input_messages: Vec<String>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    ApprovalRequested {
+        turn_id: String,
+        call_id: String,
+
+        /// One-line description of the command or patch awaiting approval.
+        summary: String,
+
+        /// Why the agent asked, when it gave a reason.
+        reason: Option<String>,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    TaskError { turn_id: String, message: String },
+
+    #[serde(rename_all = "kebab-case")]
+    StreamError { turn_id: String, message: String },
+
+    #[serde(rename_all = "kebab-case")]
+    BudgetExceeded { turn_id: String, message: String },
+
+    #[serde(rename_all = "kebab-case")]
+    ContextCompacted {
+        turn_id: String,
+        tokens_before: u64,
+        kept_turns: usize,
+    },
+}
+
+impl UserNotification {
+    pub(crate) fn kind(&self) -> NotificationKind {
+        match self {
+            UserNotification::AgentTurnComplete { .. } => NotificationKind::AgentTurnComplete,
+            UserNotification::TaskStarted { .. } => NotificationKind::TaskStarted,
+            UserNotification::ApprovalRequested { .. } => NotificationKind::ApprovalRequested,
+            UserNotification::TaskError { .. } => NotificationKind::TaskError,
+            UserNotification::StreamError { .. } => NotificationKind::StreamError,
+            UserNotification::BudgetExceeded { .. } => NotificationKind::BudgetExceeded,
+            UserNotification::ContextCompacted { .. } => NotificationKind::ContextCompacted,
+        }
+    }
+}
+
+/// Summary of a command awaiting approval, as the user would type it.
+pub(crate) fn command_summary(command: &[String]) -> String {
+    shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "))
+}
+
+/// Summary of a patch awaiting approval: the files it touches.
+pub(crate) fn patch_summary<'a>(paths: impl IntoIterator<Item = &'a Path>) -> String {
+    let mut paths: Vec<String> = paths
+        .into_iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    paths.sort();
+    let noun = if paths.len() == 1 { "file" } else { "files" };
+    format!("patch to {} {noun}: {}", paths.len(), paths.join(", "))
 }
 
 #[cfg(test)]
@@ -37,4 +107,37 @@ mod tests {
             r#"{"type":"agent-turn-complete","turn-id":"12345","input-messages":["Rename `foo` to `bar` and update the callsites."],"last-assistant-message":"Rename complete and verified `cargo build` succeeds."}"#
         );
     }
+
+    #[test]
+    fn approval_requested_notification() {
+        let notification = UserNotification::ApprovalRequested {
+            turn_id: "7".to_string(),
+            call_id: "call_1".to_string(),
+            summary: "cargo publish".to_string(),
+            reason: None,
+        };
+        assert_eq!(notification.kind(), NotificationKind::ApprovalRequested);
+        let serialized = serde_json::to_string(&notification).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"approval-requested","turn-id":"7","call-id":"call_1","summary":"cargo publish","reason":null}"#
+        );
+    }
+
+    #[test]
+    fn approval_summaries() {
+        assert_eq!(
+            command_summary(&[
+                "git".to_string(),
+                "commit".to_string(),
+                "-m".to_string(),
+                "fix it".to_string()
+            ]),
+            "git commit -m 'fix it'"
+        );
+        assert_eq!(
+            patch_summary([Path::new("src/b.rs"), Path::new("src/a.rs")]),
+            "patch to 2 files: src/a.rs, src/b.rs"
+        );
+    }
 }
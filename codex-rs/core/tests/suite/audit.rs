@@ -0,0 +1,67 @@
+use codex_core::ConversationManager;
+use codex_core::audit::AuditEvent;
+use codex_core::audit::AuditVerification;
+use codex_core::audit::read_audit_log;
+use codex_core::audit::verify_audit_log;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_start_and_overrides_are_audited() {
+    let home = TempDir::new().unwrap();
+    let audit_path = home.path().join("audit.jsonl");
+    let mut config = load_default_config_for_test(&home);
+    config.audit_log = Some(audit_path.clone());
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+        })
+        .await
+        .unwrap();
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+
+    let records = read_audit_log(&audit_path).unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(matches!(
+        records[0].event,
+        AuditEvent::SessionStarted { .. }
+    ));
+    assert_eq!(
+        records[1].event,
+        AuditEvent::ConfigOverride {
+            cwd: None,
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_policy: None,
+            model: None,
+            effort: None,
+            summary: None,
+        }
+    );
+    assert_eq!(
+        verify_audit_log(&audit_path).unwrap(),
+        AuditVerification::Intact {
+            records: 2,
+            head_checked: true,
+        }
+    );
+}
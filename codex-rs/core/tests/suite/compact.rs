@@ -249,3 +249,120 @@ async fn summarize_context_three_requests_and_instructions() {
         "third request should not include the summarize trigger"
     );
 }
+
+/// Convenience: SSE event for a completed response that reports token usage.
+fn ev_completed_with_tokens(id: &str, total_tokens: u64) -> Value {
+    serde_json::json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {"input_tokens":total_tokens,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":total_tokens}
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn auto_compact_runs_when_context_window_fills_up() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+
+    // SSE 1: normal reply that reports a nearly full context window.
+    let sse1 = sse(vec![
+        ev_assistant_message("m1", FIRST_REPLY),
+        ev_completed_with_tokens("r1", 950),
+    ]);
+    // SSE 2: automatic summarization.
+    let sse2 = sse(vec![
+        ev_assistant_message("m2", SUMMARY_TEXT),
+        ev_completed("r2"),
+    ]);
+    // SSE 3: the user's next turn.
+    let sse3 = sse(vec![ev_completed("r3")]);
+
+    let first_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains("\"text\":\"hello world\"")
+            && !body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(&server, first_matcher, sse1).await;
+
+    let second_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(&server, second_matcher, sse2).await;
+
+    let third_matcher = |req: &wiremock::Request| {
+        let body = std::str::from_utf8(&req.body).unwrap_or("");
+        body.contains(&format!("\"text\":\"{THIRD_USER_MSG}\""))
+            && !body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+    };
+    mount_sse_once(&server, third_matcher, sse3).await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    config.model_context_window = Some(1_000);
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello world".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: THIRD_USER_MSG.into(),
+            }],
+        })
+        .await
+        .unwrap();
+    let compacted = wait_for_event(&codex, |ev| matches!(ev, EventMsg::ContextCompacted(_))).await;
+    let EventMsg::ContextCompacted(compacted) = compacted else {
+        unreachable!("wait_for_event returned an unexpected event");
+    };
+    assert_eq!(compacted.tokens_before, 950);
+    assert_eq!(compacted.model_context_window, 1_000);
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3, "expected exactly three requests");
+
+    let body3 = requests[2].body_json::<serde_json::Value>().unwrap();
+    let input3 = body3.get("input").and_then(|v| v.as_array()).unwrap();
+    let texts: Vec<&str> = input3
+        .iter()
+        .filter(|item| item["type"].as_str() == Some("message"))
+        .filter_map(|item| item["content"][0]["text"].as_str())
+        .collect();
+    assert!(
+        texts.iter().any(|t| t.contains(SUMMARY_TEXT)),
+        "request after compaction should carry the summary"
+    );
+    assert!(
+        !texts.iter().any(|t| t.contains("hello world")),
+        "request after compaction should not include the summarized input"
+    );
+    assert_eq!(texts.last().copied(), Some(THIRD_USER_MSG));
+}
@@ -0,0 +1,230 @@
+#![cfg(unix)]
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::config_types::HookCommand;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use pretty_assertions::assert_eq;
+
+const SHELL_CALL_SSE: &str = r##"[
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"shell",
+        "arguments":"{\"command\":[\"touch\",\"created-by-model\"]}", "call_id":"call-1"
+    }},
+    {"type":"response.completed", "response": {"id": "__ID__"}}
+]"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failing_pre_exec_hook_blocks_command_and_reports_stderr() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(SHELL_CALL_SSE, "resp1"),
+                    "text/event-stream",
+                ),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp2"),
+                    "text/event-stream",
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.cwd = workspace.path().to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.hooks.pre_exec = vec![HookCommand {
+        command: vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "cat > payload.json; echo 'touch is not allowed here' >&2; exit 1".to_string(),
+        ],
+        timeout_ms: None,
+    }];
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "create a file".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(!workspace.path().join("created-by-model").exists());
+    let payload: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(workspace.path().join("payload.json")).unwrap())
+            .unwrap();
+    assert_eq!(payload["event"], "pre_exec");
+    assert_eq!(
+        payload["command"],
+        serde_json::json!(["touch", "created-by-model"])
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body: serde_json::Value = requests[1].body_json().unwrap();
+    let output = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output")
+        .unwrap();
+    assert_eq!(
+        output["output"],
+        "command blocked by hook: pre_exec hook `sh -c cat > payload.json; echo 'touch is not allowed here' >&2; exit 1` failed: touch is not allowed here"
+    );
+}
+
+const EXEC_COMMAND_CALL_SSE: &str = r##"[
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"exec_command",
+        "arguments":"{\"cmd\":\"echo built\",\"login\":false}", "call_id":"call-1"
+    }},
+    {"type":"response.completed", "response": {"id": "__ID__"}}
+]"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn post_exec_hook_runs_after_exec_command() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(EXEC_COMMAND_CALL_SSE, "resp1"),
+                    "text/event-stream",
+                ),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp2"),
+                    "text/event-stream",
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.cwd = workspace.path().to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.use_experimental_streamable_shell_tool = true;
+    config.hooks.post_exec = vec![HookCommand {
+        command: vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "cat > payload.json; echo 'lint failed' >&2; exit 1".to_string(),
+        ],
+        timeout_ms: None,
+    }];
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "build it".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let payload: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(workspace.path().join("payload.json")).unwrap())
+            .unwrap();
+    assert_eq!(payload["event"], "post_exec");
+    assert_eq!(payload["command"], serde_json::json!(["echo built"]));
+    assert_eq!(payload["exit_code"], 0);
+    assert!(
+        payload["output"].as_str().unwrap().contains("built"),
+        "{payload}"
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body: serde_json::Value = requests[1].body_json().unwrap();
+    let output = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output")
+        .unwrap();
+    let output = output["output"].as_str().unwrap();
+    assert!(
+        output.ends_with("post_exec hook `sh -c cat > payload.json; echo 'lint failed' >&2; exit 1` failed: lint failed"),
+        "{output}"
+    );
+}
@@ -0,0 +1,142 @@
+#![allow(clippy::unwrap_used)]
+
+use std::collections::HashMap;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::protocol::AgentMessageEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::header;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use pretty_assertions::assert_eq;
+
+/// Unlike the Responses API, every Messages event (even a bare
+/// `message_stop`) carries its JSON in a `data:` line, so the fixture is
+/// rendered here instead of with `load_sse_fixture`.
+fn messages_sse(path: &str) -> String {
+    let raw = std::fs::read_to_string(path).unwrap();
+    let events: Vec<serde_json::Value> = serde_json::from_str(&raw).unwrap();
+    events
+        .into_iter()
+        .map(|e| format!("event: {}\ndata: {e}\n\n", e["type"].as_str().unwrap()))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn messages_wire_api_streams_text_and_usage() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(header("x-api-key", "test-key"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    messages_sse("tests/fixtures/messages_text_sse.json"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let model_provider = ModelProviderInfo {
+        name: "anthropic".into(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        env_key: None,
+        env_key_instructions: None,
+        wire_api: WireApi::Messages,
+        query_params: None,
+        http_headers: Some(HashMap::from([(
+            "x-api-key".to_string(),
+            "test-key".to_string(),
+        )])),
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    config.model_max_output_tokens = Some(4_096);
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "say hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::AgentMessage(AgentMessageEvent { message }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::AgentMessage(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(message, "Hello from Messages");
+    let EventMsg::TokenCount(usage) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TokenCount(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!((usage.input_tokens, usage.output_tokens), (12, 4));
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = requests[0].body_json::<serde_json::Value>().unwrap();
+    assert_eq!(body["stream"], true);
+    // The default model supports reasoning, so thinking is enabled, with the
+    // medium effort's budget cut down to fit in the configured `max_tokens`.
+    assert_eq!(
+        body["thinking"],
+        serde_json::json!({"type": "enabled", "budget_tokens": 2_048})
+    );
+    assert_eq!(body["max_tokens"], 4_096);
+    assert!(body["system"].as_str().unwrap().contains("Codex"));
+    // Only the OpenAI bearer token would be wrong here.
+    assert!(requests[0].headers.get("authorization").is_none());
+
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 1, "user items are merged: {messages:?}");
+    assert_eq!(messages[0]["role"], "user");
+    let last_block = messages[0]["content"].as_array().unwrap().last().unwrap();
+    assert_eq!(last_block["text"], "say hello");
+
+    let shell = body["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tool| tool["name"] == "shell")
+        .unwrap();
+    assert_eq!(shell["input_schema"]["type"], "object");
+}
@@ -1,12 +1,20 @@
 // Aggregates all former standalone integration tests as modules.
 
+mod audit;
 mod cli_stream;
 mod client;
 mod compact;
 mod exec;
 mod exec_stream_events;
+mod hooks;
 mod live_cli;
+mod messages_api;
 mod prompt_caching;
+mod provider_failover;
+mod redaction;
 mod seatbelt;
+mod session_usage;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod task_budget;
+mod undo;
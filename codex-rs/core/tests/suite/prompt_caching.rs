@@ -191,7 +191,13 @@ async fn prompt_tools_are_consistent_across_requests() {
     let expected_instructions: &str = include_str!("../../prompt.md");
     // our internal implementation is responsible for keeping tools in sync
     // with the OpenAI schema, so we just verify the tool presence here
-    let expected_tools_names: &[&str] = &["shell", "update_plan", "apply_patch", "view_image"];
+    let expected_tools_names: &[&str] = &[
+        "shell",
+        "update_plan",
+        "report_finding",
+        "apply_patch",
+        "view_image",
+    ];
     let body0 = requests[0].body_json::<serde_json::Value>().unwrap();
     assert_eq!(
         body0["instructions"],
@@ -389,6 +395,7 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
                 network_access: true,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                deny_read: vec![],
             }),
             model: Some("o3".to_string()),
             effort: Some(ReasoningEffort::High),
@@ -517,10 +524,12 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
                 network_access: true,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                deny_read: vec![],
             },
             model: "o3".to_string(),
             effort: ReasoningEffort::High,
             summary: ReasoningSummary::Detailed,
+            budget: None,
         })
         .await
         .unwrap();
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::list_sessions;
+use codex_core::model_family::find_family_for_model;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::provider_failover::FallbackProvider;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event_with_timeout;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_string_contains;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn mock_provider(base_url: String) -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: "mock".into(),
+        base_url: Some(base_url),
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        wire_api: WireApi::Responses,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(5),
+        stream_idle_timeout_ms: Some(2_000),
+        requires_openai_auth: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn switches_to_fallback_provider_after_repeated_failures() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/primary/v1/responses"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/fallback/v1/responses"))
+        .and(body_string_contains("\"model\":\"o3\""))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp1"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider_id = "primary".to_string();
+    config.model_provider = mock_provider(format!("{}/primary/v1", server.uri()));
+    config.fallback_providers = vec![FallbackProvider {
+        model_provider_id: "backup".to_string(),
+        model_provider: mock_provider(format!("{}/fallback/v1", server.uri())),
+        model: "o3".to_string(),
+        model_family: find_family_for_model("o3").unwrap(),
+    }];
+    config.fallback_failure_threshold = 2;
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let switch = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::BackgroundEvent(_)),
+        Duration::from_secs(10),
+    )
+    .await;
+    let EventMsg::BackgroundEvent(switch) = switch else {
+        unreachable!()
+    };
+    assert_eq!(
+        switch.message,
+        "model provider `primary` failed 2 times in a row; switching to `backup` (o3)"
+    );
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        Duration::from_secs(10),
+    )
+    .await;
+
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::ShutdownComplete),
+        Duration::from_secs(5),
+    )
+    .await;
+
+    let sessions = list_sessions(home.path()).unwrap();
+    let rollout = std::fs::read_to_string(&sessions[0].path).unwrap();
+    let served_by: Vec<serde_json::Value> = rollout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|v| v["record_type"] == "turn_provider")
+        .collect();
+    assert_eq!(
+        served_by,
+        vec![serde_json::json!({
+            "record_type": "turn_provider",
+            "provider": "backup",
+            "model": "o3",
+        })]
+    );
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::list_sessions;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::ModelUsage;
+use codex_core::protocol::Op;
+use codex_core::protocol::SessionUsageEvent;
+use codex_core::read_session_usage;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_core::usage::ModelPricing;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use pretty_assertions::assert_eq;
+
+const COMPLETED_WITH_USAGE_SSE: &str = r##"[
+    {"type":"response.output_item.done", "item":{
+        "type":"message", "role":"assistant",
+        "content":[{"type":"output_text","text":"done"}]
+    }},
+    {"type":"response.completed", "response": {
+        "id": "__ID__",
+        "usage": {
+            "input_tokens": 3000,
+            "input_tokens_details": {"cached_tokens": 1000},
+            "output_tokens": 500,
+            "output_tokens_details": {"reasoning_tokens": 200},
+            "total_tokens": 3500
+        }
+    }}
+]"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_usage_is_priced_reported_and_persisted() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(COMPLETED_WITH_USAGE_SSE, "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.model_pricing = HashMap::from([(
+        config.model.clone(),
+        ModelPricing {
+            input: 2.0,
+            cached_input: Some(0.5),
+            output: 10.0,
+        },
+    )]);
+    let model = config.model.clone();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::SessionUsage(SessionUsageEvent { usage }) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::SessionUsage(_))).await
+    else {
+        unreachable!()
+    };
+    let expected = ModelUsage {
+        requests: 1,
+        input_tokens: 3000,
+        cached_input_tokens: 1000,
+        output_tokens: 500,
+        reasoning_output_tokens: 200,
+        // 2000 * $2 + 1000 * $0.5 + 500 * $10, per million tokens.
+        cost_usd: Some(0.0095),
+    };
+    assert_eq!(usage.models.get(&model), Some(&expected));
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+
+    let sessions = list_sessions(home.path()).unwrap();
+    assert_eq!(read_session_usage(&sessions[0].path).unwrap(), usage);
+}
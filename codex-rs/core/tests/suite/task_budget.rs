@@ -0,0 +1,193 @@
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::BudgetExceeded;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TaskBudget;
+use codex_core::protocol::TaskBudgetKind;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use pretty_assertions::assert_eq;
+
+/// A model turn that calls a tool the session does not know about, which
+/// still counts as a tool call and makes the model go again.
+const TOOL_CALL_SSE: &str = r##"[
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"no_such_tool", "arguments":"{}", "call_id":"call-1"
+    }},
+    {"type":"response.completed", "response": {"id": "__ID__"}}
+]"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_stops_when_tool_call_budget_is_used_up() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(TOOL_CALL_SSE, "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.task_budget = TaskBudget {
+        max_tool_calls: Some(2),
+        ..Default::default()
+    };
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "loop forever".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::TurnAborted(TurnAbortedEvent { reason }) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::TurnAborted(_) | EventMsg::TaskComplete(_))
+    })
+    .await
+    else {
+        panic!("task completed without hitting its budget");
+    };
+    assert_eq!(
+        reason,
+        TurnAbortReason::BudgetExceeded(BudgetExceeded {
+            budget: TaskBudgetKind::ToolCalls,
+            limit: 2,
+            used: 2,
+        })
+    );
+}
+
+/// One model turn that makes three tool calls.
+const THREE_TOOL_CALLS_SSE: &str = r##"[
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"shell",
+        "arguments":"{\"command\":[\"touch\",\"first\"]}", "call_id":"call-1"
+    }},
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"shell",
+        "arguments":"{\"command\":[\"touch\",\"second\"]}", "call_id":"call-2"
+    }},
+    {"type":"response.output_item.done", "item":{
+        "type":"function_call", "name":"shell",
+        "arguments":"{\"command\":[\"touch\",\"third\"]}", "call_id":"call-3"
+    }},
+    {"type":"response.completed", "response": {"id": "__ID__"}}
+]"##;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_calls_past_the_budget_are_not_run() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(THREE_TOOL_CALLS_SSE, "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.cwd = workspace.path().to_path_buf();
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.task_budget = TaskBudget {
+        max_tool_calls: Some(2),
+        ..Default::default()
+    };
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "touch three files".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::TurnAborted(TurnAbortedEvent { reason }) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::TurnAborted(_) | EventMsg::TaskComplete(_))
+    })
+    .await
+    else {
+        panic!("task completed without hitting its budget");
+    };
+    assert_eq!(
+        reason,
+        TurnAbortReason::BudgetExceeded(BudgetExceeded {
+            budget: TaskBudgetKind::ToolCalls,
+            limit: 2,
+            used: 2,
+        })
+    );
+    assert!(workspace.path().join("first").exists());
+    assert!(workspace.path().join("second").exists());
+    assert!(!workspace.path().join("third").exists());
+}
@@ -0,0 +1,109 @@
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnUndoneEvent;
+use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use pretty_assertions::assert_eq;
+
+fn sse_completed(id: &str) -> String {
+    load_sse_fixture_with_id("tests/fixtures/completed_template.json", id)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn undo_removes_the_last_turn_from_history() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_completed("resp"), "text/event-stream"),
+        )
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    for text in ["first turn", "turn to undo"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    codex.submit(Op::UndoTurn { turns: 1 }).await.unwrap();
+    let EventMsg::TurnUndone(TurnUndoneEvent {
+        turns,
+        restored_paths,
+    }) = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnUndone(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(turns, 1);
+    assert_eq!(restored_paths, Vec::<std::path::PathBuf>::new());
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "last turn".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    let body = std::str::from_utf8(&requests[2].body).unwrap();
+    assert!(body.contains("first turn"));
+    assert!(
+        !body.contains("turn to undo"),
+        "undone turn was sent: {body}"
+    );
+    assert!(body.contains("last turn"));
+
+    // Only two turns remain that can be undone.
+    codex.submit(Op::UndoTurn { turns: 3 }).await.unwrap();
+    let EventMsg::Error(error) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(error.message, "Only 2 turn(s) can be undone.");
+}
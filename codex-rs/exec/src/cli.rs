@@ -6,6 +6,10 @@ use std::path::PathBuf;
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
+    /// Action to perform instead of starting a new session.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Optional image(s) to attach to the initial prompt.
     #[arg(long = "image", short = 'i', value_name = "FILE", value_delimiter = ',', num_args = 1..)]
     pub images: Vec<PathBuf>,
@@ -63,12 +67,39 @@ pub struct Cli {
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Write the findings the agent reports to FILE as a SARIF 2.1.0 log.
+    #[arg(long = "sarif", value_name = "FILE")]
+    pub sarif_file: Option<PathBuf>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT")]
     pub prompt: Option<String>,
 }
 
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Continue a previous session with a new prompt.
+    Resume(ResumeArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ResumeArgs {
+    /// Id (or unique id prefix) of the session to resume, as shown by
+    /// `codex sessions list`.
+    #[arg(value_name = "SESSION_ID", required_unless_present = "last")]
+    pub session_id: Option<String>,
+
+    /// Resume the most recent session.
+    #[arg(long = "last", default_value_t = false)]
+    pub last: bool,
+
+    /// Prompt to continue the session with. If not provided as an argument
+    /// (or if `-` is used), it is read from stdin.
+    #[arg(value_name = "PROMPT")]
+    pub prompt: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
@@ -8,22 +8,31 @@ use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::ContextCompactedEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandRejectedEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FindingEvent;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::McpServerStatusEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolsChangedEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::RedactionCount;
+use codex_core::protocol::SecretRedactedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnUndoneEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use owo_colors::OwoColorize;
@@ -180,6 +189,35 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::StreamError(StreamErrorEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
+            EventMsg::McpServerStatus(McpServerStatusEvent { server, status }) => match status {
+                McpServerStatus::Running => {
+                    let message = format!("MCP server `{server}` is running again");
+                    ts_println!(self, "{}", message.style(self.dimmed));
+                }
+                McpServerStatus::Restarting { attempt } => {
+                    let message =
+                        format!("MCP server `{server}` exited, restarting (attempt {attempt})");
+                    ts_println!(self, "{}", message.style(self.dimmed));
+                }
+                McpServerStatus::Failed { error, stderr_tail } => {
+                    let message = format!("MCP server `{server}` failed: {error}");
+                    ts_println!(self, "{}", message.style(self.red));
+                    for line in stderr_tail {
+                        println!("{}", line.style(self.dimmed));
+                    }
+                }
+            },
+            EventMsg::McpToolsChanged(McpToolsChangedEvent { server, changes }) => {
+                let tools: Vec<&str> = changes
+                    .iter()
+                    .map(|change| change.current.name.as_str())
+                    .collect();
+                let message = format!(
+                    "MCP server `{server}` changed the definitions of {}; these tools are disabled until you run `codex mcp trust {server}`",
+                    tools.join(", ")
+                );
+                ts_println!(self, "{}", message.style(self.red));
+            }
             EventMsg::TaskStarted(_) => {
                 // Ignore.
             }
@@ -192,6 +230,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::TokenCount(token_usage) => {
                 ts_println!(self, "tokens used: {}", token_usage.blended_total());
             }
+            EventMsg::SessionUsage(_) => {
+                // Totals are summarized by `codex usage`.
+            }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
                 if !self.answer_started {
                     ts_println!(self, "{}\n", "codex".style(self.italic).style(self.magenta));
@@ -286,6 +327,16 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     cwd.to_string_lossy(),
                 );
             }
+            EventMsg::ExecCommandRejected(ExecCommandRejectedEvent {
+                command, reason, ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {}: {reason}",
+                    "exec rejected".style(self.red),
+                    escape_command(&command).style(self.bold),
+                );
+            }
             EventMsg::ExecCommandOutputDelta(_) => {}
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
                 call_id,
@@ -485,12 +536,59 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
             }
+            EventMsg::ContextCompacted(ContextCompactedEvent {
+                tokens_before,
+                model_context_window,
+                kept_turns,
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {tokens_before}/{model_context_window} tokens used; kept the last {kept_turns} turns",
+                    "context compacted:".style(self.magenta)
+                );
+            }
+            EventMsg::SecretRedacted(SecretRedactedEvent {
+                call_id,
+                redactions,
+            }) => {
+                let summary = redactions
+                    .iter()
+                    .map(|RedactionCount { kind, count }| format!("{count} {kind}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let source = call_id.map_or_else(
+                    || "user input".to_string(),
+                    |call_id| format!("output of {call_id}"),
+                );
+                ts_println!(
+                    self,
+                    "{} {summary} in {source}",
+                    "secrets redacted:".style(self.magenta)
+                );
+            }
+            EventMsg::TurnUndone(TurnUndoneEvent {
+                turns,
+                restored_paths,
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {turns} turn(s); restored {} file(s)",
+                    "undone:".style(self.magenta),
+                    restored_paths.len()
+                );
+                for path in restored_paths {
+                    println!("{}", path.display().style(self.dimmed));
+                }
+            }
             EventMsg::ExecApprovalRequest(_) => {
                 // Should we exit?
             }
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::McpToolApprovalRequest(_) => {
+                // Should we exit?
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -529,15 +627,44 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "explanation: {explanation:?}");
                 ts_println!(self, "plan: {plan:?}");
             }
+            EventMsg::Finding(FindingEvent { number, finding }) => {
+                let location = match (finding.start_line, finding.end_line) {
+                    (Some(start), Some(end)) if end != start => {
+                        format!("{}:{start}-{end}", finding.path)
+                    }
+                    (Some(start), _) => format!("{}:{start}", finding.path),
+                    _ => finding.path.clone(),
+                };
+                ts_println!(
+                    self,
+                    "{} #{number} [{}] {} {location}",
+                    "finding".style(self.red),
+                    finding.severity,
+                    finding.rule_id.style(self.bold),
+                );
+                println!("{}", finding.description);
+                if let Some(remediation) = &finding.remediation {
+                    println!("{} {remediation}", "remediation:".style(self.dimmed));
+                }
+            }
             EventMsg::GetHistoryEntryResponse(_) => {
                 // Currently ignored in exec output.
             }
             EventMsg::McpListToolsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::McpListResourcesResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::McpListPromptsResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::ListApprovalGrantsResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
@@ -545,6 +672,11 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::Replaced => {
                     ts_println!(self, "task aborted: replaced by a new task");
                 }
+                TurnAbortReason::BudgetExceeded(exceeded) => {
+                    let prefix = "ERROR:".style(self.red);
+                    ts_println!(self, "{prefix} task stopped: {exceeded}");
+                    return CodexStatus::InitiateShutdown;
+                }
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationHistory(_) => {}
@@ -2,23 +2,30 @@ mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 mod event_processor_with_json_output;
+mod sarif;
 
 use std::io::IsTerminal;
 use std::io::Read;
 use std::path::PathBuf;
 
 pub use cli::Cli;
+use cli::ResumeArgs;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::find_session;
+use codex_core::list_sessions;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::FindingEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use codex_core::util::is_inside_git_repo;
 use codex_login::AuthManager;
 use codex_ollama::DEFAULT_OSS_MODEL;
@@ -33,8 +40,13 @@ use tracing_subscriber::EnvFilter;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 
+/// Exit code of `codex exec` when the task was stopped because it used up
+/// its `task_budget`.
+pub const BUDGET_EXCEEDED_EXIT_CODE: i32 = 3;
+
 pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     let Cli {
+        command,
         images,
         model: model_cli_arg,
         oss,
@@ -45,12 +57,29 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         skip_git_repo_check,
         color,
         last_message_file,
+        sarif_file,
         json: json_mode,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         config_overrides,
     } = cli;
 
+    let (prompt, resume) = match command {
+        Some(cli::Command::Resume(ResumeArgs {
+            session_id,
+            last,
+            prompt: resume_prompt,
+        })) => {
+            if last {
+                // With `--last` the first positional is the prompt, not an id.
+                (resume_prompt.or(session_id), Some(None))
+            } else {
+                (resume_prompt, Some(session_id))
+            }
+        }
+        None => (prompt, None),
+    };
+
     // Determine the prompt based on CLI arg and/or stdin.
     let prompt = match prompt {
         Some(p) if p != "-" => p,
@@ -162,7 +191,20 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    let mut config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    if let Some(session_id) = resume {
+        let session = match session_id.as_deref() {
+            Some(id) => find_session(&config.codex_home, id)?,
+            None => list_sessions(&config.codex_home)?.into_iter().next(),
+        };
+        let Some(session) = session else {
+            eprintln!(
+                "No session found to resume. Run `codex sessions list` to see recorded sessions."
+            );
+            std::process::exit(1);
+        };
+        config.experimental_resume = Some(session.path);
+    }
     let mut event_processor: Box<dyn EventProcessor> = if json_mode {
         Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
     } else {
@@ -188,6 +230,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         std::process::exit(1);
     }
 
+    let sarif_cwd = config.cwd.clone();
     let conversation_manager = ConversationManager::new(AuthManager::shared(
         config.codex_home.clone(),
         config.preferred_auth_method,
@@ -266,7 +309,16 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     info!("Sent prompt with event ID: {initial_prompt_task_id}");
 
     // Run the loop until the task is complete.
+    let mut findings = Vec::new();
+    let mut budget_exceeded = false;
     while let Some(event) = rx.recv().await {
+        match &event.msg {
+            EventMsg::Finding(FindingEvent { finding, .. }) => findings.push(finding.clone()),
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded(_),
+            }) => budget_exceeded = true,
+            _ => {}
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -279,5 +331,13 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
+    if let Some(sarif_file) = sarif_file {
+        sarif::write_sarif_file(&sarif_file, &findings, &sarif_cwd);
+    }
+
+    if budget_exceeded {
+        std::process::exit(BUDGET_EXCEEDED_EXIT_CODE);
+    }
+
     Ok(())
 }
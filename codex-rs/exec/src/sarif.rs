@@ -0,0 +1,226 @@
+//! Export of the findings reported during a run as a SARIF 2.1.0 log, the
+//! format code-scanning dashboards ingest.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use codex_core::finding_tool::Finding;
+use codex_core::finding_tool::FindingSeverity;
+use serde_json::Value;
+use serde_json::json;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SRCROOT: &str = "%SRCROOT%";
+
+pub(crate) fn write_sarif_file(path: &Path, findings: &[Finding], cwd: &Path) {
+    let contents = match serde_json::to_string_pretty(&sarif_log(findings, cwd)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Failed to serialize SARIF output: {e}");
+            return;
+        }
+    };
+    if let Err(e) = std::fs::write(path, contents) {
+        eprintln!("Failed to write SARIF file {path:?}: {e}");
+    }
+}
+
+fn sarif_log(findings: &[Finding], cwd: &Path) -> Value {
+    // One rule per distinct rule id, in order of first appearance; the
+    // severity GitHub code scanning shows for a rule is its worst finding.
+    let mut rule_ids: Vec<&str> = Vec::new();
+    let mut rules: BTreeMap<&str, (FindingSeverity, Option<&str>)> = BTreeMap::new();
+    for finding in findings {
+        let rule_id = finding.rule_id.as_str();
+        match rules.get_mut(rule_id) {
+            Some((severity, cwe)) => {
+                *severity = (*severity).max(finding.severity);
+                if cwe.is_none() {
+                    *cwe = finding.cwe.as_deref();
+                }
+            }
+            None => {
+                rule_ids.push(rule_id);
+                rules.insert(rule_id, (finding.severity, finding.cwe.as_deref()));
+            }
+        }
+    }
+
+    let rules_json: Vec<Value> = rule_ids
+        .iter()
+        .map(|rule_id| {
+            let (severity, cwe) = rules[rule_id];
+            let mut tags = vec!["security".to_string()];
+            if let Some(cwe) = cwe {
+                tags.push(format!("external/cwe/{}", cwe.trim().to_ascii_lowercase()));
+            }
+            let mut properties = json!({ "tags": tags });
+            if let Some(score) = security_severity(severity) {
+                properties["security-severity"] = json!(score);
+            }
+            json!({
+                "id": rule_id,
+                "properties": properties,
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let mut text = finding.description.clone();
+            if let Some(remediation) = &finding.remediation {
+                text.push_str("\n\nRemediation: ");
+                text.push_str(remediation);
+            }
+            let mut physical_location = json!({
+                "artifactLocation": {
+                    "uri": artifact_uri(&finding.path, cwd),
+                    "uriBaseId": SRCROOT,
+                },
+            });
+            if let Some(start_line) = finding.start_line {
+                physical_location["region"] = json!({
+                    "startLine": start_line,
+                    "endLine": finding.end_line.unwrap_or(start_line),
+                });
+            }
+            json!({
+                "ruleId": finding.rule_id,
+                "ruleIndex": rule_ids.iter().position(|id| *id == finding.rule_id),
+                "level": level(finding.severity),
+                "message": { "text": text },
+                "locations": [{ "physicalLocation": physical_location }],
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "codex",
+                    "informationUri": "https://github.com/openai/codex",
+                    "semanticVersion": env!("CARGO_PKG_VERSION"),
+                    "rules": rules_json,
+                },
+            },
+            "originalUriBaseIds": {
+                SRCROOT: { "uri": directory_uri(cwd) },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn level(severity: FindingSeverity) -> &'static str {
+    match severity {
+        FindingSeverity::Critical | FindingSeverity::High => "error",
+        FindingSeverity::Medium => "warning",
+        FindingSeverity::Low | FindingSeverity::Info => "note",
+    }
+}
+
+/// Score on the CVSS-like scale GitHub uses to bucket security alerts.
+fn security_severity(severity: FindingSeverity) -> Option<&'static str> {
+    match severity {
+        FindingSeverity::Critical => Some("9.5"),
+        FindingSeverity::High => Some("8.0"),
+        FindingSeverity::Medium => Some("5.5"),
+        FindingSeverity::Low => Some("2.0"),
+        FindingSeverity::Info => None,
+    }
+}
+
+/// Path of the finding relative to `cwd`, with `/` separators, so results
+/// line up with the repository root in code-scanning tools.
+fn artifact_uri(path: &str, cwd: &Path) -> String {
+    let path = Path::new(path);
+    let relative = path.strip_prefix(cwd).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn directory_uri(dir: &Path) -> String {
+    let mut uri = format!("file://{}", dir.to_string_lossy().replace('\\', "/"));
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule_id: &str, severity: FindingSeverity, path: &str) -> Finding {
+        Finding {
+            rule_id: rule_id.to_string(),
+            severity,
+            cwe: None,
+            path: path.to_string(),
+            start_line: None,
+            end_line: None,
+            description: "description".to_string(),
+            remediation: None,
+        }
+    }
+
+    #[test]
+    fn findings_become_rules_and_results() {
+        let cwd = Path::new("/repo");
+        let findings = vec![
+            Finding {
+                cwe: Some("CWE-89".to_string()),
+                start_line: Some(10),
+                remediation: Some("use bind parameters".to_string()),
+                ..finding("sql-injection", FindingSeverity::Medium, "/repo/src/db.rs")
+            },
+            finding("hardcoded-secret", FindingSeverity::Low, "config.toml"),
+            finding("sql-injection", FindingSeverity::Critical, "src/api.rs"),
+        ];
+
+        let log = sarif_log(&findings, cwd);
+        let run = &log["runs"][0];
+        assert_eq!(run["originalUriBaseIds"][SRCROOT]["uri"], "file:///repo/");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([
+                {
+                    "id": "sql-injection",
+                    "properties": {
+                        "tags": ["security", "external/cwe/cwe-89"],
+                        "security-severity": "9.5",
+                    },
+                },
+                {
+                    "id": "hardcoded-secret",
+                    "properties": { "tags": ["security"], "security-severity": "2.0" },
+                },
+            ])
+        );
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "sql-injection",
+                "ruleIndex": 0,
+                "level": "warning",
+                "message": { "text": "description\n\nRemediation: use bind parameters" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/db.rs", "uriBaseId": SRCROOT },
+                        "region": { "startLine": 10, "endLine": 10 },
+                    },
+                }],
+            })
+        );
+        assert_eq!(run["results"][1]["ruleIndex"], 1);
+        assert_eq!(run["results"][1]["level"], "note");
+        assert_eq!(run["results"][2]["level"], "error");
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use crate::ExecCall;
+use crate::policy::program_name;
+
+/// A `deny_program()` or `forbid_args()` rule.
+#[derive(Debug)]
+pub struct DenyRule {
+    pub program: String,
+    /// Arguments that must all be present, in any order, for the rule to
+    /// apply. Empty for `deny_program()`, which matches every invocation.
+    pub args: Vec<String>,
+    pub reason: String,
+}
+
+/// A `deny_pipeline()` rule: refuses pipelines in which `source` is followed
+/// by one of the `sinks`, e.g. `curl ... | sh`.
+#[derive(Debug)]
+pub struct PipelineRule {
+    pub source: String,
+    pub sinks: Vec<String>,
+    pub reason: String,
+}
+
+impl PipelineRule {
+    /// The indexes of the first source in `pipeline`, whose commands are
+    /// given in order, and of the first sink after it. Programs are matched
+    /// by name or by full path.
+    pub(crate) fn matches(&self, pipeline: &[ExecCall]) -> Option<(usize, usize)> {
+        let is = |program: &String, exec_call: &ExecCall| {
+            *program == exec_call.program || program == program_name(&exec_call.program)
+        };
+        let source = pipeline
+            .iter()
+            .position(|exec_call| is(&self.source, exec_call))?;
+        let sink = pipeline[source + 1..]
+            .iter()
+            .position(|exec_call| self.sinks.iter().any(|sink| is(sink, exec_call)))?;
+        Some((source, source + 1 + sink))
+    }
+}
+
+/// Spellings of the same option, per program and, for programs whose
+/// options differ between subcommands, per subcommand: the first entry is the
+/// one both the rule and the command are normalized to. Only options that
+/// deny rules are likely to name are listed.
+const OPTION_ALIASES: &[(&str, Option<&str>, &[&str])] = &[
+    ("rm", None, &["-r", "-R", "--recursive"]),
+    ("rm", None, &["-f", "--force"]),
+    ("chmod", None, &["-R", "--recursive"]),
+    ("chown", None, &["-R", "--recursive"]),
+    (
+        "git",
+        Some("push"),
+        &["--force", "-f", "--force-with-lease", "--force-if-includes"],
+    ),
+];
+
+/// Options of `git` itself that take a value, which may come before the
+/// subcommand.
+const GIT_OPTIONS_WITH_VALUE: &[&str] = &["-C", "-c", "--git-dir", "--work-tree", "--namespace"];
+
+/// Shortest abbreviation of a long option that is recognized, e.g. `--rec`
+/// for `--recursive`, as GNU getopt and git accept.
+const MIN_LONG_OPTION_PREFIX: usize = 4;
+
+impl DenyRule {
+    /// Whether an invocation of `program`, whose file name is
+    /// `program_name`, with `args` is refused by this rule. Combined short
+    /// flags (`-fr`) are split and known long spellings (`--recursive`) are
+    /// treated like the short ones, so `rm -rf /`, `rm -r -f /` and
+    /// `rm --recursive --force /` match the same rule.
+    pub(crate) fn matches(&self, program: &str, program_name: &str, args: &[String]) -> bool {
+        if self.program != program && self.program != program_name {
+            return false;
+        }
+        // Both sides are normalized for the command's subcommand, so that a
+        // rule naming `--force` only covers `-f` where the two are the same.
+        let subcommand = subcommand(program_name, args);
+        let normalize = |arg: &String| normalize(program_name, subcommand, arg);
+        let observed: HashSet<String> = args.iter().flat_map(normalize).collect();
+        self.args
+            .iter()
+            .flat_map(normalize)
+            .all(|required| observed.contains(&required))
+    }
+}
+
+/// The first argument that is not an option, skipping the values of `git`'s
+/// own options.
+fn subcommand<'a>(program: &str, args: &'a [String]) -> Option<&'a str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if program == "git" && GIT_OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+/// The options `arg` stands for, in their canonical spelling. Anything that
+/// is not an option is returned unchanged.
+fn normalize(program: &str, subcommand: Option<&str>, arg: &str) -> Vec<String> {
+    if arg.len() > 2 && arg.starts_with("--") {
+        let name = arg.split_once('=').map_or(arg, |(name, _)| name);
+        return vec![canonical_long(program, subcommand, name)];
+    }
+    match arg.strip_prefix('-') {
+        Some(flags) if flags.len() > 1 && flags.chars().all(|c| c.is_ascii_alphabetic()) => flags
+            .chars()
+            .map(|c| canonical(program, subcommand, &format!("-{c}")))
+            .collect(),
+        _ => vec![canonical(program, subcommand, arg)],
+    }
+}
+
+fn canonical(program: &str, subcommand: Option<&str>, option: &str) -> String {
+    aliases(program, subcommand)
+        .find(|spellings| spellings.contains(&option))
+        .map_or(option, |spellings| spellings[0])
+        .to_string()
+}
+
+/// Like [`canonical`], also resolving an abbreviated long option to the
+/// known spelling it is a prefix of.
+fn canonical_long(program: &str, subcommand: Option<&str>, option: &str) -> String {
+    if aliases(program, subcommand).any(|spellings| spellings.contains(&option))
+        || option.len() < MIN_LONG_OPTION_PREFIX
+    {
+        return canonical(program, subcommand, option);
+    }
+    aliases(program, subcommand)
+        .find(|spellings| {
+            spellings
+                .iter()
+                .any(|spelling| spelling.starts_with("--") && spelling.starts_with(option))
+        })
+        .map_or(option, |spellings| spellings[0])
+        .to_string()
+}
+
+fn aliases<'a>(
+    program: &'a str,
+    subcommand: Option<&'a str>,
+) -> impl Iterator<Item = &'static [&'static str]> + 'a {
+    OPTION_ALIASES
+        .iter()
+        .filter(move |(name, scope, _)| {
+            *name == program && scope.is_none_or(|scope| Some(scope) == subcommand)
+        })
+        .map(|(_, _, spellings)| *spellings)
+}
@@ -6,6 +6,7 @@ extern crate starlark;
 mod arg_matcher;
 mod arg_resolver;
 mod arg_type;
+mod deny_rule;
 mod error;
 mod exec_call;
 mod execv_checker;
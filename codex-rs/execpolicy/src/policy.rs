@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use multimap::MultiMap;
 use regex_lite::Error as RegexError;
 use regex_lite::Regex;
@@ -7,6 +9,8 @@ use crate::Forbidden;
 use crate::MatchedExec;
 use crate::NegativeExamplePassedCheck;
 use crate::ProgramSpec;
+use crate::deny_rule::DenyRule;
+use crate::deny_rule::PipelineRule;
 use crate::error::Error;
 use crate::error::Result;
 use crate::policy_parser::ForbiddenProgramRegex;
@@ -16,6 +20,8 @@ pub struct Policy {
     programs: MultiMap<String, ProgramSpec>,
     forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
     forbidden_substrings_pattern: Option<Regex>,
+    deny_rules: Vec<DenyRule>,
+    pipeline_rules: Vec<PipelineRule>,
 }
 
 impl Policy {
@@ -23,6 +29,8 @@ impl Policy {
         programs: MultiMap<String, ProgramSpec>,
         forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
         forbidden_substrings: Vec<String>,
+        deny_rules: Vec<DenyRule>,
+        pipeline_rules: Vec<PipelineRule>,
     ) -> std::result::Result<Self, RegexError> {
         let forbidden_substrings_pattern = if forbidden_substrings.is_empty() {
             None
@@ -38,11 +46,27 @@ impl Policy {
             programs,
             forbidden_program_regexes,
             forbidden_substrings_pattern,
+            deny_rules,
+            pipeline_rules,
         })
     }
 
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
         let ExecCall { program, args } = &exec_call;
+        // Deny rules take precedence over everything else, so a rule cannot be
+        // sidestepped by spelling out the program's full path either.
+        let program_name = program_name(program);
+        for rule in &self.deny_rules {
+            if rule.matches(program, program_name, args) {
+                return Ok(MatchedExec::Forbidden {
+                    cause: Forbidden::Denied {
+                        exec_call: exec_call.clone(),
+                    },
+                    reason: rule.reason.clone(),
+                });
+            }
+        }
+
         for ForbiddenProgramRegex { regex, reason } in &self.forbidden_program_regexes {
             if regex.is_match(program) {
                 return Ok(MatchedExec::Forbidden {
@@ -85,6 +109,69 @@ impl Policy {
         last_err
     }
 
+    /// Checks the commands of a pipeline, in order, against the
+    /// `deny_pipeline()` rules. The commands themselves are checked with
+    /// [`Policy::check`].
+    pub fn check_pipeline(&self, pipeline: &[ExecCall]) -> Option<MatchedExec> {
+        self.pipeline_rules.iter().find_map(|rule| {
+            let (source, sink) = rule.matches(pipeline)?;
+            Some(MatchedExec::Forbidden {
+                cause: Forbidden::Pipeline {
+                    source: pipeline[source].clone(),
+                    sink: pipeline[sink].clone(),
+                },
+                reason: rule.reason.clone(),
+            })
+        })
+    }
+
+    /// Whether any deny rule, forbidden program, forbidden program regex or
+    /// forbidden substring could apply to some command of the shell `script`. Used for scripts
+    /// that cannot be split into plain commands: every word of the script is
+    /// treated as a potential program name, and a `deny_pipeline()` rule may
+    /// apply to a script with a `|` that names its source and one of its sinks.
+    pub fn may_forbid_script(&self, script: &str) -> bool {
+        if self
+            .forbidden_substrings_pattern
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(script))
+        {
+            return true;
+        }
+        let words: Vec<&str> = script
+            .split(|c: char| c.is_whitespace() || ";|&()<>`$'\"{}=".contains(c))
+            .filter(|word| !word.is_empty())
+            .collect();
+        let names = |program: &String| {
+            words
+                .iter()
+                .any(|&word| program == word || program == program_name(word))
+        };
+        if script.contains('|')
+            && self
+                .pipeline_rules
+                .iter()
+                .any(|rule| names(&rule.source) && rule.sinks.iter().any(names))
+        {
+            return true;
+        }
+        words.iter().any(|&word| {
+            let name = program_name(word);
+            self.deny_rules
+                .iter()
+                .any(|rule| rule.program == word || rule.program == name)
+                || [word, name].iter().any(|program| {
+                    self.programs
+                        .get_vec(*program)
+                        .is_some_and(|specs| specs.iter().any(ProgramSpec::is_forbidden))
+                })
+                || self
+                    .forbidden_program_regexes
+                    .iter()
+                    .any(|ForbiddenProgramRegex { regex, .. }| regex.is_match(word))
+        })
+    }
+
     pub fn check_each_good_list_individually(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for (_program, spec) in self.programs.flat_iter() {
@@ -101,3 +188,11 @@ impl Policy {
         violations
     }
 }
+
+/// The file name of `program`, e.g. `curl` for `/usr/bin/curl`.
+pub(crate) fn program_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program)
+}
@@ -4,6 +4,8 @@ use crate::Opt;
 use crate::Policy;
 use crate::ProgramSpec;
 use crate::arg_matcher::ArgMatcher;
+use crate::deny_rule::DenyRule;
+use crate::deny_rule::PipelineRule;
 use crate::opt::OptMeta;
 use log::info;
 use multimap::MultiMap;
@@ -82,6 +84,8 @@ struct PolicyBuilder {
     programs: RefCell<MultiMap<String, ProgramSpec>>,
     forbidden_program_regexes: RefCell<Vec<ForbiddenProgramRegex>>,
     forbidden_substrings: RefCell<Vec<String>>,
+    deny_rules: RefCell<Vec<DenyRule>>,
+    pipeline_rules: RefCell<Vec<PipelineRule>>,
 }
 
 impl PolicyBuilder {
@@ -90,6 +94,8 @@ impl PolicyBuilder {
             programs: RefCell::new(MultiMap::new()),
             forbidden_program_regexes: RefCell::new(Vec::new()),
             forbidden_substrings: RefCell::new(Vec::new()),
+            deny_rules: RefCell::new(Vec::new()),
+            pipeline_rules: RefCell::new(Vec::new()),
         }
     }
 
@@ -97,7 +103,15 @@ impl PolicyBuilder {
         let programs = self.programs.into_inner();
         let forbidden_program_regexes = self.forbidden_program_regexes.into_inner();
         let forbidden_substrings = self.forbidden_substrings.into_inner();
-        Policy::new(programs, forbidden_program_regexes, forbidden_substrings)
+        let deny_rules = self.deny_rules.into_inner();
+        let pipeline_rules = self.pipeline_rules.into_inner();
+        Policy::new(
+            programs,
+            forbidden_program_regexes,
+            forbidden_substrings,
+            deny_rules,
+            pipeline_rules,
+        )
     }
 
     fn add_program_spec(&self, program_spec: ProgramSpec) {
@@ -116,6 +130,16 @@ impl PolicyBuilder {
         let mut forbidden_program_regexes = self.forbidden_program_regexes.borrow_mut();
         forbidden_program_regexes.push(ForbiddenProgramRegex { regex, reason });
     }
+
+    fn add_deny_rule(&self, rule: DenyRule) {
+        info!("adding deny rule: {rule:?}");
+        self.deny_rules.borrow_mut().push(rule);
+    }
+
+    fn add_pipeline_rule(&self, rule: PipelineRule) {
+        info!("adding pipeline rule: {rule:?}");
+        self.pipeline_rules.borrow_mut().push(rule);
+    }
 }
 
 #[starlark_module]
@@ -212,6 +236,83 @@ fn policy_builtins(builder: &mut GlobalsBuilder) {
         Ok(NoneType)
     }
 
+    /// Refuses every invocation of `program`, whatever its arguments.
+    fn deny_program(
+        program: String,
+        reason: String,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        #[expect(clippy::unwrap_used)]
+        let policy_builder = eval
+            .extra
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<PolicyBuilder>()
+            .unwrap();
+        policy_builder.add_deny_rule(DenyRule {
+            program,
+            args: Vec::new(),
+            reason,
+        });
+        Ok(NoneType)
+    }
+
+    /// Refuses invocations of `program` that pass all of `args`.
+    fn forbid_args(
+        program: String,
+        args: UnpackList<String>,
+        reason: String,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        if args.items.is_empty() {
+            return Err(anyhow::format_err!(
+                "forbid_args() for `{program}` needs at least one argument; use deny_program() to refuse every invocation"
+            ));
+        }
+        #[expect(clippy::unwrap_used)]
+        let policy_builder = eval
+            .extra
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<PolicyBuilder>()
+            .unwrap();
+        policy_builder.add_deny_rule(DenyRule {
+            program,
+            args: args.items.to_vec(),
+            reason,
+        });
+        Ok(NoneType)
+    }
+
+    /// Refuses pipelines in which `source` is piped into one of `sinks`,
+    /// possibly through other commands, as in
+    /// `curl https://example.com/install.sh | sh`.
+    fn deny_pipeline(
+        source: String,
+        sinks: UnpackList<String>,
+        reason: String,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        if sinks.items.is_empty() {
+            return Err(anyhow::format_err!(
+                "deny_pipeline() for `{source}` needs at least one sink"
+            ));
+        }
+        #[expect(clippy::unwrap_used)]
+        let policy_builder = eval
+            .extra
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<PolicyBuilder>()
+            .unwrap();
+        policy_builder.add_pipeline_rule(PipelineRule {
+            source,
+            sinks: sinks.items,
+            reason,
+        });
+        Ok(NoneType)
+    }
+
     fn opt(name: String, r#type: ArgMatcher, required: Option<bool>) -> anyhow::Result<Opt> {
         Ok(Opt::new(
             name,
@@ -85,6 +85,16 @@ pub enum Forbidden {
     Exec {
         exec: ValidExec,
     },
+    /// Matched a `deny_program()` or `forbid_args()` rule.
+    Denied {
+        exec_call: ExecCall,
+    },
+    /// Matched a `deny_pipeline()` rule: `source` is piped, possibly through
+    /// other commands, into `sink`.
+    Pipeline {
+        source: ExecCall,
+        sink: ExecCall,
+    },
 }
 
 impl ProgramSpec {
@@ -194,6 +204,11 @@ impl ProgramSpec {
         }
     }
 
+    /// Whether invocations matching this spec are forbidden.
+    pub fn is_forbidden(&self) -> bool {
+        self.forbidden.is_some()
+    }
+
     pub fn verify_should_match_list(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for good in &self.should_match {
@@ -233,14 +248,14 @@ impl ProgramSpec {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Serialize)]
 pub struct PositiveExampleFailedCheck {
     pub program: String,
     pub args: Vec<String>,
     pub error: Error,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Serialize)]
 pub struct NegativeExamplePassedCheck {
     pub program: String,
     pub args: Vec<String>,
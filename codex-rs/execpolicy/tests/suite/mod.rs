@@ -1,6 +1,7 @@
 // Aggregates all former standalone integration tests as modules.
 mod bad;
 mod cp;
+mod deny;
 mod good;
 mod head;
 mod literal;
@@ -1,4 +1,6 @@
 use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -41,36 +43,146 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let deny_read = canonicalize_existing(sandbox_policy.get_deny_read_paths());
+        // Hiding the denied paths behind empty mounts covers files and
+        // directories created after the sandbox starts. Where unprivileged
+        // user namespaces are unavailable, fall back to leaving the denied
+        // paths out of the Landlock rules. Only the entries that exist now
+        // are granted then, so say so on stderr, where the command's output
+        // goes.
+        let hidden = if deny_read.is_empty() {
+            Ok(())
+        } else {
+            hide_paths_in_mount_namespace(&deny_read)
+        };
+        let carved_out = match hidden {
+            Ok(()) => Vec::new(),
+            Err(err) => {
+                eprintln!(
+                    "codex-linux-sandbox: warning: could not hide read-denied paths in a mount \
+                     namespace ({err}); files created next to them while the command runs \
+                     cannot be read"
+                );
+                deny_read
+            }
+        };
+        install_filesystem_landlock_rules_on_current_thread(writable_roots, &carved_out)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the current (single-threaded) process into new user and mount
+/// namespaces and mounts an empty, read-only tmpfs over every directory in
+/// `paths` and `/dev/null` over every file, so that their contents cannot be
+/// read. The mounts cannot be undone once Landlock rules are in place, since
+/// Landlock forbids mount changes.
+fn hide_paths_in_mount_namespace(paths: &[PathBuf]) -> std::io::Result<()> {
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // Keep the same ids inside the namespace; `setgroups` has to be denied
+    // before an unprivileged process may write its `gid_map`.
+    match std::fs::write("/proc/self/setgroups", "deny") {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
     }
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
+    // Do not propagate the mounts below back to the parent namespace.
+    mount(
+        None,
+        Path::new("/"),
+        None,
+        libc::MS_REC | libc::MS_PRIVATE,
+        None,
+    )?;
+    for path in paths {
+        if path.is_dir() {
+            mount(
+                Some("tmpfs"),
+                path,
+                Some("tmpfs"),
+                libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                Some("size=0,mode=000"),
+            )?;
+        } else {
+            mount(Some("/dev/null"), path, None, libc::MS_BIND, None)?;
+            mount(
+                None,
+                path,
+                None,
+                libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY,
+                None,
+            )?;
+        }
+    }
+    Ok(())
+}
 
+fn mount(
+    source: Option<&str>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+    data: Option<&str>,
+) -> std::io::Result<()> {
+    let to_cstring = |value: &str| CString::new(value).map_err(std::io::Error::other);
+    let source = source.map(to_cstring).transpose()?;
+    let target = CString::new(target.as_os_str().as_bytes()).map_err(std::io::Error::other)?;
+    let fstype = fstype.map(to_cstring).transpose()?;
+    let data = data.map(to_cstring).transpose()?;
+    let result = unsafe {
+        libc::mount(
+            source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            target.as_ptr(),
+            fstype.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            flags,
+            data.as_ref()
+                .map_or(std::ptr::null(), |s| s.as_ptr().cast::<libc::c_void>()),
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
     Ok(())
 }
 
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to the entire file-system except `carved_out` while restricting
+/// write access to `/dev/null` and the provided list of `writable_roots`.
+///
+/// Landlock can only grant access, so a carved out path is excluded by
+/// granting access to each of its siblings instead of to its parent. As a
+/// consequence the directories leading up to it cannot be listed, and entries
+/// created in them after the sandbox starts are not accessible either.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    writable_roots: Vec<PathBuf>,
+    carved_out: &[PathBuf],
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
 
+    let readable_paths = paths_beneath_excluding(&[PathBuf::from("/")], carved_out);
+    let writable_paths = paths_beneath_excluding(&writable_roots, carved_out);
+
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&readable_paths, access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    if !writable_paths.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_paths, access_rw))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,6 +194,54 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Resolves symlinks in `paths` so they can be compared against the entries
+/// found while walking the file-system. Paths that do not exist are dropped:
+/// there is nothing to protect and Landlock cannot refer to them anyway.
+fn canonicalize_existing(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect()
+}
+
+/// Returns paths whose subtrees together cover everything beneath `roots`
+/// except the subtrees rooted at `excluded`. Roots that contain an excluded
+/// path are replaced by their entries, recursively. Symlinks are skipped
+/// because a rule on a symlink applies to its target, which may be excluded.
+fn paths_beneath_excluding(roots: &[PathBuf], excluded: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for root in roots {
+        let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+        collect_paths_beneath_excluding(root, excluded, &mut paths);
+    }
+    paths
+}
+
+fn collect_paths_beneath_excluding(root: PathBuf, excluded: &[PathBuf], out: &mut Vec<PathBuf>) {
+    if excluded.iter().any(|path| root.starts_with(path)) {
+        return;
+    }
+    if !excluded.iter().any(|path| path.starts_with(&root)) {
+        out.push(root);
+        return;
+    }
+
+    // If the directory cannot be listed its entries stay inaccessible, which
+    // errs on the side of denying too much.
+    let Ok(entries) = std::fs::read_dir(&root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_symlink())
+        {
+            continue;
+        }
+        collect_paths_beneath_excluding(entry.path(), excluded, out);
+    }
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
 /// AF_UNIX domain sockets.
 fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
@@ -143,3 +303,50 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn paths_beneath_excluding_carves_out_excluded_subtrees() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("home/.ssh")).unwrap();
+        fs::create_dir_all(root.join("home/project/src")).unwrap();
+        fs::write(root.join("home/.bashrc"), "").unwrap();
+        fs::create_dir_all(root.join("etc")).unwrap();
+        std::os::unix::fs::symlink(root.join("home/.ssh"), root.join("home/keys")).unwrap();
+
+        let mut paths =
+            paths_beneath_excluding(std::slice::from_ref(&root), &[root.join("home/.ssh")]);
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                root.join("etc"),
+                root.join("home/.bashrc"),
+                root.join("home/project"),
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_beneath_excluding_drops_roots_inside_excluded_paths() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("secrets/nested")).unwrap();
+        fs::create_dir_all(root.join("workspace")).unwrap();
+
+        let paths = paths_beneath_excluding(
+            &[root.join("secrets/nested"), root.join("workspace")],
+            &[root.join("secrets")],
+        );
+
+        assert_eq!(paths, vec![root.join("workspace")]);
+    }
+}
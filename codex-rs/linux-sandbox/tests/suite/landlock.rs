@@ -34,7 +34,7 @@ fn create_env_from_core_vars() -> HashMap<String, String> {
 }
 
 #[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
-async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
+async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], deny_read: &[PathBuf], timeout_ms: u64) {
     let params = ExecParams {
         command: cmd.iter().map(|elm| elm.to_string()).collect(),
         cwd: std::env::current_dir().expect("cwd should exist"),
@@ -52,6 +52,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        deny_read: deny_read.to_vec(),
     };
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
@@ -74,7 +75,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
 
 #[tokio::test]
 async fn test_root_read() {
-    run_cmd(&["ls", "-l", "/bin"], &[], SHORT_TIMEOUT_MS).await;
+    run_cmd(&["ls", "-l", "/bin"], &[], &[], SHORT_TIMEOUT_MS).await;
 }
 
 #[tokio::test]
@@ -85,6 +86,7 @@ async fn test_root_write() {
     run_cmd(
         &["bash", "-lc", &format!("echo blah > {tmpfile_path}")],
         &[],
+        &[],
         SHORT_TIMEOUT_MS,
     )
     .await;
@@ -95,6 +97,7 @@ async fn test_dev_null_write() {
     run_cmd(
         &["bash", "-lc", "echo blah > /dev/null"],
         &[],
+        &[],
         // We have seen timeouts when running this test in CI on GitHub,
         // so we are using a generous timeout until we can diagnose further.
         LONG_TIMEOUT_MS,
@@ -113,6 +116,7 @@ async fn test_writable_root() {
             &format!("echo blah > {}", file_path.to_string_lossy()),
         ],
         &[tmpdir.path().to_path_buf()],
+        &[],
         // We have seen timeouts when running this test in CI on GitHub,
         // so we are using a generous timeout until we can diagnose further.
         LONG_TIMEOUT_MS,
@@ -120,10 +124,76 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+#[should_panic]
+async fn test_deny_read() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secrets = tmpdir.path().join("secrets");
+    std::fs::create_dir(&secrets).unwrap();
+    let key_path = secrets.join("id_ed25519");
+    std::fs::write(&key_path, "PRIVATE KEY").unwrap();
+    run_cmd(
+        &["cat", &key_path.to_string_lossy()],
+        &[],
+        &[secrets],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_deny_read_allows_siblings() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secrets = tmpdir.path().join("secrets");
+    std::fs::create_dir(&secrets).unwrap();
+    let notes_path = tmpdir.path().join("notes.txt");
+    std::fs::write(&notes_path, "hello").unwrap();
+    run_cmd(
+        &["cat", &notes_path.to_string_lossy()],
+        &[],
+        &[secrets],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_deny_read_inside_writable_root_allows_new_directories() {
+    // Without user namespaces the denied path is carved out of the Landlock
+    // rules, which cannot cover directories created later.
+    let user_namespaces = std::process::Command::new("unshare")
+        .args(["-Ur", "true"])
+        .status()
+        .is_ok_and(|status| status.success());
+    if !user_namespaces {
+        return;
+    }
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secrets = tmpdir.path().join("secrets");
+    std::fs::create_dir(&secrets).unwrap();
+    std::fs::write(secrets.join("key"), "PRIVATE KEY").unwrap();
+    let root = tmpdir.path().to_string_lossy();
+    run_cmd(
+        &[
+            "bash",
+            "-lc",
+            &format!(
+                "mkdir {root}/build && echo ok > {root}/build/out && cat {root}/build/out \
+                 && ! cat {root}/secrets/key"
+            ),
+        ],
+        &[tmpdir.path().to_path_buf()],
+        &[secrets],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout)")]
 async fn test_timeout() {
-    run_cmd(&["sleep", "2"], &[], 50).await;
+    run_cmd(&["sleep", "2"], &[], &[], 50).await;
 }
 
 /// Helper that runs `cmd` under the Linux sandbox and asserts that the command
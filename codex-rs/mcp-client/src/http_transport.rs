@@ -0,0 +1,335 @@
+//! HTTP transports for talking to a remote MCP server.
+//!
+//! The [Streamable HTTP] transport is tried first: every JSON-RPC message is
+//! `POST`ed to the server URL and any reply arrives either as a single JSON
+//! body or as an SSE stream in the response. If the server rejects the very
+//! first `POST` with a 4xx status we assume it only speaks the older
+//! [HTTP+SSE] transport: we open an SSE stream with `GET`, wait for the
+//! `endpoint` event and `POST` every message (including the rejected one) to
+//! that endpoint while replies arrive on the long-lived stream.
+//!
+//! Once the transport is known, every request is `POST`ed from its own task
+//! so that a slow tool call does not hold up the requests sent after it.
+//! Notifications and responses are acknowledged without waiting for any work
+//! on the server and are still sent in order.
+//!
+//! [Streamable HTTP]: https://modelcontextprotocol.io/specification/2025-03-26/basic/transports#streamable-http
+//! [HTTP+SSE]: https://modelcontextprotocol.io/specification/2024-11-05/basic/transports#http-with-sse
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use eventsource_stream::Event;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use reqwest::StatusCode;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use reqwest::header::AUTHORIZATION;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use crate::mcp_client::McpClient;
+use crate::mcp_client::NotificationSender;
+use crate::mcp_client::PendingMap;
+
+/// Header the server uses to assign a session id in the response to
+/// `initialize`; the client must echo it on every subsequent request.
+const MCP_SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// `Accept` header required by the Streamable HTTP transport for `POST`s.
+const STREAMABLE_HTTP_ACCEPT: &str = "application/json, text/event-stream";
+
+/// How long to wait for the `endpoint` event after opening a legacy SSE
+/// stream.
+const SSE_ENDPOINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC "internal error" code, used to fail requests that could not be
+/// delivered to the server.
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Which transport the server turned out to speak.
+#[derive(Clone)]
+enum Transport {
+    /// No message has been delivered yet.
+    Negotiating,
+    StreamableHttp {
+        session_id: Option<String>,
+    },
+    Sse {
+        endpoint: Url,
+    },
+}
+
+/// Where messages received from the server are routed: replies to their
+/// pending request, notifications to the client's subscribers.
+#[derive(Clone)]
+struct Inbox {
+    pending: PendingMap,
+    notifications: NotificationSender,
+}
+
+impl Inbox {
+    async fn dispatch(&self, message: JSONRPCMessage) {
+        McpClient::dispatch_message(message, &self.pending, &self.notifications).await;
+    }
+}
+
+/// Build the HTTP client used for all requests to one server. `headers` and
+/// the optional bearer token are attached to every request.
+pub(crate) fn build_http_client(
+    headers: HashMap<String, String>,
+    bearer_token: Option<String>,
+) -> Result<reqwest::Client> {
+    let mut default_headers = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid HTTP header name `{name}`"))?;
+        let value = HeaderValue::from_str(&value)
+            .with_context(|| format!("invalid value for HTTP header `{name}`"))?;
+        default_headers.insert(name, value);
+    }
+    if let Some(token) = bearer_token {
+        let mut value =
+            HeaderValue::from_str(&format!("Bearer {token}")).context("invalid bearer token")?;
+        value.set_sensitive(true);
+        default_headers.insert(AUTHORIZATION, value);
+    }
+
+    reqwest::Client::builder()
+        .default_headers(default_headers)
+        .build()
+        .context("failed to build HTTP client")
+}
+
+/// Writer task: deliver every message from `outgoing_rx` to the server at
+/// `url`. Messages received in reply are routed through `pending` and
+/// `notifications`.
+pub(crate) async fn run_writer(
+    http: reqwest::Client,
+    url: Url,
+    mut outgoing_rx: mpsc::Receiver<JSONRPCMessage>,
+    pending: PendingMap,
+    notifications: NotificationSender,
+) {
+    let mut transport = Transport::Negotiating;
+    let inbox = Inbox {
+        pending,
+        notifications,
+    };
+    while let Some(message) = outgoing_rx.recv().await {
+        let negotiated = !matches!(transport, Transport::Negotiating);
+        if negotiated && matches!(message, JSONRPCMessage::Request(_)) {
+            let http = http.clone();
+            let url = url.clone();
+            let mut transport = transport.clone();
+            let inbox = inbox.clone();
+            tokio::spawn(async move {
+                if let Err(e) = send(&http, &url, &mut transport, &message, &inbox).await {
+                    fail_message(&url, message, e, &inbox).await;
+                }
+            });
+        } else if let Err(e) = send(&http, &url, &mut transport, &message, &inbox).await {
+            fail_message(&url, message, e, &inbox).await;
+        }
+    }
+}
+
+/// Log a message that could not be delivered. A request is failed right away
+/// rather than leaving the caller waiting for a reply that will never arrive.
+async fn fail_message(url: &Url, message: JSONRPCMessage, e: anyhow::Error, inbox: &Inbox) {
+    error!("failed to send message to MCP server at {url}: {e:#}");
+    if let JSONRPCMessage::Request(request) = message {
+        let err = JSONRPCError {
+            error: JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                data: None,
+                message: format!("{e:#}"),
+            },
+            id: request.id,
+            jsonrpc: JSONRPC_VERSION.to_string(),
+        };
+        McpClient::dispatch_error(err, &inbox.pending).await;
+    }
+}
+
+async fn send(
+    http: &reqwest::Client,
+    url: &Url,
+    transport: &mut Transport,
+    message: &JSONRPCMessage,
+    inbox: &Inbox,
+) -> Result<()> {
+    match transport {
+        Transport::Negotiating => {
+            let response = post_streamable(http, url, None, message).await?;
+            let status = response.status();
+            if status.is_client_error() {
+                info!("MCP server at {url} rejected POST with {status}; falling back to HTTP+SSE");
+                let endpoint = connect_sse(http, url, inbox).await.with_context(|| {
+                    format!("server rejected POST with {status} and the SSE fallback failed")
+                })?;
+                post_to_sse_endpoint(http, &endpoint, message).await?;
+                *transport = Transport::Sse { endpoint };
+            } else {
+                let session_id = response
+                    .headers()
+                    .get(MCP_SESSION_ID_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                handle_streamable_response(response, inbox).await?;
+                *transport = Transport::StreamableHttp { session_id };
+            }
+        }
+        Transport::StreamableHttp { session_id } => {
+            let response = post_streamable(http, url, session_id.as_deref(), message).await?;
+            handle_streamable_response(response, inbox).await?;
+        }
+        Transport::Sse { endpoint } => post_to_sse_endpoint(http, endpoint, message).await?,
+    }
+    Ok(())
+}
+
+async fn post_streamable(
+    http: &reqwest::Client,
+    url: &Url,
+    session_id: Option<&str>,
+    message: &JSONRPCMessage,
+) -> Result<reqwest::Response> {
+    debug!("MCP message to server: {message:?}");
+    let mut request = http
+        .post(url.clone())
+        .header(ACCEPT, STREAMABLE_HTTP_ACCEPT)
+        .json(message);
+    if let Some(session_id) = session_id {
+        request = request.header(MCP_SESSION_ID_HEADER, session_id);
+    }
+    Ok(request.send().await?)
+}
+
+/// Handle the response to a Streamable HTTP `POST`: a JSON body is dispatched
+/// immediately, an SSE body is read in the background so that a long-lived
+/// stream does not hold up later messages.
+async fn handle_streamable_response(response: reqwest::Response, inbox: &Inbox) -> Result<()> {
+    let response = response.error_for_status()?;
+    if response.status() == StatusCode::ACCEPTED {
+        // Notifications and responses are acknowledged without a body.
+        return Ok(());
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if content_type.starts_with("text/event-stream") {
+        let events = response.bytes_stream().eventsource();
+        let inbox = inbox.clone();
+        tokio::spawn(async move { dispatch_sse_messages(events, &inbox).await });
+    } else if content_type.starts_with("application/json") {
+        let body = response.bytes().await?;
+        if !body.is_empty() {
+            // The body is either a single message or a JSON-RPC batch.
+            let messages = match serde_json::from_slice::<serde_json::Value>(&body)? {
+                serde_json::Value::Array(values) => values,
+                value => vec![value],
+            };
+            for message in messages {
+                inbox.dispatch(serde_json::from_value(message)?).await;
+            }
+        }
+    } else {
+        debug!("ignoring MCP response with content type `{content_type}`");
+    }
+    Ok(())
+}
+
+/// Open a legacy HTTP+SSE stream and wait for the server to announce the
+/// endpoint that messages must be `POST`ed to. The remainder of the stream is
+/// read in the background.
+async fn connect_sse(http: &reqwest::Client, url: &Url, inbox: &Inbox) -> Result<Url> {
+    let response = http
+        .get(url.clone())
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .await?
+        .error_for_status()?;
+    let mut events = response.bytes_stream().eventsource();
+
+    let endpoint = tokio::time::timeout(SSE_ENDPOINT_TIMEOUT, async {
+        while let Some(event) = events.next().await {
+            let event = event.map_err(|e| anyhow!("error reading SSE stream: {e}"))?;
+            if event.event == "endpoint" {
+                return url
+                    .join(event.data.trim())
+                    .with_context(|| format!("invalid SSE endpoint `{}`", event.data));
+            }
+            debug!("ignoring SSE event `{}` before endpoint", event.event);
+        }
+        Err(anyhow!(
+            "SSE stream closed before the endpoint was announced"
+        ))
+    })
+    .await
+    .map_err(|_| anyhow!("timed out waiting for the SSE endpoint event"))??;
+
+    let inbox = inbox.clone();
+    tokio::spawn(async move { dispatch_sse_messages(events, &inbox).await });
+    Ok(endpoint)
+}
+
+async fn post_to_sse_endpoint(
+    http: &reqwest::Client,
+    endpoint: &Url,
+    message: &JSONRPCMessage,
+) -> Result<()> {
+    debug!("MCP message to server: {message:?}");
+    http.post(endpoint.clone())
+        .json(message)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Dispatch every `message` event on an SSE stream until it ends.
+async fn dispatch_sse_messages<S, E>(mut events: S, inbox: &Inbox)
+where
+    S: Stream<Item = std::result::Result<Event, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) if event.event.is_empty() || event.event == "message" => {
+                debug!("MCP message from server: {}", event.data);
+                match serde_json::from_str::<JSONRPCMessage>(&event.data) {
+                    Ok(message) => inbox.dispatch(message).await,
+                    Err(e) => error!(
+                        "failed to deserialize JSONRPCMessage: {e}; data = {}",
+                        event.data
+                    ),
+                }
+            }
+            Ok(event) => debug!("ignoring SSE event `{}`", event.event),
+            Err(e) => {
+                error!("error reading SSE stream from MCP server: {e}");
+                break;
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+mod http_transport;
 mod mcp_client;
 
 pub use mcp_client::McpClient;
+pub use mcp_client::RequestTimedOut;
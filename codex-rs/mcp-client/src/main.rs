@@ -7,7 +7,9 @@
 //! ```
 //!
 //! Any additional arguments after the first one are forwarded to the spawned
-//! program. The utility connects, issues a `tools/list` request and prints the
+//! program. If the first argument is an `http://` or `https://` URL, the
+//! utility connects to that remote server instead of spawning a program. The
+//! utility connects, issues a `tools/list` request and prints the
 //! server's response as pretty JSON.
 
 use std::ffi::OsString;
@@ -41,17 +43,26 @@ async fn main() -> Result<()> {
     let mut args: Vec<OsString> = std::env::args_os().skip(1).collect();
 
     if args.is_empty() || args[0] == "--help" || args[0] == "-h" {
-        eprintln!("Usage: mcp-client <program> [args..]\n\nExample: mcp-client codex-mcp-server");
+        eprintln!(
+            "Usage: mcp-client <program> [args..] | <url>\n\nExample: mcp-client codex-mcp-server"
+        );
         std::process::exit(1);
     }
     let original_args = args.clone();
 
-    // Spawn the subprocess and connect the client.
     let program = args.remove(0);
-    let env = None;
-    let client = McpClient::new_stdio_client(program, args, env)
-        .await
-        .with_context(|| format!("failed to spawn subprocess: {original_args:?}"))?;
+    let client = match program.to_str() {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+            McpClient::new_streamable_http_client(url, Default::default(), None).await?
+        }
+        _ => {
+            // Spawn the subprocess and connect the client.
+            let env = None;
+            McpClient::new_stdio_client(program, args, env)
+                .await
+                .with_context(|| format!("failed to spawn subprocess: {original_args:?}"))?
+        }
+    };
 
     let params = InitializeRequestParams {
         capabilities: ClientCapabilities {
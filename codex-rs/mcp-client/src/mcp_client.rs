@@ -2,16 +2,21 @@
 //!
 //! The client is intentionally lightweight – it is only capable of:
 //!   1. Spawning a subprocess that launches a conforming MCP server that
-//!      communicates over stdio.
+//!      communicates over stdio, or connecting to a remote MCP server over
+//!      HTTP (see [`crate::http_transport`]).
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
-//!   3. Offering a convenience helper for the common `tools/list` request.
+//!   3. Offering convenience helpers for the common `tools/*`, `resources/*`
+//!      and `prompts/*` requests.
+//!   4. Forwarding server-initiated notifications to subscribers (see
+//!      [`McpClient::subscribe_notifications`]).
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
 //! issue requests and receive strongly-typed results.
 
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::ffi::OsString;
 use std::sync::Arc;
 use std::sync::atomic::AtomicI64;
@@ -23,6 +28,11 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CancelledNotification;
+use mcp_types::CancelledNotificationParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -31,11 +41,20 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -44,38 +63,110 @@ use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::time;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::http_transport;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
 
+/// Number of server notifications buffered for each subscriber before the
+/// oldest ones are dropped.
+const NOTIFICATION_CAPACITY: usize = 64;
+
+/// Number of lines of the server's stderr kept for
+/// [`McpClient::stderr_tail`].
+const STDERR_TAIL_LINES: usize = 20;
+
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// Map of `request.id -> oneshot::Sender` shared between the client API and
+/// the transport tasks.
+pub(crate) type PendingMap = Arc<Mutex<HashMap<i64, PendingSender>>>;
+
+/// Sender for notifications initiated by the server, shared between the client
+/// API and the transport tasks.
+pub(crate) type NotificationSender = broadcast::Sender<JSONRPCNotification>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
-    #[allow(dead_code)]
-    child: tokio::process::Child,
+    ///
+    /// `None` for clients connected to a remote server over HTTP.
+    child: Option<tokio::process::Child>,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
 
     /// Map of `request.id -> oneshot::Sender` used to dispatch responses back
     /// to the originating caller.
-    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    pending: PendingMap,
+
+    /// Notifications received from the server, see
+    /// [`subscribe_notifications`](Self::subscribe_notifications).
+    notifications: NotificationSender,
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Becomes `true` (or is dropped) once the connection to the server is
+    /// lost, see [`closed`](Self::closed).
+    closed: watch::Receiver<bool>,
+
+    /// The last lines the server wrote to stderr. Always empty for remote
+    /// servers.
+    stderr_tail: Arc<std::sync::Mutex<VecDeque<String>>>,
+}
+
+/// Error returned by [`McpClient::send_request`] when the server does not reply
+/// within the timeout. The request has been cancelled on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTimedOut {
+    /// The method of the request that timed out, e.g. `tools/call`.
+    pub method: &'static str,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for RequestTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "request `{}` timed out after {}ms",
+            self.method,
+            self.timeout.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for RequestTimedOut {}
+
+/// Sends `notifications/cancelled` for an in-flight request when dropped,
+/// unless it was disarmed because the request completed.
+struct CancelOnDrop<'a> {
+    client: &'a McpClient,
+    id: i64,
+    reason: &'static str,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.client.cancel_request(self.id, self.reason);
+        }
+    }
 }
 
 impl McpClient {
@@ -93,7 +184,7 @@ impl McpClient {
             .envs(create_env_for_mcp_server(env))
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
             // As noted in the `kill_on_drop` documentation, the Tokio runtime makes
             // a "best effort" to reap-after-exit to avoid zombie processes, but it
             // is not a guarantee.
@@ -108,9 +199,16 @@ impl McpClient {
             .stdout
             .take()
             .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| std::io::Error::other("failed to capture child stderr"))?;
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
-        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        let (closed_tx, closed) = watch::channel(false);
+        let stderr_tail = Arc::new(std::sync::Mutex::new(VecDeque::new()));
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -138,35 +236,49 @@ impl McpClient {
         };
 
         // Spawn reader task. It reads line-delimited JSON from the child's
-        // STDOUT and dispatches responses to the pending map.
+        // STDOUT and dispatches responses to the pending map. Once STDOUT is
+        // closed, e.g. because the child exited, the requests still waiting
+        // for a reply fail and the client is marked as closed.
         let reader_handle = {
             let pending = pending.clone();
+            let notifications = notifications.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
                     debug!("MCP message from server: {line}");
                     match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
+                        Ok(message) => {
+                            Self::dispatch_message(message, &pending, &notifications).await
                         }
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
                         }
                     }
                 }
+                // Mark the client as closed *before* failing the pending
+                // requests, see `send_request`.
+                let _ = closed_tx.send(true);
+                pending.lock().await.clear();
+            })
+        };
+
+        // Spawn stderr task. It keeps the last lines the child wrote to
+        // STDERR so that they can be shown when the server misbehaves.
+        let stderr_handle = {
+            let stderr_tail = stderr_tail.clone();
+            let mut lines = BufReader::new(stderr).lines();
+
+            tokio::spawn(async move {
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("MCP server stderr: {line}");
+                    if let Ok(mut tail) = stderr_tail.lock() {
+                        if tail.len() == STDERR_TAIL_LINES {
+                            tail.pop_front();
+                        }
+                        tail.push_back(line);
+                    }
+                }
             })
         };
 
@@ -174,13 +286,65 @@ impl McpClient {
         // background as long as their respective resources (channels/stdin/
         // stdout) are alive. Dropping `McpClient` cancels the tasks due to
         // dropped resources.
-        let _ = (writer_handle, reader_handle);
+        let _ = (writer_handle, reader_handle, stderr_handle);
+
+        Ok(Self {
+            child: Some(child),
+            outgoing_tx,
+            pending,
+            notifications,
+            id_counter: AtomicI64::new(1),
+            closed,
+            stderr_tail,
+        })
+    }
+
+    /// Connect to a remote MCP server at `url` using the Streamable HTTP
+    /// transport, falling back to the legacy HTTP+SSE transport if the server
+    /// rejects the first `POST`. `headers` are sent with every request and
+    /// `bearer_token`, if any, is sent as an `Authorization: Bearer` header.
+    ///
+    /// No network traffic happens until the first message is sent, so as with
+    /// [`new_stdio_client`](Self::new_stdio_client) the caller is responsible
+    /// for calling [`initialize`](Self::initialize).
+    pub async fn new_streamable_http_client(
+        url: &str,
+        headers: HashMap<String, String>,
+        bearer_token: Option<String>,
+    ) -> Result<Self> {
+        let url =
+            reqwest::Url::parse(url).with_context(|| format!("invalid MCP server url `{url}`"))?;
+        let http = http_transport::build_http_client(headers, bearer_token)?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        let (closed_tx, closed) = watch::channel(false);
+
+        // As with the stdio transport, the writer task is detached and exits
+        // once `outgoing_tx` is dropped together with the client. A remote
+        // server has no process that could exit, so the client only counts
+        // as closed from then on.
+        let writer = http_transport::run_writer(
+            http,
+            url,
+            outgoing_rx,
+            pending.clone(),
+            notifications.clone(),
+        );
+        tokio::spawn(async move {
+            writer.await;
+            let _ = closed_tx.send(true);
+        });
 
         Ok(Self {
-            child,
+            child: None,
             outgoing_tx,
             pending,
+            notifications,
             id_counter: AtomicI64::new(1),
+            closed,
+            stderr_tail: Arc::default(),
         })
     }
 
@@ -188,7 +352,11 @@ impl McpClient {
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
     /// is supplied and no response is received within the given period, a
-    /// timeout error is returned.
+    /// [`RequestTimedOut`] error is returned.
+    ///
+    /// If the timeout fires or the returned future is dropped before the
+    /// reply arrives, the server is sent `notifications/cancelled` for the
+    /// request.
     pub async fn send_request<R>(
         &self,
         params: R::Params,
@@ -231,8 +399,28 @@ impl McpClient {
             guard.insert(id, tx);
         }
 
+        // Once the client is closed nobody would reply. Checking *after*
+        // registering cannot miss a concurrent close: the reader task clears
+        // the pending map after marking the client as closed.
+        if *self.closed.borrow() {
+            self.pending.lock().await.remove(&id);
+            return Err(anyhow!("connection to the MCP server is closed"));
+        }
+
+        // From here on the server may be working on the request, so tell it to
+        // stop if we give up on the reply: because the timeout fires or
+        // because this future is dropped, e.g. when the user interrupts a turn.
+        let mut cancel = CancelOnDrop {
+            client: self,
+            id,
+            reason: "request cancelled by the client",
+            armed: true,
+        };
+
         // Send to writer task.
         if self.outgoing_tx.send(message).await.is_err() {
+            cancel.armed = false;
+            self.pending.lock().await.remove(&id);
             return Err(anyhow!(
                 "failed to send message to writer task - channel closed"
             ));
@@ -245,6 +433,7 @@ impl McpClient {
                     Ok(Ok(msg)) => msg,
                     Ok(Err(_)) => {
                         // Channel closed without a reply – remove the pending entry.
+                        cancel.armed = false;
                         let mut guard = self.pending.lock().await;
                         guard.remove(&id);
                         return Err(anyhow!(
@@ -252,17 +441,25 @@ impl McpClient {
                         ));
                     }
                     Err(_) => {
-                        // Timed out. Remove the pending entry so we don't leak.
+                        // Timed out. Remove the pending entry so we don't leak;
+                        // `cancel` notifies the server when it goes out of scope.
+                        cancel.reason = "request timed out";
                         let mut guard = self.pending.lock().await;
                         guard.remove(&id);
-                        return Err(anyhow!("request timed out"));
+                        return Err(RequestTimedOut {
+                            method: R::METHOD,
+                            timeout: duration,
+                        }
+                        .into());
                     }
                 }
             }
-            None => rx
-                .await
-                .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
+            None => rx.await.map_err(|_| {
+                cancel.armed = false;
+                anyhow!("response channel closed before a reply was received")
+            })?,
         };
+        cancel.armed = false;
 
         match msg {
             JSONRPCMessage::Response(JSONRPCResponse { result, .. }) => {
@@ -308,6 +505,50 @@ impl McpClient {
             .with_context(|| format!("failed to send notification `{method}` to writer task"))
     }
 
+    /// Resolves once the connection to the server is lost, e.g. because the
+    /// server process exited. Requests that were still waiting for a reply
+    /// have failed by then.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static + use<> {
+        let mut closed = self.closed.clone();
+        async move {
+            // An error means the reader task is gone, which also counts as closed.
+            let _ = closed.wait_for(|closed| *closed).await;
+        }
+    }
+
+    /// The last lines (at most 20) the server process wrote to stderr, oldest
+    /// first.
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Best-effort cancellation of request `id`: forgets its pending reply and
+    /// sends `notifications/cancelled` without waiting for the writer task,
+    /// which makes it usable from `Drop`.
+    fn cancel_request(&self, id: i64, reason: &str) {
+        if let Ok(mut guard) = self.pending.try_lock() {
+            guard.remove(&id);
+        }
+        let params = CancelledNotificationParams {
+            reason: Some(reason.to_string()),
+            request_id: RequestId::Integer(id),
+        };
+        let notification = JSONRPCNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: CancelledNotification::METHOD.to_string(),
+            params: serde_json::to_value(params).ok(),
+        };
+        if let Err(e) = self
+            .outgoing_tx
+            .try_send(JSONRPCMessage::Notification(notification))
+        {
+            warn!("failed to cancel MCP request {id}: {e}");
+        }
+    }
+
     /// Negotiates the initialization with the MCP server. Sends an `initialize`
     /// request with the specified `initialize_params` and then the
     /// `notifications/initialized` notification once the response has been
@@ -335,6 +576,12 @@ impl McpClient {
         self.send_request::<ListToolsRequest>(params, timeout).await
     }
 
+    /// Subscribe to the notifications that the server sends from now on, such
+    /// as `notifications/resources/list_changed`.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<JSONRPCNotification> {
+        self.notifications.subscribe()
+    }
+
     /// Convenience wrapper around `tools/call`.
     pub async fn call_tool(
         &self,
@@ -347,11 +594,74 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
-    /// Internal helper: route a JSON-RPC *response* object to the pending map.
-    async fn dispatch_response(
-        resp: JSONRPCResponse,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
+    /// Internal helper: route any message received from the server. Responses
+    /// and errors are paired with their pending request and notifications are
+    /// forwarded to subscribers; everything else is only logged.
+    pub(crate) async fn dispatch_message(
+        message: JSONRPCMessage,
+        pending: &PendingMap,
+        notifications: &NotificationSender,
     ) {
+        match message {
+            JSONRPCMessage::Response(resp) => Self::dispatch_response(resp, pending).await,
+            JSONRPCMessage::Error(err) => Self::dispatch_error(err, pending).await,
+            JSONRPCMessage::Notification(notification) => {
+                info!("<- notification: {:?}", notification);
+                // Sending only fails when nobody is subscribed.
+                let _ = notifications.send(notification);
+            }
+            JSONRPCMessage::Request(request) => {
+                // Server-initiated requests are currently not supported – log
+                // and ignore.
+                info!("<- unhandled request: {:?}", request);
+            }
+        }
+    }
+
+    /// Internal helper: route a JSON-RPC *response* object to the pending map.
+    async fn dispatch_response(resp: JSONRPCResponse, pending: &PendingMap) {
         let id = match resp.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => {
@@ -375,10 +685,7 @@ impl McpClient {
     }
 
     /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
+    pub(crate) async fn dispatch_error(err: mcp_types::JSONRPCError, pending: &PendingMap) {
         let id = match err.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => return, // see comment above
@@ -400,7 +707,9 @@ impl Drop for McpClient {
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.try_wait();
+        }
     }
 }
 
@@ -0,0 +1,491 @@
+#![expect(clippy::unwrap_used)]
+
+//! Exercises the HTTP transports of `McpClient` against a minimal in-process
+//! HTTP server.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use codex_mcp_client::McpClient;
+use codex_mcp_client::RequestTimedOut;
+use mcp_types::ClientCapabilities;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use mcp_types::MCP_SCHEMA_VERSION;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::sync::mpsc;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+#[derive(Debug, Clone)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    /// Header names are lowercased.
+    headers: HashMap<String, String>,
+    body: Option<Value>,
+}
+
+impl RecordedRequest {
+    fn rpc_method(&self) -> Option<&str> {
+        self.body.as_ref()?.get("method")?.as_str()
+    }
+}
+
+type Requests = Arc<Mutex<Vec<RecordedRequest>>>;
+
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<RecordedRequest> {
+    let mut request_line = String::new();
+    stream.read_line(&mut request_line).await.ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    let body = if content_length > 0 {
+        let mut buf = vec![0; content_length];
+        stream.read_exact(&mut buf).await.ok()?;
+        serde_json::from_slice(&buf).ok()
+    } else {
+        None
+    };
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+async fn write_response(
+    stream: &mut BufReader<TcpStream>,
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) {
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    let _ = stream.get_mut().write_all(response.as_bytes()).await;
+    let _ = stream.get_mut().shutdown().await;
+}
+
+/// The reply a well-behaved server would send for `message`, if any.
+fn reply_to(message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let result = match message.get("method")?.as_str()? {
+        "initialize" => json!({
+            "capabilities": { "tools": {} },
+            "protocolVersion": MCP_SCHEMA_VERSION,
+            "serverInfo": { "name": "test-server", "version": "0.0.0" },
+        }),
+        "tools/list" => json!({
+            "tools": [{ "name": "scan", "inputSchema": { "type": "object" } }],
+        }),
+        _ => return None,
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// Server that speaks the Streamable HTTP transport: `initialize` is answered
+/// with a JSON body, later requests with an SSE body.
+async fn start_streamable_http_server() -> (SocketAddr, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Requests::default();
+
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                let Some(request) = read_request(&mut stream).await else {
+                    return;
+                };
+                recorded.lock().await.push(request.clone());
+
+                let reply = request.body.as_ref().and_then(reply_to);
+                match (request.rpc_method(), reply) {
+                    (Some("initialize"), Some(reply)) => {
+                        let headers = [
+                            ("Content-Type", "application/json"),
+                            ("Mcp-Session-Id", "session-1"),
+                        ];
+                        write_response(&mut stream, "200 OK", &headers, &reply.to_string()).await;
+                    }
+                    (_, Some(reply)) => {
+                        let body = format!("event: message\ndata: {reply}\n\n");
+                        let headers = [("Content-Type", "text/event-stream")];
+                        write_response(&mut stream, "200 OK", &headers, &body).await;
+                    }
+                    (_, None) => write_response(&mut stream, "202 Accepted", &[], "").await,
+                }
+            });
+        }
+    });
+
+    (addr, requests)
+}
+
+/// Server that only speaks the legacy HTTP+SSE transport: `POST /` is
+/// rejected, `GET /` opens the event stream and replies to messages posted
+/// to `/messages` are delivered on that stream.
+async fn start_legacy_sse_server() -> (SocketAddr, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Requests::default();
+    let (reply_tx, reply_rx) = mpsc::unbounded_channel::<Value>();
+    let reply_rx = Arc::new(Mutex::new(Some(reply_rx)));
+
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let recorded = recorded.clone();
+            let reply_tx = reply_tx.clone();
+            let reply_rx = reply_rx.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                let Some(request) = read_request(&mut stream).await else {
+                    return;
+                };
+                recorded.lock().await.push(request.clone());
+
+                match (request.method.as_str(), request.path.as_str()) {
+                    ("GET", "/") => {
+                        let Some(mut reply_rx) = reply_rx.lock().await.take() else {
+                            return;
+                        };
+                        let stream = stream.get_mut();
+                        let _ = stream
+                            .write_all(
+                                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\nevent: endpoint\ndata: /messages?session_id=abc\n\n",
+                            )
+                            .await;
+                        while let Some(reply) = reply_rx.recv().await {
+                            let event = format!("event: message\ndata: {reply}\n\n");
+                            if stream.write_all(event.as_bytes()).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    ("POST", "/messages?session_id=abc") => {
+                        if let Some(reply) = request.body.as_ref().and_then(reply_to) {
+                            let _ = reply_tx.send(reply);
+                        }
+                        write_response(&mut stream, "202 Accepted", &[], "").await;
+                    }
+                    _ => write_response(&mut stream, "405 Method Not Allowed", &[], "").await,
+                }
+            });
+        }
+    });
+
+    (addr, requests)
+}
+
+/// Streamable HTTP server that holds back its reply to `tools/call` until it
+/// has received a `tools/list` request.
+async fn start_slow_tool_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let tools_listed = Arc::new(Notify::new());
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tools_listed = tools_listed.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                let Some(request) = read_request(&mut stream).await else {
+                    return;
+                };
+                let Some(body) = request.body.clone() else {
+                    return;
+                };
+                let reply = match request.rpc_method() {
+                    Some("tools/call") => {
+                        tools_listed.notified().await;
+                        Some(json!({
+                            "jsonrpc": "2.0",
+                            "id": body["id"],
+                            "result": { "content": [] },
+                        }))
+                    }
+                    Some("tools/list") => {
+                        tools_listed.notify_one();
+                        reply_to(&body)
+                    }
+                    _ => reply_to(&body),
+                };
+                match reply {
+                    Some(reply) => {
+                        let headers = [("Content-Type", "application/json")];
+                        write_response(&mut stream, "200 OK", &headers, &reply.to_string()).await;
+                    }
+                    None => write_response(&mut stream, "202 Accepted", &[], "").await,
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+fn initialize_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: "0.0.0".to_owned(),
+            title: None,
+        },
+        protocol_version: MCP_SCHEMA_VERSION.to_owned(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streamable_http_client_initializes_and_lists_tools() {
+    let (addr, requests) = start_streamable_http_server().await;
+
+    let headers = HashMap::from([("X-Scanner".to_string(), "nightly".to_string())]);
+    let client = McpClient::new_streamable_http_client(
+        &format!("http://{addr}/mcp"),
+        headers,
+        Some("secret-token".to_string()),
+    )
+    .await
+    .unwrap();
+
+    let init = client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(init.server_info.name, "test-server");
+
+    let tools = client.list_tools(None, TIMEOUT).await.unwrap();
+    let names: Vec<_> = tools.tools.into_iter().map(|tool| tool.name).collect();
+    assert_eq!(names, vec!["scan".to_string()]);
+
+    let requests = requests.lock().await.clone();
+    let methods: Vec<_> = requests.iter().map(RecordedRequest::rpc_method).collect();
+    assert_eq!(
+        methods,
+        vec![
+            Some("initialize"),
+            Some("notifications/initialized"),
+            Some("tools/list"),
+        ]
+    );
+    for request in &requests {
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/mcp");
+        assert_eq!(
+            request.headers.get("authorization").map(String::as_str),
+            Some("Bearer secret-token")
+        );
+        assert_eq!(
+            request.headers.get("x-scanner").map(String::as_str),
+            Some("nightly")
+        );
+    }
+    // The session id assigned in response to `initialize` is echoed back.
+    let session_ids: Vec<_> = requests
+        .iter()
+        .map(|request| request.headers.get("mcp-session-id").cloned())
+        .collect();
+    assert_eq!(
+        session_ids,
+        vec![
+            None,
+            Some("session-1".to_string()),
+            Some("session-1".to_string()),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn http_client_falls_back_to_sse_transport() {
+    let (addr, requests) = start_legacy_sse_server().await;
+
+    let client =
+        McpClient::new_streamable_http_client(&format!("http://{addr}/"), HashMap::new(), None)
+            .await
+            .unwrap();
+
+    let init = client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(init.server_info.name, "test-server");
+
+    let tools = client.list_tools(None, TIMEOUT).await.unwrap();
+    assert_eq!(tools.tools.len(), 1);
+
+    let requests = requests.lock().await.clone();
+    let summary: Vec<_> = requests
+        .iter()
+        .map(|request| {
+            (
+                request.method.as_str(),
+                request.path.as_str(),
+                request.rpc_method(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("POST", "/", Some("initialize")),
+            ("GET", "/", None),
+            ("POST", "/messages?session_id=abc", Some("initialize")),
+            (
+                "POST",
+                "/messages?session_id=abc",
+                Some("notifications/initialized")
+            ),
+            ("POST", "/messages?session_id=abc", Some("tools/list")),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn slow_requests_do_not_hold_up_later_ones() {
+    let addr = start_slow_tool_server().await;
+
+    let client =
+        McpClient::new_streamable_http_client(&format!("http://{addr}/mcp"), HashMap::new(), None)
+            .await
+            .unwrap();
+    client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .unwrap();
+
+    // `tools/call` is only answered once `tools/list` reaches the server,
+    // which requires both requests to be in flight at the same time.
+    let (call, tools) = tokio::join!(client.call_tool("scan".to_string(), None, TIMEOUT), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.list_tools(None, TIMEOUT).await
+    },);
+    assert_eq!(call.unwrap().content, Vec::new());
+    assert_eq!(tools.unwrap().tools.len(), 1);
+}
+
+#[tokio::test]
+async fn http_client_fails_requests_when_server_is_unreachable() {
+    // Bind and immediately drop a listener to get a port nothing listens on.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let client =
+        McpClient::new_streamable_http_client(&format!("http://{addr}/"), HashMap::new(), None)
+            .await
+            .unwrap();
+
+    // The request fails promptly instead of waiting for the timeout.
+    let err = client
+        .list_tools(None, Some(Duration::from_secs(30)))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("server returned JSON-RPC error"),
+        "unexpected error: {err}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timed_out_requests_are_cancelled_on_the_server() {
+    let (addr, requests) = start_streamable_http_server().await;
+
+    let client =
+        McpClient::new_streamable_http_client(&format!("http://{addr}/mcp"), HashMap::new(), None)
+            .await
+            .unwrap();
+    client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .unwrap();
+
+    // The test server never replies to `tools/call`.
+    let err = client
+        .call_tool("scan".to_string(), None, Some(Duration::from_millis(200)))
+        .await
+        .unwrap_err();
+    let timed_out = err.downcast_ref::<RequestTimedOut>().unwrap();
+    assert_eq!(
+        timed_out,
+        &RequestTimedOut {
+            method: "tools/call",
+            timeout: Duration::from_millis(200),
+        }
+    );
+
+    let cancelled = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let requests = requests.lock().await.clone();
+            if let Some(body) = requests
+                .iter()
+                .find(|request| request.rpc_method() == Some("notifications/cancelled"))
+                .and_then(|request| request.body.clone())
+            {
+                return body;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let call_id = requests
+        .lock()
+        .await
+        .iter()
+        .find(|request| request.rpc_method() == Some("tools/call"))
+        .and_then(|request| request.body.as_ref()?.get("id").cloned())
+        .unwrap();
+    assert_eq!(
+        cancelled["params"],
+        json!({ "reason": "request timed out", "requestId": call_id })
+    );
+}
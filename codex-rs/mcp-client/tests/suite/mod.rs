@@ -0,0 +1,2 @@
+mod http_transport;
+mod stdio;
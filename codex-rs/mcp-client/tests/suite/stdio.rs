@@ -0,0 +1,52 @@
+//! Exercises `McpClient` against server processes spawned over stdio.
+
+use std::time::Duration;
+
+use codex_mcp_client::McpClient;
+use pretty_assertions::assert_eq;
+
+#[cfg(unix)]
+#[tokio::test]
+async fn exited_server_closes_the_client_and_keeps_its_stderr() {
+    let client = McpClient::new_stdio_client(
+        "/bin/sh".into(),
+        vec![
+            "-c".into(),
+            "echo 'starting up' >&2; echo 'fatal: no config' >&2; exit 1".into(),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), client.closed())
+        .await
+        .unwrap();
+
+    // stderr is drained by a separate task; give it a moment to catch up.
+    let tail = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let tail = client.stderr_tail();
+            if tail.len() == 2 {
+                return tail;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        tail,
+        vec!["starting up".to_string(), "fatal: no config".to_string()]
+    );
+
+    // Requests to the closed client fail instead of waiting for the timeout.
+    let err = client
+        .list_tools(None, Some(Duration::from_secs(30)))
+        .await
+        .unwrap_err();
+    assert!(
+        !err.is::<codex_mcp_client::RequestTimedOut>(),
+        "unexpected timeout: {err}"
+    );
+}
@@ -15,6 +15,7 @@ use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::McpToolApprovalRequestEvent;
 use codex_core::protocol::ReviewDecision;
 use codex_login::AuthManager;
 use codex_protocol::mcp_protocol::AuthMode;
@@ -54,6 +55,9 @@ use codex_protocol::mcp_protocol::InterruptConversationParams;
 use codex_protocol::mcp_protocol::InterruptConversationResponse;
 use codex_protocol::mcp_protocol::LoginChatGptCompleteNotification;
 use codex_protocol::mcp_protocol::LoginChatGptResponse;
+use codex_protocol::mcp_protocol::MCP_TOOL_APPROVAL_METHOD;
+use codex_protocol::mcp_protocol::McpToolApprovalParams;
+use codex_protocol::mcp_protocol::McpToolApprovalResponse;
 use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::RemoveConversationListenerParams;
@@ -539,6 +543,7 @@ impl CodexMessageProcessor {
                 model,
                 effort,
                 summary,
+                budget: None,
             })
             .await;
 
@@ -752,6 +757,27 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
+        EventMsg::McpToolApprovalRequest(McpToolApprovalRequestEvent {
+            call_id,
+            invocation,
+            reason,
+        }) => {
+            let params = McpToolApprovalParams {
+                conversation_id,
+                call_id,
+                server: invocation.server,
+                tool: invocation.tool,
+                arguments: invocation.arguments,
+                reason,
+            };
+            let value = serde_json::to_value(&params).unwrap_or_default();
+            let rx = outgoing
+                .send_request(MCP_TOOL_APPROVAL_METHOD, Some(value))
+                .await;
+            tokio::spawn(async move {
+                on_mcp_tool_approval_response(event_id, rx, conversation).await;
+            });
+        }
         // If this is a TurnAborted, reply to any pending interrupt requests.
         EventMsg::TurnAborted(turn_aborted_event) => {
             let pending = {
@@ -890,3 +916,34 @@ async fn on_exec_approval_response(
         error!("failed to submit ExecApproval: {err}");
     }
 }
+
+async fn on_mcp_tool_approval_response(
+    event_id: String,
+    receiver: tokio::sync::oneshot::Receiver<mcp_types::Result>,
+    conversation: Arc<CodexConversation>,
+) {
+    // If the request fails or the response cannot be understood, deny the
+    // call to be conservative.
+    let decision = match receiver.await {
+        Ok(value) => serde_json::from_value::<McpToolApprovalResponse>(value)
+            .map(|response| response.decision)
+            .unwrap_or_else(|err| {
+                error!("failed to deserialize McpToolApprovalResponse: {err}");
+                ReviewDecision::Denied
+            }),
+        Err(err) => {
+            error!("request failed: {err:?}");
+            ReviewDecision::Denied
+        }
+    };
+
+    if let Err(err) = conversation
+        .submit(Op::McpToolApproval {
+            id: event_id,
+            decision,
+        })
+        .await
+    {
+        error!("failed to submit McpToolApproval: {err}");
+    }
+}
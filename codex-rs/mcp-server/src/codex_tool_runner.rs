@@ -15,6 +15,7 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::McpToolApprovalRequestEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
@@ -27,6 +28,7 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::exec_approval::handle_exec_approval_request;
+use crate::mcp_tool_approval::handle_mcp_tool_approval_request;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotificationMeta;
 use crate::patch_approval::handle_patch_approval_request;
@@ -221,6 +223,24 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
+                    EventMsg::McpToolApprovalRequest(McpToolApprovalRequestEvent {
+                        call_id,
+                        invocation,
+                        reason,
+                    }) => {
+                        handle_mcp_tool_approval_request(
+                            call_id,
+                            reason,
+                            invocation,
+                            outgoing.clone(),
+                            codex.clone(),
+                            request_id.clone(),
+                            request_id_str.clone(),
+                            event.id.clone(),
+                        )
+                        .await;
+                        continue;
+                    }
                     EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
                         let text = match last_agent_message {
                             Some(msg) => msg.clone(),
@@ -259,17 +279,27 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentReasoningRawContentDelta(_)
                     | EventMsg::TaskStarted(_)
                     | EventMsg::TokenCount(_)
+                    | EventMsg::SessionUsage(_)
+                    | EventMsg::ExecCommandRejected(_)
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::McpListToolsResponse(_)
+                    | EventMsg::McpListResourcesResponse(_)
+                    | EventMsg::McpListPromptsResponse(_)
+                    | EventMsg::McpServerStatus(_)
+                    | EventMsg::McpToolsChanged(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::ListApprovalGrantsResponse(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::StreamError(_)
+                    | EventMsg::ContextCompacted(_)
+                    | EventMsg::SecretRedacted(_)
+                    | EventMsg::TurnUndone(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
                     | EventMsg::TurnDiff(_)
@@ -277,6 +307,7 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::WebSearchEnd(_)
                     | EventMsg::GetHistoryEntryResponse(_)
                     | EventMsg::PlanUpdate(_)
+                    | EventMsg::Finding(_)
                     | EventMsg::TurnAborted(_)
                     | EventMsg::ConversationHistory(_)
                     | EventMsg::ShutdownComplete => {
@@ -26,6 +26,7 @@ mod codex_tool_runner;
 mod error_code;
 mod exec_approval;
 mod json_to_toml;
+mod mcp_tool_approval;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
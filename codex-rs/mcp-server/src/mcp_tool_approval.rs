@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::McpInvocation;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::codex_tool_runner::INVALID_PARAMS_ERROR_CODE;
+use crate::outgoing_message::OutgoingMessageSender;
+
+#[derive(Debug, Serialize)]
+pub struct McpToolApprovalElicitRequestParams {
+    pub message: String,
+    #[serde(rename = "requestedSchema")]
+    pub requested_schema: ElicitRequestParamsRequestedSchema,
+    pub codex_elicitation: String,
+    pub codex_mcp_tool_call_id: String,
+    pub codex_event_id: String,
+    pub codex_call_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codex_reason: Option<String>,
+    pub codex_server: String,
+    pub codex_tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codex_arguments: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct McpToolApprovalResponse {
+    pub decision: ReviewDecision,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn handle_mcp_tool_approval_request(
+    call_id: String,
+    reason: Option<String>,
+    invocation: McpInvocation,
+    outgoing: Arc<OutgoingMessageSender>,
+    codex: Arc<CodexConversation>,
+    request_id: RequestId,
+    tool_call_id: String,
+    event_id: String,
+) {
+    let McpInvocation {
+        server,
+        tool,
+        arguments,
+    } = invocation;
+    let mut message_lines = Vec::new();
+    if let Some(r) = &reason {
+        message_lines.push(r.clone());
+    }
+    message_lines.push(format!(
+        "Allow Codex to call the tool `{tool}` of the MCP server `{server}`?"
+    ));
+
+    let params = McpToolApprovalElicitRequestParams {
+        message: message_lines.join("\n"),
+        requested_schema: ElicitRequestParamsRequestedSchema {
+            r#type: "object".to_string(),
+            properties: json!({}),
+            required: None,
+        },
+        codex_elicitation: "mcp-tool-approval".to_string(),
+        codex_mcp_tool_call_id: tool_call_id.clone(),
+        codex_event_id: event_id.clone(),
+        codex_call_id: call_id,
+        codex_reason: reason,
+        codex_server: server,
+        codex_tool: tool,
+        codex_arguments: arguments,
+    };
+    let params_json = match serde_json::to_value(&params) {
+        Ok(value) => value,
+        Err(err) => {
+            let message = format!("Failed to serialize McpToolApprovalElicitRequestParams: {err}");
+            error!("{message}");
+
+            outgoing
+                .send_error(
+                    request_id.clone(),
+                    JSONRPCErrorError {
+                        code: INVALID_PARAMS_ERROR_CODE,
+                        message,
+                        data: None,
+                    },
+                )
+                .await;
+
+            return;
+        }
+    };
+
+    let on_response = outgoing
+        .send_request(ElicitRequest::METHOD, Some(params_json))
+        .await;
+
+    // Listen for the response on a separate task so we don't block the main agent loop.
+    tokio::spawn(async move {
+        on_mcp_tool_approval_response(event_id, on_response, codex).await;
+    });
+}
+
+async fn on_mcp_tool_approval_response(
+    event_id: String,
+    receiver: tokio::sync::oneshot::Receiver<mcp_types::Result>,
+    codex: Arc<CodexConversation>,
+) {
+    // If the request fails or the response cannot be understood, deny the
+    // call to be conservative.
+    let decision = match receiver.await {
+        Ok(value) => serde_json::from_value::<McpToolApprovalResponse>(value)
+            .map(|response| response.decision)
+            .unwrap_or_else(|err| {
+                error!("failed to deserialize McpToolApprovalResponse: {err}");
+                ReviewDecision::Denied
+            }),
+        Err(err) => {
+            error!("request failed: {err:?}");
+            ReviewDecision::Denied
+        }
+    };
+
+    if let Err(err) = codex
+        .submit(Op::McpToolApproval {
+            id: event_id,
+            decision,
+        })
+        .await
+    {
+        error!("failed to submit McpToolApproval: {err}");
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use serde::Serialize;
+use strum_macros::Display;
+
+/// Severity of a reported finding, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum FindingSeverity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Arguments of the `report_finding` tool: a single issue the agent found in
+/// the code it is reviewing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Finding {
+    /// Stable identifier of the kind of issue, e.g. `sql-injection`.
+    pub rule_id: String,
+    pub severity: FindingSeverity,
+    /// CWE identifier such as `CWE-89`, when one applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwe: Option<String>,
+    /// File the finding is in, relative to the session's working directory.
+    pub path: String,
+    /// First line (1-based) of the affected range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Last line (1-based, inclusive) of the affected range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
@@ -1,5 +1,6 @@
 pub mod config_types;
 pub mod custom_prompts;
+pub mod finding_tool;
 pub mod mcp_protocol;
 pub mod message_history;
 pub mod models;
@@ -323,6 +323,7 @@ pub enum InputItem {
 
 pub const APPLY_PATCH_APPROVAL_METHOD: &str = "applyPatchApproval";
 pub const EXEC_COMMAND_APPROVAL_METHOD: &str = "execCommandApproval";
+pub const MCP_TOOL_APPROVAL_METHOD: &str = "mcpToolApproval";
 
 /// Request initiated from the server and sent to the client.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -340,6 +341,12 @@ pub enum ServerRequest {
         request_id: RequestId,
         params: ExecCommandApprovalParams,
     },
+    /// Request to call a tool of an MCP server.
+    McpToolApproval {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: McpToolApprovalParams,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -370,6 +377,21 @@ pub struct ExecCommandApprovalParams {
     pub reason: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpToolApprovalParams {
+    pub conversation_id: ConversationId,
+    /// Use to correlate this with [codex_core::protocol::McpToolCallBeginEvent]
+    /// and [codex_core::protocol::McpToolCallEndEvent].
+    pub call_id: String,
+    /// Name of the MCP server as defined in the config.
+    pub server: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 pub struct ExecCommandApprovalResponse {
     pub decision: ReviewDecision,
@@ -380,6 +402,11 @@ pub struct ApplyPatchApprovalResponse {
     pub decision: ReviewDecision,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpToolApprovalResponse {
+    pub decision: ReviewDecision,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginChatGptCompleteNotification {
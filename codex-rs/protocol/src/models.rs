@@ -230,6 +230,16 @@ impl From<Vec<InputItem>> for ResponseInputItem {
                             None
                         }
                     },
+                    // Resolved into text and images by codex-core before the
+                    // input reaches this point.
+                    InputItem::McpResource { server, uri } => {
+                        tracing::warn!("Skipping unresolved MCP resource {server}:{uri}");
+                        None
+                    }
+                    InputItem::McpPrompt { server, name, .. } => {
+                        tracing::warn!("Skipping unresolved MCP prompt {server}:{name}");
+                        None
+                    }
                 })
                 .collect::<Vec<ContentItem>>(),
         }
@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -12,6 +13,8 @@ use std::time::Duration;
 
 use crate::custom_prompts::CustomPrompt;
 use mcp_types::CallToolResult;
+use mcp_types::Prompt as McpPrompt;
+use mcp_types::Resource as McpResource;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -22,6 +25,7 @@ use uuid::Uuid;
 
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::finding_tool::Finding;
 use crate::message_history::HistoryEntry;
 use crate::models::ResponseItem;
 use crate::parse_command::ParsedCommand;
@@ -77,6 +81,11 @@ pub enum Op {
 
         /// Will only be honored if the model is configured to use reasoning.
         summary: ReasoningSummaryConfig,
+
+        /// Limits for this task. Unset limits fall back to the configured
+        /// `task_budget`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        budget: Option<TaskBudget>,
     },
 
     /// Override parts of the persistent turn context for subsequent turns.
@@ -127,6 +136,21 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve a call to an MCP tool. `ApprovedForSession` approves every
+    /// further call to the same tool; the other approvals cover this call
+    /// only.
+    McpToolApproval {
+        /// The id of the submission we are approving
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
+    /// Trust the changed tool definitions of an MCP server reported by
+    /// `EventMsg::McpToolsChanged`: they are pinned and the tools are offered
+    /// to the model again.
+    TrustMcpTools { server: String },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -147,13 +171,32 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
+    /// Request the resources offered by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListResourcesResponse`.
+    ListMcpResources,
+
+    /// Request the prompts offered by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListPromptsResponse`.
+    ListMcpPrompts,
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Request the approvals granted for the rest of the session that are
+    /// still active.
+    /// Reply is delivered via `EventMsg::ListApprovalGrantsResponse`.
+    ListApprovalGrants,
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
     Compact,
+
+    /// Undo the last `turns` agent turns: restore the files they edited with
+    /// `apply_patch` and remove them from the conversation history.
+    /// Reply is delivered via `EventMsg::TurnUndone`.
+    UndoTurn { turns: u32 },
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -195,9 +238,14 @@ pub enum SandboxPolicy {
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
 
-    /// Read-only access to the entire file-system.
+    /// Read-only access to the entire file-system, except for `deny_read`.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// Paths (and everything beneath them) that must not be readable from
+        /// within the sandbox. Defaults to [`default_deny_read_paths`].
+        #[serde(default = "default_deny_read_paths")]
+        deny_read: Vec<PathBuf>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -223,9 +271,33 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// Paths (and everything beneath them) that must not be readable from
+        /// within the sandbox. Defaults to [`default_deny_read_paths`].
+        #[serde(default = "default_deny_read_paths")]
+        deny_read: Vec<PathBuf>,
     },
 }
 
+/// Credential stores that sandboxed commands should never be able to read:
+/// SSH, AWS and GnuPG keys and Codex's own `auth.json`. Paths are absolute;
+/// if the home directory cannot be determined the list is empty.
+pub fn default_deny_read_paths() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let codex_home = match std::env::var_os("CODEX_HOME") {
+        Some(codex_home) if !codex_home.is_empty() => PathBuf::from(codex_home),
+        _ => home.join(".codex"),
+    };
+    vec![
+        home.join(".ssh"),
+        home.join(".aws"),
+        home.join(".gnupg"),
+        codex_home.join("auth.json"),
+    ]
+}
+
 /// A writable root path accompanied by a list of subpaths that should remain
 /// read‑only even when the root is writable. This is primarily used to ensure
 /// top‑level VCS metadata directories (e.g. `.git`) under a writable root are
@@ -268,7 +340,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            deny_read: default_deny_read_paths(),
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -280,18 +354,28 @@ impl SandboxPolicy {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            deny_read: default_deny_read_paths(),
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Returns `true` unless some paths are denied for reading.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        self.get_deny_read_paths().is_empty()
+    }
+
+    /// Paths that must not be readable from within the sandbox.
+    pub fn get_deny_read_paths(&self) -> &[PathBuf] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly { deny_read } => deny_read,
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => deny_read,
+        }
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -299,7 +383,7 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
@@ -310,12 +394,13 @@ impl SandboxPolicy {
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                deny_read: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -385,6 +470,22 @@ pub enum InputItem {
     LocalImage {
         path: std::path::PathBuf,
     },
+
+    /// Resource `uri` of the MCP server `server`. Its contents are read and
+    /// attached to the turn in place of this item.
+    McpResource {
+        server: String,
+        uri: String,
+    },
+
+    /// Prompt `name` of the MCP server `server`. The messages it expands to
+    /// with `arguments` replace this item.
+    McpPrompt {
+        server: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        arguments: HashMap<String, String>,
+    },
 }
 
 /// Event Queue Entry - events from agent
@@ -414,6 +515,10 @@ pub enum EventMsg {
     /// used in the current session.
     TokenCount(TokenUsage),
 
+    /// Running token and cost totals for the session, sent after every
+    /// [`EventMsg::TokenCount`].
+    SessionUsage(SessionUsageEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -455,14 +560,34 @@ pub enum EventMsg {
 
     ExecApprovalRequest(ExecApprovalRequestEvent),
 
+    /// Notification that a command was refused without running it, e.g.
+    /// because it matched a deny rule in the exec policy.
+    ExecCommandRejected(ExecCommandRejectedEvent),
+
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// The agent wants to call an MCP tool that the configuration requires
+    /// the user to approve. Answered with `Op::McpToolApproval`.
+    McpToolApprovalRequest(McpToolApprovalRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
     /// and the system is handling it (e.g., retrying with backoff).
     StreamError(StreamErrorEvent),
 
+    /// Notification that older conversation history was replaced by a summary
+    /// because the context window was close to full.
+    ContextCompacted(ContextCompactedEvent),
+
+    /// Notification that secrets were masked in a tool output or user message
+    /// before it was recorded in the conversation history.
+    SecretRedacted(SecretRedactedEvent),
+
+    /// Notification that agent turns were undone in response to
+    /// `Op::UndoTurn`.
+    TurnUndone(TurnUndoneEvent),
+
     /// Notification that the agent is about to apply a code patch. Mirrors
     /// `ExecCommandBegin` so front‑ends can show progress indicators.
     PatchApplyBegin(PatchApplyBeginEvent),
@@ -478,11 +603,34 @@ pub enum EventMsg {
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
+    /// Resources offered by the MCP servers, sent in response to
+    /// `Op::ListMcpResources` and whenever a server reports that its list
+    /// changed.
+    McpListResourcesResponse(McpListResourcesResponseEvent),
+
+    /// Prompts offered by the MCP servers, sent in response to
+    /// `Op::ListMcpPrompts` and whenever a server reports that its list
+    /// changed.
+    McpListPromptsResponse(McpListPromptsResponseEvent),
+
+    /// An MCP server crashed, is being restarted or is running again.
+    McpServerStatus(McpServerStatusEvent),
+
+    /// Tools of an MCP server no longer match their pinned definitions and
+    /// are withheld from the model until trusted with `Op::TrustMcpTools`.
+    McpToolsChanged(McpToolsChangedEvent),
+
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Approvals granted in this session that are still active.
+    ListApprovalGrantsResponse(ListApprovalGrantsResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
+    /// A finding the agent reported with the `report_finding` tool.
+    Finding(FindingEvent),
+
     TurnAborted(TurnAbortedEvent),
 
     /// Notification that the agent is shutting down.
@@ -572,6 +720,78 @@ impl TokenUsage {
     }
 }
 
+/// Tokens one model used over a session, and what they cost.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct ModelUsage {
+    /// Model requests that reported usage.
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_output_tokens: u64,
+    /// Cost in US dollars, or `None` when no price is known for the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl ModelUsage {
+    /// Adds `other` to these totals. Costs are summed over the usage whose
+    /// price is known.
+    pub fn add(&mut self, other: &ModelUsage) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_output_tokens += other.reasoning_output_tokens;
+        self.cost_usd = match (self.cost_usd, other.cost_usd) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// Running token and cost totals for a session, per model slug.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct SessionUsage {
+    pub models: BTreeMap<String, ModelUsage>,
+}
+
+impl SessionUsage {
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Adds one model request; `cost_usd` is its cost when the model's price
+    /// is known.
+    pub fn add_request(&mut self, model: &str, usage: &TokenUsage, cost_usd: Option<f64>) {
+        self.models
+            .entry(model.to_string())
+            .or_default()
+            .add(&ModelUsage {
+                requests: 1,
+                input_tokens: usage.input_tokens,
+                cached_input_tokens: usage.cached_input(),
+                output_tokens: usage.output_tokens,
+                reasoning_output_tokens: usage.reasoning_output_tokens.unwrap_or(0),
+                cost_usd,
+            });
+    }
+
+    /// Totals over every model.
+    pub fn total(&self) -> ModelUsage {
+        let mut total = ModelUsage::default();
+        for usage in self.models.values() {
+            total.add(usage);
+        }
+        total
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionUsageEvent {
+    pub usage: SessionUsage,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FinalOutput {
     pub token_usage: TokenUsage,
@@ -754,6 +974,16 @@ pub struct ExecApprovalRequestEvent {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExecCommandRejectedEvent {
+    /// Identifier for the refused exec call.
+    pub call_id: String,
+    /// The command that was refused.
+    pub command: Vec<String>,
+    /// Why the command was refused; also reported to the model.
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ApplyPatchApprovalRequestEvent {
     /// Responses API call id for the associated patch apply call, if available.
@@ -767,6 +997,17 @@ pub struct ApplyPatchApprovalRequestEvent {
     pub grant_root: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpToolApprovalRequestEvent {
+    /// Identifier for the associated MCP tool call.
+    pub call_id: String,
+    /// The tool and the arguments it would be called with.
+    pub invocation: McpInvocation,
+    /// Optional human-readable reason for the approval.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackgroundEventEvent {
     pub message: String,
@@ -777,6 +1018,46 @@ pub struct StreamErrorEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContextCompactedEvent {
+    /// Tokens in the context window when compaction was triggered.
+    pub tokens_before: u64,
+    /// Size of the model's context window, in tokens.
+    pub model_context_window: u64,
+    /// Number of recent turns that were kept verbatim.
+    pub kept_turns: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SecretRedactedEvent {
+    /// Tool call whose output was redacted; `None` for user input.
+    pub call_id: Option<String>,
+    /// Number of secrets masked, per detector. Never includes the secrets.
+    pub redactions: Vec<RedactionCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RedactionCount {
+    /// Detector that matched, e.g. `aws_access_key_id` or `custom_pattern`.
+    pub kind: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TurnUndoneEvent {
+    /// Number of turns that were undone.
+    pub turns: u32,
+    /// Files restored to their state before the undone turns.
+    pub restored_paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FindingEvent {
+    /// 1-based position of this finding among those reported in the session.
+    pub number: usize,
+    pub finding: Finding,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PatchApplyBeginEvent {
     /// Identifier so this can be paired with the PatchApplyEnd event.
@@ -818,6 +1099,70 @@ pub struct GetHistoryEntryResponseEvent {
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
+
+    /// Server name -> health of that server.
+    #[serde(default)]
+    pub servers: std::collections::HashMap<String, McpServerStatus>,
+}
+
+/// Health of a configured MCP server.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum McpServerStatus {
+    Running,
+    /// The server exited and is being started again.
+    Restarting {
+        attempt: u32,
+    },
+    /// The server could not be started, or was given up on after it kept
+    /// crashing. `stderr_tail` holds the last lines it wrote to stderr.
+    Failed {
+        error: String,
+        stderr_tail: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpServerStatusEvent {
+    pub server: String,
+    pub status: McpServerStatus,
+}
+
+/// The parts of an MCP tool definition that are pinned.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct McpToolDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema")]
+    pub input_schema: serde_json::Value,
+}
+
+/// A tool whose definition differs from the one pinned for it.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct McpToolChange {
+    pub pinned: McpToolDefinition,
+    pub current: McpToolDefinition,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpToolsChangedEvent {
+    pub server: String,
+    pub changes: Vec<McpToolChange>,
+}
+
+/// Response payload for `Op::ListMcpResources`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpListResourcesResponseEvent {
+    /// Server name -> resources offered by that server.
+    pub resources: std::collections::HashMap<String, Vec<McpResource>>,
+}
+
+/// Response payload for `Op::ListMcpPrompts`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpListPromptsResponseEvent {
+    /// Server name -> prompts offered by that server.
+    pub prompts: std::collections::HashMap<String, Vec<McpPrompt>>,
 }
 
 /// Response payload for `Op::ListCustomPrompts`.
@@ -826,6 +1171,34 @@ pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
 }
 
+/// Response payload for `Op::ListApprovalGrants`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListApprovalGrantsResponseEvent {
+    pub grants: Vec<ApprovalGrant>,
+}
+
+/// Commands the user approved beyond a single run.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ApprovalGrant {
+    #[serde(flatten)]
+    pub scope: ApprovalScope,
+    /// Seconds until the grant expires; `None` if it lasts for the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Which commands an [`ApprovalGrant`] covers.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum ApprovalScope {
+    /// Exactly this command.
+    Command { command: Vec<String> },
+    /// Any command whose arguments start with `prefix`.
+    Prefix { prefix: Vec<String> },
+    /// Any invocation of `program` in `cwd` or below it.
+    Program { program: String, cwd: PathBuf },
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
@@ -853,6 +1226,20 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and any future command that starts
+    /// with the same program and subcommand, for the remainder of the session.
+    /// The commands it covers still run in the sandbox.
+    ApprovedPrefixForSession,
+
+    /// User has approved this command and any future invocation of the same
+    /// program in the same directory, for the remainder of the session. The
+    /// commands it covers still run in the sandbox.
+    ApprovedProgramInDirectory,
+
+    /// User has approved future identical instances of this command for 15
+    /// minutes. They still run in the sandbox.
+    ApprovedTemporarily,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -894,6 +1281,76 @@ pub struct TurnAbortedEvent {
 pub enum TurnAbortReason {
     Interrupted,
     Replaced,
+    /// The task used up one of its [`TaskBudget`] limits.
+    BudgetExceeded(BudgetExceeded),
+}
+
+/// Limits on the work a single task may do. A task that reaches one of them
+/// is stopped with [`TurnAbortReason::BudgetExceeded`] before its next model
+/// request; limits that are not set are not enforced.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, TS)]
+pub struct TaskBudget {
+    /// Tool calls the model may make.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u64>,
+
+    /// Wall-clock time the task may run for, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+
+    /// Input tokens summed over every model request of the task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_input_tokens: Option<u64>,
+
+    /// Output tokens summed over every model request of the task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+}
+
+impl TaskBudget {
+    /// Limits set in `self`, with the unset ones taken from `fallback`.
+    pub fn or(self, fallback: TaskBudget) -> TaskBudget {
+        TaskBudget {
+            max_tool_calls: self.max_tool_calls.or(fallback.max_tool_calls),
+            max_duration_secs: self.max_duration_secs.or(fallback.max_duration_secs),
+            max_input_tokens: self.max_input_tokens.or(fallback.max_input_tokens),
+            max_output_tokens: self.max_output_tokens.or(fallback.max_output_tokens),
+        }
+    }
+}
+
+/// Which [`TaskBudget`] limit a task reached.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskBudgetKind {
+    ToolCalls,
+    Duration,
+    InputTokens,
+    OutputTokens,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+pub struct BudgetExceeded {
+    pub budget: TaskBudgetKind,
+    pub limit: u64,
+    /// Amount used when the task was stopped, in the unit of `limit`.
+    pub used: u64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (what, unit) = match self.budget {
+            TaskBudgetKind::ToolCalls => ("tool call", "calls"),
+            TaskBudgetKind::Duration => ("time", "seconds"),
+            TaskBudgetKind::InputTokens => ("input token", "tokens"),
+            TaskBudgetKind::OutputTokens => ("output token", "tokens"),
+        };
+        write!(
+            f,
+            "{what} budget exhausted ({} of {} {unit})",
+            self.used, self.limit
+        )
+    }
 }
 
 #[cfg(test)]
@@ -920,4 +1377,37 @@ mod tests {
             r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0}}"#
         );
     }
+
+    #[test]
+    fn sandbox_policy_deny_read_defaults_when_omitted() {
+        let policy: SandboxPolicy = serde_json::from_str(r#"{"mode":"read-only"}"#).unwrap();
+        assert_eq!(policy, SandboxPolicy::new_read_only_policy());
+
+        let policy: SandboxPolicy =
+            serde_json::from_str(r#"{"mode":"workspace-write","deny_read":[]}"#).unwrap();
+        assert!(policy.get_deny_read_paths().is_empty());
+        assert!(policy.has_full_disk_read_access());
+    }
+
+    #[test]
+    fn budget_exceeded_abort_carries_details() {
+        let event = TurnAbortedEvent {
+            reason: TurnAbortReason::BudgetExceeded(BudgetExceeded {
+                budget: TaskBudgetKind::ToolCalls,
+                limit: 10,
+                used: 10,
+            }),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"reason":{"budget_exceeded":{"budget":"tool_calls","limit":10,"used":10}}}"#
+        );
+        let TurnAbortReason::BudgetExceeded(exceeded) = event.reason else {
+            unreachable!()
+        };
+        assert_eq!(
+            exceeded.to_string(),
+            "tool call budget exhausted (10 of 10 calls)"
+        );
+    }
 }
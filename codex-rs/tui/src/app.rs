@@ -253,6 +253,19 @@ impl App {
                 ));
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::ShowFindings => {
+                let _ = tui.enter_alt_screen();
+                self.overlay = Some(Overlay::new_static_with_title(
+                    self.chat_widget.findings_panel_lines(),
+                    "F I N D I N G S".to_string(),
+                ));
+                tui.frame_requester().schedule_frame();
+            }
+            AppEvent::DesktopNotification(message) => {
+                if let Err(e) = tui.notify(message, self.config.tui.notification_method) {
+                    tracing::warn!("failed to raise terminal notification: {e}");
+                }
+            }
             AppEvent::StartFileSearch(query) => {
                 if !query.is_empty() {
                     self.file_search.on_user_query(query);
@@ -40,6 +40,12 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Open the panel listing the findings reported in this session.
+    ShowFindings,
+
+    /// Raise a terminal notification with the given message.
+    DesktopNotification(String),
+
     InsertHistoryLines(Vec<Line<'static>>),
     InsertHistoryCell(Box<dyn HistoryCell>),
 
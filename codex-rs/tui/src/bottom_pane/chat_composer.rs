@@ -24,6 +24,7 @@ use ratatui::widgets::WidgetRef;
 use super::chat_composer_history::ChatComposerHistory;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
+use super::command_popup::McpPromptCommand;
 use super::file_search_popup::FileSearchPopup;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
@@ -90,6 +91,8 @@ pub(crate) struct ChatComposer {
     current_file_query: Option<String>,
     pending_pastes: Vec<(String, String)>,
     token_usage_info: Option<TokenUsageInfo>,
+    /// Cost of the session so far, when the price of its models is known.
+    session_cost_usd: Option<f64>,
     has_focus: bool,
     attached_images: Vec<AttachedImage>,
     placeholder_text: String,
@@ -98,6 +101,7 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptCommand>,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -130,12 +134,14 @@ impl ChatComposer {
             current_file_query: None,
             pending_pastes: Vec::new(),
             token_usage_info: None,
+            session_cost_usd: None,
             has_focus: has_input_focus,
             attached_images: Vec::new(),
             placeholder_text,
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
@@ -194,6 +200,11 @@ impl ChatComposer {
         });
     }
 
+    /// Update the session cost shown next to the token count in the footer.
+    pub(crate) fn set_session_cost(&mut self, session_cost_usd: Option<f64>) {
+        self.session_cost_usd = session_cost_usd;
+    }
+
     /// Record the history metadata advertised by `SessionConfiguredEvent` so
     /// that the composer can navigate cross-session history.
     pub(crate) fn set_history_metadata(&mut self, log_id: u64, entry_count: usize) {
@@ -417,6 +428,15 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some(command) = popup.mcp_prompt(idx).map(|p| p.command()) {
+                                let starts_with_cmd =
+                                    first_line.trim_start().starts_with(&format!("/{command}"));
+                                if !starts_with_cmd {
+                                    self.textarea.set_text(&format!("/{command} "));
+                                }
+                            }
+                        }
                     }
                     // After completing the command, move cursor to the end.
                     if !self.textarea.text().is_empty() {
@@ -431,6 +451,28 @@ impl ChatComposer {
                 modifiers: KeyModifiers::NONE,
                 ..
             } => {
+                if let Some(CommandItem::McpPrompt(idx)) = popup.selected_item() {
+                    let Some(command) = popup.mcp_prompt(idx).cloned() else {
+                        return (InputResult::None, true);
+                    };
+                    // The prompt is expanded by the chat widget. Submit it
+                    // once the command is complete, asking for arguments
+                    // first if the prompt takes any.
+                    let text = self.textarea.text().trim().to_string();
+                    let invocation = format!("/{}", command.command());
+                    let typed_command = text.split_whitespace().next() == Some(invocation.as_str());
+                    self.active_popup = ActivePopup::None;
+                    if typed_command || !command.takes_arguments() {
+                        self.textarea.set_text("");
+                        let text = if typed_command { text } else { invocation };
+                        self.history.record_local_submission(&text);
+                        return (InputResult::Submitted(text), true);
+                    }
+                    self.textarea.set_text(&format!("{invocation} "));
+                    let end = self.textarea.text().len();
+                    self.textarea.set_cursor(end);
+                    return (InputResult::None, true);
+                }
                 if let Some(sel) = popup.selected_item() {
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
@@ -454,6 +496,7 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(_) => return (InputResult::None, true),
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -1148,6 +1191,7 @@ impl ChatComposer {
             _ => {
                 if input_starts_with_slash {
                     let mut command_popup = CommandPopup::new(self.custom_prompts.clone());
+                    command_popup.set_mcp_prompts(self.mcp_prompts.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1155,6 +1199,13 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptCommand>) {
+        self.mcp_prompts = prompts.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(prompts);
+        }
+    }
+
     pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
         self.custom_prompts = prompts.clone();
         if let ActivePopup::Command(popup) = &mut self.active_popup {
@@ -1275,6 +1326,13 @@ impl WidgetRef for ChatComposer {
                         Span::from(format!("{} tokens used", token_usage.blended_total()))
                             .style(Style::default().add_modifier(Modifier::DIM)),
                     );
+                    if let Some(cost) = self.session_cost_usd {
+                        hint.push(Span::from("   "));
+                        hint.push(
+                            Span::from(format!("${cost:.2} spent"))
+                                .style(Style::default().add_modifier(Modifier::DIM)),
+                        );
+                    }
                     let last_token_usage = &token_usage_info.last_token_usage;
                     if let Some(context_window) = token_usage_info.model_context_window {
                         let percent_remaining: u8 = if context_window > 0 {
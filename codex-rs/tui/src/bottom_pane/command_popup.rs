@@ -10,20 +10,46 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use codex_common::fuzzy_match::fuzzy_match;
 use codex_protocol::custom_prompts::CustomPrompt;
+use mcp_types::Prompt;
 use std::collections::HashSet;
 
-/// A selectable item in the popup: either a built-in command or a user prompt.
+/// A selectable item in the popup: a built-in command, a user prompt or a
+/// prompt of an MCP server.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CommandItem {
     Builtin(SlashCommand),
     // Index into `prompts`
     UserPrompt(usize),
+    // Index into `mcp_prompts`
+    McpPrompt(usize),
+}
+
+/// A prompt offered by an MCP server, invoked as `/<server>:<prompt>`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct McpPromptCommand {
+    pub server: String,
+    pub prompt: Prompt,
+}
+
+impl McpPromptCommand {
+    /// The command without the leading `/`.
+    pub(crate) fn command(&self) -> String {
+        format!("{}:{}", self.server, self.prompt.name)
+    }
+
+    pub(crate) fn takes_arguments(&self) -> bool {
+        self.prompt
+            .arguments
+            .as_ref()
+            .is_some_and(|arguments| !arguments.is_empty())
+    }
 }
 
 pub(crate) struct CommandPopup {
     command_filter: String,
     builtins: Vec<(&'static str, SlashCommand)>,
     prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptCommand>,
     state: ScrollState,
 }
 
@@ -38,6 +64,7 @@ impl CommandPopup {
             command_filter: String::new(),
             builtins,
             prompts,
+            mcp_prompts: Vec::new(),
             state: ScrollState::new(),
         }
     }
@@ -53,6 +80,15 @@ impl CommandPopup {
         self.prompts = prompts;
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, mut prompts: Vec<McpPromptCommand>) {
+        prompts.sort_by_key(McpPromptCommand::command);
+        self.mcp_prompts = prompts;
+    }
+
+    pub(crate) fn mcp_prompt(&self, idx: usize) -> Option<&McpPromptCommand> {
+        self.mcp_prompts.get(idx)
+    }
+
     pub(crate) fn prompt_name(&self, idx: usize) -> Option<&str> {
         self.prompts.get(idx).map(|p| p.name.as_str())
     }
@@ -98,8 +134,8 @@ impl CommandPopup {
         self.filtered_items().len().clamp(1, MAX_POPUP_ROWS) as u16
     }
 
-    /// Compute fuzzy-filtered matches over built-in commands and user prompts,
-    /// paired with optional highlight indices and score. Sorted by ascending
+    /// Compute fuzzy-filtered matches over built-in commands, user prompts and
+    /// MCP prompts, paired with optional highlight indices and score. Sorted by ascending
     /// score, then by name for stability.
     fn filtered(&self) -> Vec<(CommandItem, Option<Vec<usize>>, i32)> {
         let filter = self.command_filter.trim();
@@ -113,6 +149,9 @@ impl CommandPopup {
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            for idx in 0..self.mcp_prompts.len() {
+                out.push((CommandItem::McpPrompt(idx), None, 0));
+            }
             return out;
         }
 
@@ -126,23 +165,27 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, p) in self.mcp_prompts.iter().enumerate() {
+            if let Some((indices, score)) = fuzzy_match(&p.command(), filter) {
+                out.push((CommandItem::McpPrompt(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score and then by name for stability.
         out.sort_by(|a, b| {
-            a.2.cmp(&b.2).then_with(|| {
-                let an = match a.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
-                let bn = match b.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
-                an.cmp(bn)
-            })
+            a.2.cmp(&b.2)
+                .then_with(|| self.item_name(a.0).cmp(&self.item_name(b.0)))
         });
         out
     }
 
+    fn item_name(&self, item: CommandItem) -> String {
+        match item {
+            CommandItem::Builtin(c) => c.command().to_string(),
+            CommandItem::UserPrompt(i) => self.prompts[i].name.clone(),
+            CommandItem::McpPrompt(i) => self.mcp_prompts[i].command(),
+        }
+    }
+
     fn filtered_items(&self) -> Vec<CommandItem> {
         self.filtered().into_iter().map(|(c, _, _)| c).collect()
     }
@@ -192,6 +235,17 @@ impl WidgetRef for CommandPopup {
                         is_current: false,
                         description: Some("send saved prompt".to_string()),
                     },
+                    CommandItem::McpPrompt(i) => {
+                        let command = &self.mcp_prompts[i];
+                        GenericDisplayRow {
+                            name: format!("/{}", command.command()),
+                            match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
+                            is_current: false,
+                            description: Some(command.prompt.description.clone().unwrap_or_else(
+                                || format!("prompt from MCP server {}", command.server),
+                            )),
+                        }
+                    }
                 })
                 .collect()
         };
@@ -215,7 +269,7 @@ mod tests {
         let matches = popup.filtered_items();
         let has_init = matches.iter().any(|item| match item {
             CommandItem::Builtin(cmd) => cmd.command() == "init",
-            CommandItem::UserPrompt(_) => false,
+            CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_) => false,
         });
         assert!(
             has_init,
@@ -233,7 +287,9 @@ mod tests {
         let selected = popup.selected_item();
         match selected {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "init"),
-            Some(CommandItem::UserPrompt(_)) => panic!("unexpected prompt selected for '/init'"),
+            Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
+                panic!("unexpected prompt selected for '/init'")
+            }
             None => panic!("expected a selected command for exact match"),
         }
     }
@@ -283,4 +339,27 @@ mod tests {
             "prompt with builtin name should be ignored"
         );
     }
+
+    #[test]
+    fn mcp_prompts_are_listed_as_server_commands() {
+        let mut popup = CommandPopup::new(Vec::new());
+        popup.set_mcp_prompts(vec![McpPromptCommand {
+            server: "github".to_string(),
+            prompt: Prompt {
+                arguments: None,
+                description: Some("Summarize open pull requests".to_string()),
+                name: "pr_summary".to_string(),
+                title: None,
+            },
+        }]);
+        popup.on_composer_text_change("/github:pr".to_string());
+
+        match popup.selected_item() {
+            Some(CommandItem::McpPrompt(idx)) => {
+                let command = popup.mcp_prompt(idx).map(McpPromptCommand::command);
+                assert_eq!(command.as_deref(), Some("github:pr_summary"));
+            }
+            other => panic!("expected the MCP prompt to be selected, got {other:?}"),
+        }
+    }
 }
@@ -37,6 +37,7 @@ pub(crate) enum CancellationEvent {
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 use codex_protocol::custom_prompts::CustomPrompt;
+pub(crate) use command_popup::McpPromptCommand;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 use approval_modal_view::ApprovalModalView;
@@ -343,6 +344,11 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptCommand>) {
+        self.composer.set_mcp_prompts(prompts);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
@@ -371,6 +377,12 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update the session cost shown in the composer footer.
+    pub(crate) fn set_session_cost(&mut self, session_cost_usd: Option<f64>) {
+        self.composer.set_session_cost(session_cost_usd);
+        self.request_redraw();
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(&mut self, request: ApprovalRequest) {
         let request = if let Some(view) = self.active_view.as_mut() {
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::config::Config;
+use codex_core::config_types::NotificationKind;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
@@ -12,24 +13,37 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::ContextCompactedEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandRejectedEvent;
+use codex_core::protocol::FindingEvent;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::ListApprovalGrantsResponseEvent;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
+use codex_core::protocol::McpListPromptsResponseEvent;
+use codex_core::protocol::McpListResourcesResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpServerStatusEvent;
+use codex_core::protocol::McpToolApprovalRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolsChangedEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::SecretRedactedEvent;
+use codex_core::protocol::SessionUsage;
+use codex_core::protocol::SessionUsageEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnUndoneEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::parse_command::ParsedCommand;
@@ -42,6 +56,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
 use ratatui::layout::Rect;
+use ratatui::text::Line;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
 use tokio::sync::mpsc::UnboundedSender;
@@ -53,8 +68,10 @@ use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::InputResult;
+use crate::bottom_pane::McpPromptCommand;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
+use crate::exec_command::strip_bash_lc_and_escape;
 use crate::get_git_diff::get_git_diff;
 use crate::history_cell;
 use crate::history_cell::CommandOutput;
@@ -62,6 +79,7 @@ use crate::history_cell::ExecCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
 use crate::slash_command::SlashCommand;
+use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
 // streaming internals are provided by crate::streaming and crate::markdown_stream
 use crate::user_approval_widget::ApprovalRequest;
@@ -70,6 +88,9 @@ use self::interrupts::InterruptManager;
 mod agent;
 use self::agent::spawn_agent;
 use self::agent::spawn_agent_from_existing;
+mod mcp_input;
+use self::mcp_input::mcp_prompt_item;
+use self::mcp_input::mcp_resource_items;
 use crate::streaming::controller::AppEventHistorySink;
 use crate::streaming::controller::StreamController;
 use codex_common::approval_presets::ApprovalPreset;
@@ -83,6 +104,10 @@ use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_file_search::FileMatch;
 use uuid::Uuid;
 
+/// Longest message shown in a terminal notification; the full text, e.g. a
+/// long final answer, is in the transcript.
+const NOTIFICATION_MAX_GRAPHEMES: usize = 200;
+
 // Track information about an in-flight exec command.
 struct RunningCommand {
     command: Vec<String>,
@@ -97,6 +122,8 @@ pub(crate) struct ChatWidget {
     config: Config,
     initial_user_message: Option<UserMessage>,
     total_token_usage: TokenUsage,
+    /// Token and cost totals reported by the session.
+    session_usage: SessionUsage,
     last_token_usage: TokenUsage,
     // Stream lifecycle controller
     stream: StreamController,
@@ -116,6 +143,11 @@ pub(crate) struct ChatWidget {
     last_history_was_exec: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Findings reported by the agent in this session, shown by `/findings`
+    findings: Vec<FindingEvent>,
+    // Resources and prompts of the MCP servers, keyed by server name
+    mcp_resources: HashMap<String, Vec<mcp_types::Resource>>,
+    mcp_prompts: HashMap<String, Vec<mcp_types::Prompt>>,
 }
 
 struct UserMessage {
@@ -155,8 +187,13 @@ impl ChatWidget {
             event,
             self.show_welcome_banner,
         ));
-        // Ask codex-core to enumerate custom prompts for this session.
+        // Ask codex-core to enumerate custom prompts and the resources and
+        // prompts of MCP servers for this session.
         self.submit_op(Op::ListCustomPrompts);
+        if !self.config.mcp_servers.is_empty() {
+            self.submit_op(Op::ListMcpResources);
+            self.submit_op(Op::ListMcpPrompts);
+        }
         if let Some(user_message) = self.initial_user_message.take() {
             self.submit_user_message(user_message);
         }
@@ -213,6 +250,10 @@ impl ChatWidget {
     // Raw reasoning uses the same flow as summarized reasoning
 
     fn on_task_started(&mut self) {
+        self.notify(
+            NotificationKind::TaskStarted,
+            "Codex started working".to_string(),
+        );
         self.bottom_pane.clear_ctrl_c_quit_hint();
         self.bottom_pane.set_task_running(true);
         self.stream.reset_headers_for_new_turn();
@@ -221,7 +262,11 @@ impl ChatWidget {
         self.request_redraw();
     }
 
-    fn on_task_complete(&mut self) {
+    fn on_task_complete(&mut self, last_agent_message: Option<String>) {
+        self.notify(
+            NotificationKind::AgentTurnComplete,
+            last_agent_message.unwrap_or_else(|| "Codex finished its turn".to_string()),
+        );
         // If a stream is currently active, finalize only that stream to flush any tail
         // without emitting stray headers for other streams.
         if self.stream.is_write_cycle_active() {
@@ -247,6 +292,11 @@ impl ChatWidget {
         );
     }
 
+    fn on_session_usage(&mut self, usage: SessionUsage) {
+        self.bottom_pane.set_session_cost(usage.total().cost_usd);
+        self.session_usage = usage;
+    }
+
     /// Finalize any active exec as failed, push an error message into history,
     /// and stop/clear running UI state.
     fn finalize_turn_with_error_message(&mut self, message: String) {
@@ -261,6 +311,14 @@ impl ChatWidget {
     }
 
     fn on_error(&mut self, message: String) {
+        self.notify(
+            NotificationKind::TaskError,
+            format!("Codex error: {message}"),
+        );
+        self.end_turn_with_error(message);
+    }
+
+    fn end_turn_with_error(&mut self, message: String) {
         self.finalize_turn_with_error_message(message);
         self.request_redraw();
 
@@ -297,6 +355,13 @@ impl ChatWidget {
     }
 
     fn on_exec_approval_request(&mut self, id: String, ev: ExecApprovalRequestEvent) {
+        self.notify(
+            NotificationKind::ApprovalRequested,
+            format!(
+                "Approval requested: {}",
+                strip_bash_lc_and_escape(&ev.command)
+            ),
+        );
         let id2 = id.clone();
         let ev2 = ev.clone();
         self.defer_or_handle(
@@ -305,7 +370,37 @@ impl ChatWidget {
         );
     }
 
+    fn on_mcp_tool_approval_request(&mut self, id: String, ev: McpToolApprovalRequestEvent) {
+        self.notify(
+            NotificationKind::ApprovalRequested,
+            format!(
+                "Approval requested: {}.{}",
+                ev.invocation.server, ev.invocation.tool
+            ),
+        );
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_mcp_tool_approval(id, ev),
+            |s| s.handle_mcp_tool_approval_now(id2, ev2),
+        );
+    }
+
+    fn on_exec_command_rejected(&mut self, ev: ExecCommandRejectedEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_exec_rejected(&ev.command, ev.reason));
+        self.request_redraw();
+    }
+
     fn on_apply_patch_approval_request(&mut self, id: String, ev: ApplyPatchApprovalRequestEvent) {
+        let files = ev.changes.len();
+        self.notify(
+            NotificationKind::ApprovalRequested,
+            format!(
+                "Approval requested: patch to {files} {}",
+                if files == 1 { "file" } else { "files" }
+            ),
+        );
         let id2 = id.clone();
         let ev2 = ev.clone();
         self.defer_or_handle(
@@ -392,15 +487,55 @@ impl ChatWidget {
         debug!("TurnDiffEvent: {unified_diff}");
     }
 
+    /// Ask the app to raise a terminal notification when `[tui]
+    /// notifications` selects `kind`. The message is put on one line and
+    /// shortened to fit a notification.
+    fn notify(&self, kind: NotificationKind, message: String) {
+        if self.config.tui.notifications.contains(&kind) {
+            let message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+            self.app_event_tx
+                .send(AppEvent::DesktopNotification(truncate_text(
+                    &message,
+                    NOTIFICATION_MAX_GRAPHEMES,
+                )));
+        }
+    }
+
     fn on_background_event(&mut self, message: String) {
         debug!("BackgroundEvent: {message}");
     }
 
     fn on_stream_error(&mut self, message: String) {
+        self.notify(NotificationKind::StreamError, message.clone());
         // Show stream errors in the transcript so users see retry/backoff info.
         self.add_to_history(history_cell::new_stream_error_event(message));
         self.request_redraw();
     }
+
+    fn on_context_compacted(&mut self, ev: ContextCompactedEvent) {
+        self.notify(
+            NotificationKind::ContextCompacted,
+            "Codex summarized the conversation to free up context".to_string(),
+        );
+        self.add_to_history(history_cell::new_context_compacted(ev));
+        self.request_redraw();
+    }
+
+    fn on_secret_redacted(&mut self, ev: SecretRedactedEvent) {
+        self.add_to_history(history_cell::new_secret_redacted(ev));
+        self.request_redraw();
+    }
+
+    fn on_turn_undone(&mut self, ev: TurnUndoneEvent) {
+        self.add_to_history(history_cell::new_turn_undone(ev));
+        self.request_redraw();
+    }
+
+    fn on_finding(&mut self, ev: FindingEvent) {
+        self.add_to_history(history_cell::new_finding(&ev));
+        self.findings.push(ev);
+        self.request_redraw();
+    }
     /// Periodic tick to commit at most one queued line to history with a small delay,
     /// animating the output.
     pub(crate) fn on_commit_tick(&mut self) {
@@ -534,6 +669,22 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    pub(crate) fn handle_mcp_tool_approval_now(
+        &mut self,
+        id: String,
+        ev: McpToolApprovalRequestEvent,
+    ) {
+        self.flush_answer_stream_with_separator();
+
+        let request = ApprovalRequest::McpTool {
+            id,
+            invocation: ev.invocation,
+            reason: ev.reason,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.running_commands.insert(
@@ -624,6 +775,7 @@ impl ChatWidget {
                 initial_images,
             ),
             total_token_usage: TokenUsage::default(),
+            session_usage: SessionUsage::default(),
             last_token_usage: TokenUsage::default(),
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
@@ -635,6 +787,9 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            findings: Vec::new(),
+            mcp_resources: HashMap::new(),
+            mcp_prompts: HashMap::new(),
             show_welcome_banner: true,
         }
     }
@@ -670,6 +825,7 @@ impl ChatWidget {
             config: config.clone(),
             initial_user_message: None,
             total_token_usage: TokenUsage::default(),
+            session_usage: SessionUsage::default(),
             last_token_usage: TokenUsage::default(),
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
@@ -681,6 +837,9 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            findings: Vec::new(),
+            mcp_resources: HashMap::new(),
+            mcp_prompts: HashMap::new(),
             show_welcome_banner: false,
         }
     }
@@ -785,10 +944,15 @@ impl ChatWidget {
                 self.clear_token_usage();
                 self.app_event_tx.send(AppEvent::CodexOp(Op::Compact));
             }
+            SlashCommand::Undo => {
+                self.app_event_tx
+                    .send(AppEvent::CodexOp(Op::UndoTurn { turns: 1 }));
+            }
             SlashCommand::Model => {
                 self.open_model_popup();
             }
             SlashCommand::Approvals => {
+                self.submit_op(Op::ListApprovalGrants);
                 self.open_approvals_popup();
             }
             SlashCommand::Quit => {
@@ -817,6 +981,9 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Findings => {
+                self.app_event_tx.send(AppEvent::ShowFindings);
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -917,8 +1084,11 @@ impl ChatWidget {
         let UserMessage { text, image_paths } = user_message;
         let mut items: Vec<InputItem> = Vec::new();
 
-        if !text.is_empty() {
+        if let Some(prompt) = mcp_prompt_item(&text, &self.mcp_prompts) {
+            items.push(prompt);
+        } else if !text.is_empty() {
             items.push(InputItem::Text { text: text.clone() });
+            items.extend(mcp_resource_items(&text, &self.mcp_resources));
         }
 
         for path in image_paths {
@@ -978,20 +1148,32 @@ impl ChatWidget {
             }
             EventMsg::AgentReasoningSectionBreak(_) => self.on_reasoning_section_break(),
             EventMsg::TaskStarted(_) => self.on_task_started(),
-            EventMsg::TaskComplete(TaskCompleteEvent { .. }) => self.on_task_complete(),
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                self.on_task_complete(last_agent_message)
+            }
             EventMsg::TokenCount(token_usage) => self.on_token_count(token_usage),
+            EventMsg::SessionUsage(SessionUsageEvent { usage }) => self.on_session_usage(usage),
             EventMsg::Error(ErrorEvent { message }) => self.on_error(message),
             EventMsg::TurnAborted(ev) => match ev.reason {
                 TurnAbortReason::Interrupted => {
                     self.on_interrupted_turn();
                 }
                 TurnAbortReason::Replaced => {
-                    self.on_error("Turn aborted: replaced by a new task".to_owned())
+                    self.end_turn_with_error("Turn aborted: replaced by a new task".to_owned())
+                }
+                TurnAbortReason::BudgetExceeded(exceeded) => {
+                    self.notify(
+                        NotificationKind::BudgetExceeded,
+                        format!("Codex stopped: {exceeded}"),
+                    );
+                    self.end_turn_with_error(format!("Task stopped: {exceeded}"));
                 }
             },
             EventMsg::PlanUpdate(update) => self.on_plan_update(update),
             EventMsg::ExecApprovalRequest(ev) => self.on_exec_approval_request(id, ev),
+            EventMsg::ExecCommandRejected(ev) => self.on_exec_command_rejected(ev),
             EventMsg::ApplyPatchApprovalRequest(ev) => self.on_apply_patch_approval_request(id, ev),
+            EventMsg::McpToolApprovalRequest(ev) => self.on_mcp_tool_approval_request(id, ev),
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
@@ -1003,13 +1185,22 @@ impl ChatWidget {
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
+            EventMsg::McpListResourcesResponse(ev) => self.on_list_mcp_resources(ev),
+            EventMsg::McpListPromptsResponse(ev) => self.on_list_mcp_prompts(ev),
+            EventMsg::McpServerStatus(ev) => self.on_mcp_server_status(ev),
+            EventMsg::McpToolsChanged(ev) => self.on_mcp_tools_changed(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::ListApprovalGrantsResponse(ev) => self.on_list_approval_grants(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
             EventMsg::StreamError(StreamErrorEvent { message }) => self.on_stream_error(message),
+            EventMsg::ContextCompacted(ev) => self.on_context_compacted(ev),
+            EventMsg::SecretRedacted(ev) => self.on_secret_redacted(ev),
+            EventMsg::TurnUndone(ev) => self.on_turn_undone(ev),
+            EventMsg::Finding(ev) => self.on_finding(ev),
             EventMsg::ConversationHistory(ev) => {
                 // Forward to App so it can process backtrack flows.
                 self.app_event_tx
@@ -1062,10 +1253,16 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// Contents of the findings panel opened by `/findings`.
+    pub(crate) fn findings_panel_lines(&self) -> Vec<Line<'static>> {
+        history_cell::findings_panel_lines(&self.findings)
+    }
+
     pub(crate) fn add_status_output(&mut self) {
         self.add_to_history(history_cell::new_status_output(
             &self.config,
             &self.total_token_usage,
+            self.session_usage.total().cost_usd,
             &self.session_id,
         ));
     }
@@ -1105,7 +1302,9 @@ impl ChatWidget {
 
         self.bottom_pane.show_selection_view(
             "Select model and reasoning level".to_string(),
-            Some("Switch between OpenAI models for this and future Nova Shield session".to_string()),
+            Some(
+                "Switch between OpenAI models for this and future Nova Shield session".to_string(),
+            ),
             Some("Press Enter to confirm or Esc to go back".to_string()),
             items,
         );
@@ -1230,7 +1429,58 @@ impl ChatWidget {
     }
 
     fn on_list_mcp_tools(&mut self, ev: McpListToolsResponseEvent) {
-        self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
+        self.add_to_history(history_cell::new_mcp_tools_output(
+            &self.config,
+            ev.tools,
+            &ev.servers,
+            &self.mcp_resources,
+            &self.mcp_prompts,
+        ));
+    }
+
+    fn on_mcp_server_status(&mut self, ev: McpServerStatusEvent) {
+        self.add_to_history(history_cell::new_mcp_server_status(&ev.server, &ev.status));
+    }
+
+    fn on_mcp_tools_changed(&mut self, ev: McpToolsChangedEvent) {
+        self.notify(
+            NotificationKind::ApprovalRequested,
+            format!("MCP server {} changed its tools", ev.server),
+        );
+        self.add_to_history(history_cell::new_mcp_tools_changed(&ev.server, &ev.changes));
+        let request = ApprovalRequest::McpToolChanges {
+            server: ev.server,
+            tools: ev
+                .changes
+                .into_iter()
+                .map(|change| change.current.name)
+                .collect(),
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
+    fn on_list_mcp_resources(&mut self, ev: McpListResourcesResponseEvent) {
+        self.mcp_resources = ev.resources;
+    }
+
+    fn on_list_mcp_prompts(&mut self, ev: McpListPromptsResponseEvent) {
+        let commands = ev
+            .prompts
+            .iter()
+            .flat_map(|(server, prompts)| {
+                prompts.iter().map(|prompt| McpPromptCommand {
+                    server: server.clone(),
+                    prompt: prompt.clone(),
+                })
+            })
+            .collect();
+        self.bottom_pane.set_mcp_prompts(commands);
+        self.mcp_prompts = ev.prompts;
+    }
+
+    fn on_list_approval_grants(&mut self, ev: ListApprovalGrantsResponseEvent) {
+        self.add_to_history(history_cell::new_approval_grants_output(&ev.grants));
     }
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
@@ -1266,6 +1516,8 @@ impl ChatWidget {
 
     pub(crate) fn clear_token_usage(&mut self) {
         self.total_token_usage = TokenUsage::default();
+        self.session_usage = SessionUsage::default();
+        self.bottom_pane.set_session_cost(None);
         self.bottom_pane.set_token_usage(
             self.total_token_usage.clone(),
             self.last_token_usage.clone(),
@@ -0,0 +1,86 @@
+//! Recognizes references to MCP prompts and resources in a submitted message.
+//!
+//! A message of the form `/<server>:<prompt> [key=value ...]` invokes a prompt
+//! of an MCP server, and every `@<server>:<uri>` mention of a known resource
+//! attaches that resource to the turn. codex-core replaces the resulting
+//! input items with the prompt messages and resource contents.
+
+use std::collections::HashMap;
+
+use codex_core::protocol::InputItem;
+use mcp_types::Prompt;
+use mcp_types::Resource;
+
+/// The prompt invoked by `text`, if it starts with `/<server>:<prompt>` naming
+/// a known prompt. `key=value` words are passed as arguments; any other words
+/// become the value of the first argument that is not otherwise set.
+pub(super) fn mcp_prompt_item(
+    text: &str,
+    prompts: &HashMap<String, Vec<Prompt>>,
+) -> Option<InputItem> {
+    let invocation = text.trim().strip_prefix('/')?;
+    let (command, rest) = invocation
+        .split_once(char::is_whitespace)
+        .unwrap_or((invocation, ""));
+    let (server, name) = command.split_once(':')?;
+    let prompt = prompts
+        .get(server)?
+        .iter()
+        .find(|prompt| prompt.name == name)?;
+
+    let words =
+        shlex::split(rest).unwrap_or_else(|| rest.split_whitespace().map(str::to_string).collect());
+    let mut arguments = HashMap::new();
+    let mut free_words = Vec::new();
+    for word in words {
+        match word.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                arguments.insert(key.to_string(), value.to_string());
+            }
+            _ => free_words.push(word),
+        }
+    }
+    if !free_words.is_empty()
+        && let Some(argument) = prompt
+            .arguments
+            .iter()
+            .flatten()
+            .find(|argument| !arguments.contains_key(&argument.name))
+    {
+        arguments.insert(argument.name.clone(), free_words.join(" "));
+    }
+
+    Some(InputItem::McpPrompt {
+        server: server.to_string(),
+        name: name.to_string(),
+        arguments,
+    })
+}
+
+/// The known resources that `text` mentions as `@<server>:<uri>`, in order of
+/// first mention.
+pub(super) fn mcp_resource_items(
+    text: &str,
+    resources: &HashMap<String, Vec<Resource>>,
+) -> Vec<InputItem> {
+    let mut items: Vec<InputItem> = Vec::new();
+    for word in text.split_whitespace() {
+        let Some((server, uri)) = word
+            .strip_prefix('@')
+            .and_then(|mention| mention.split_once(':'))
+        else {
+            continue;
+        };
+        let known = resources
+            .get(server)
+            .is_some_and(|resources| resources.iter().any(|resource| resource.uri == uri));
+        let item = InputItem::McpResource {
+            server: server.to_string(),
+            uri: uri.to_string(),
+        };
+        if known && !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items
+}
@@ -60,4 +60,19 @@ pub struct Cli {
 
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
+
+    /// Session to resume instead of starting a new one (set by `codex resume`).
+    #[clap(skip)]
+    pub resume: Option<ResumeSelection>,
+}
+
+/// How `codex resume` chooses the session to continue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumeSelection {
+    /// Let the user choose from the recorded sessions.
+    Picker,
+    /// The most recent session.
+    Last,
+    /// The session with this id (or unique id prefix).
+    Id(String),
 }
@@ -9,15 +9,27 @@ use codex_ansi_escape::ansi_escape_line;
 use codex_common::create_config_summary_entries;
 use codex_common::elapsed::format_duration;
 use codex_core::config::Config;
+use codex_core::finding_tool::Finding;
+use codex_core::finding_tool::FindingSeverity;
+use codex_core::mcp_tool_pins::definition_diff;
 use codex_core::plan_tool::PlanItemArg;
 use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::project_doc::discover_project_doc_paths;
+use codex_core::protocol::ApprovalGrant;
+use codex_core::protocol::ApprovalScope;
+use codex_core::protocol::ContextCompactedEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FindingEvent;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::McpToolChange;
+use codex_core::protocol::RedactionCount;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::SecretRedactedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TurnUndoneEvent;
 use codex_login::get_auth_file;
 use codex_login::try_read_auth_json;
 use codex_protocol::parse_command::ParsedCommand;
@@ -655,6 +667,7 @@ pub(crate) fn new_completed_mcp_tool_call(
 pub(crate) fn new_status_output(
     config: &Config,
     usage: &TokenUsage,
+    session_cost_usd: Option<f64>,
     session_id: &Option<Uuid>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
@@ -693,7 +706,7 @@ pub(crate) fn new_status_output(
     // Sandbox (simplified name only)
     let sandbox_name = match &config.sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access",
-        SandboxPolicy::ReadOnly => "read-only",
+        SandboxPolicy::ReadOnly { .. } => "read-only",
         SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
     };
     lines.push(Line::from(vec![
@@ -842,6 +855,13 @@ pub(crate) fn new_status_output(
         "  • Total: ".into(),
         usage.blended_total().to_string().into(),
     ]));
+    // Cost: $<cost>, when the model's price is known
+    if let Some(cost) = session_cost_usd {
+        lines.push(Line::from(vec![
+            "  • Cost: ".into(),
+            format!("${cost:.2}").into(),
+        ]));
+    }
 
     PlainHistoryCell { lines }
 }
@@ -873,6 +893,9 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
 pub(crate) fn new_mcp_tools_output(
     config: &Config,
     tools: std::collections::HashMap<String, mcp_types::Tool>,
+    servers: &std::collections::HashMap<String, McpServerStatus>,
+    resources: &std::collections::HashMap<String, Vec<mcp_types::Resource>>,
+    prompts: &std::collections::HashMap<String, Vec<mcp_types::Prompt>>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = vec![
         Line::from("/mcp".magenta()),
@@ -881,7 +904,7 @@ pub(crate) fn new_mcp_tools_output(
         Line::from(""),
     ];
 
-    if tools.is_empty() {
+    if tools.is_empty() && servers.is_empty() {
         lines.push(Line::from("  • No MCP tools available.".italic()));
         lines.push(Line::from(""));
         return PlainHistoryCell { lines };
@@ -901,6 +924,15 @@ pub(crate) fn new_mcp_tools_output(
             server.clone().into(),
         ]));
 
+        if let Some(status) = servers.get(server) {
+            let mut spans = vec!["    • Status: ".into()];
+            spans.extend(mcp_server_status_spans(status));
+            lines.push(Line::from(spans));
+            if let McpServerStatus::Failed { stderr_tail, .. } = status {
+                lines.extend(stderr_tail_lines(stderr_tail));
+            }
+        }
+
         if !cfg.command.is_empty() {
             let cmd_display = format!("{} {}", cfg.command, cfg.args.join(" "));
 
@@ -910,6 +942,10 @@ pub(crate) fn new_mcp_tools_output(
             ]));
         }
 
+        if let Some(url) = cfg.url.as_ref() {
+            lines.push(Line::from(vec!["    • URL: ".into(), url.clone().into()]));
+        }
+
         if let Some(env) = cfg.env.as_ref()
             && !env.is_empty()
         {
@@ -929,12 +965,161 @@ pub(crate) fn new_mcp_tools_output(
                 names.join(", ").into(),
             ]));
         }
+
+        if let Some(resources) = resources.get(server)
+            && !resources.is_empty()
+        {
+            let mut mentions: Vec<String> = resources
+                .iter()
+                .map(|resource| format!("@{server}:{}", resource.uri))
+                .collect();
+            mentions.sort();
+            lines.push(Line::from(vec![
+                "    • Resources: ".into(),
+                mentions.join(", ").into(),
+            ]));
+        }
+
+        if let Some(prompts) = prompts.get(server)
+            && !prompts.is_empty()
+        {
+            let mut commands: Vec<String> = prompts
+                .iter()
+                .map(|prompt| format!("/{server}:{}", prompt.name))
+                .collect();
+            commands.sort();
+            lines.push(Line::from(vec![
+                "    • Prompts: ".into(),
+                commands.join(", ").into(),
+            ]));
+        }
         lines.push(Line::from(""));
     }
 
     PlainHistoryCell { lines }
 }
 
+fn mcp_server_status_spans(status: &McpServerStatus) -> Vec<Span<'static>> {
+    match status {
+        McpServerStatus::Running => vec!["running".green()],
+        McpServerStatus::Restarting { attempt } => {
+            vec![format!("restarting (attempt {attempt})").magenta()]
+        }
+        McpServerStatus::Failed { error, .. } => {
+            vec!["failed".red().bold(), format!(": {error}").into()]
+        }
+    }
+}
+
+fn stderr_tail_lines(stderr_tail: &[String]) -> impl Iterator<Item = Line<'static>> + '_ {
+    stderr_tail
+        .iter()
+        .map(|line| Line::from(vec!["      │ ".dim(), line.clone().dim()]))
+}
+
+/// Report a change to the health of an MCP server.
+pub(crate) fn new_mcp_server_status(server: &str, status: &McpServerStatus) -> PlainHistoryCell {
+    let mut spans: Vec<Span<'static>> = vec![
+        padded_emoji("🔌").into(),
+        " MCP server ".into(),
+        server.to_string().bold(),
+        ": ".into(),
+    ];
+    spans.extend(mcp_server_status_spans(status));
+    let mut lines: Vec<Line<'static>> = vec![Line::from(spans)];
+    if let McpServerStatus::Failed { stderr_tail, .. } = status {
+        lines.extend(stderr_tail_lines(stderr_tail));
+    }
+    lines.push("".into());
+    PlainHistoryCell { lines }
+}
+
+/// Render the tools of `server` whose definitions no longer match their pins,
+/// with a diff of the pinned and the current definition of each.
+pub(crate) fn new_mcp_tools_changed(server: &str, changes: &[McpToolChange]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![Line::from(vec![
+        padded_emoji("⚠️").into(),
+        " MCP server ".into(),
+        server.to_string().bold(),
+        " changed the definitions of these tools; they are disabled until you trust them".into(),
+    ])];
+    for change in changes {
+        lines.push(Line::from(vec![
+            "  • ".into(),
+            change.current.name.clone().bold(),
+        ]));
+        for line in definition_diff(change).lines() {
+            let line = format!("    {line}");
+            lines.push(
+                if line.starts_with("    +++") || line.starts_with("    ---") {
+                    line.dim().into()
+                } else if line.starts_with("    +") {
+                    line.green().into()
+                } else if line.starts_with("    -") {
+                    line.red().into()
+                } else if line.starts_with("    @@") {
+                    line.cyan().into()
+                } else {
+                    line.dim().into()
+                },
+            );
+        }
+    }
+    lines.push("".into());
+    PlainHistoryCell { lines }
+}
+
+/// Render the approvals granted for the rest of the session.
+pub(crate) fn new_approval_grants_output(grants: &[ApprovalGrant]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from("/approvals".magenta()),
+        Line::from(""),
+        Line::from(vec![padded_emoji("✅").into(), "Session approvals".bold()]),
+    ];
+
+    if grants.is_empty() {
+        lines.push(Line::from(
+            "  • No approvals granted in this session.".italic(),
+        ));
+    }
+    for grant in grants {
+        let mut spans: Vec<Span<'static>> = vec!["  • ".into()];
+        match &grant.scope {
+            ApprovalScope::Command { command } => {
+                spans.push(strip_bash_lc_and_escape(command).into());
+            }
+            ApprovalScope::Prefix { prefix } => {
+                spans.push(format!("{} …", strip_bash_lc_and_escape(prefix)).into());
+            }
+            ApprovalScope::Program { program, cwd } => {
+                let cwd = match relativize_to_home(cwd) {
+                    Some(rel) if !rel.as_os_str().is_empty() => {
+                        let sep = std::path::MAIN_SEPARATOR;
+                        format!("~{sep}{}", rel.display())
+                    }
+                    Some(_) => "~".to_string(),
+                    None => cwd.display().to_string(),
+                };
+                spans.push(format!("{program} …").into());
+                spans.push(format!(" in {cwd}").dim());
+            }
+        }
+        if let Some(secs) = grant.expires_in_secs {
+            spans.push(
+                format!(
+                    " (expires in {})",
+                    format_duration(Duration::from_secs(secs))
+                )
+                .dim(),
+            );
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // Use a hair space (U+200A) to create a subtle, near-invisible separation
     // before the text. VS16 is intentionally omitted to keep spacing tighter
@@ -959,6 +1144,191 @@ pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_exec_rejected(command: &[String], reason: String) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            "✗ ".red().bold(),
+            "Rejected ".bold(),
+            strip_bash_lc_and_escape(command).into(),
+        ]
+        .into(),
+        vec!["  └ ".dim(), reason.red()].into(),
+        "".into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_context_compacted(event: ContextCompactedEvent) -> PlainHistoryCell {
+    let ContextCompactedEvent {
+        tokens_before,
+        model_context_window,
+        kept_turns,
+    } = event;
+    let lines: Vec<Line<'static>> = vec![
+        "".into(),
+        vec![
+            padded_emoji("🗜").magenta().bold(),
+            " ".into(),
+            "Context compacted".bold(),
+            format!(
+                " ({tokens_before}/{model_context_window} tokens used; kept the last {kept_turns} turns)"
+            )
+            .dim(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_secret_redacted(event: SecretRedactedEvent) -> PlainHistoryCell {
+    let SecretRedactedEvent {
+        call_id,
+        redactions,
+    } = event;
+    let summary = redactions
+        .iter()
+        .map(|RedactionCount { kind, count }| format!("{count} {kind}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let source = if call_id.is_some() {
+        "tool output"
+    } else {
+        "your message"
+    };
+    let lines: Vec<Line<'static>> = vec![
+        "".into(),
+        vec![
+            padded_emoji("🔒").magenta().bold(),
+            " ".into(),
+            "Secrets redacted".bold(),
+            format!(" from {source} before sending to the model ({summary})").dim(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_turn_undone(event: TurnUndoneEvent) -> PlainHistoryCell {
+    let TurnUndoneEvent {
+        turns,
+        restored_paths,
+    } = event;
+    let turns_label = if turns == 1 {
+        "1 turn".to_string()
+    } else {
+        format!("{turns} turns")
+    };
+    let mut lines: Vec<Line<'static>> = vec![
+        "".into(),
+        vec![
+            padded_emoji("↩").magenta().bold(),
+            " ".into(),
+            format!("Undid {turns_label}").bold(),
+            format!(" (restored {} file(s))", restored_paths.len()).dim(),
+        ]
+        .into(),
+    ];
+    for path in restored_paths {
+        lines.push(Line::from(format!("  {}", path.display()).dim()));
+    }
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_finding(event: &FindingEvent) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec!["".into()];
+    lines.extend(finding_lines(event.number, &event.finding));
+    PlainHistoryCell { lines }
+}
+
+/// Lines for the findings panel opened by `/findings`: a count per severity
+/// followed by every finding, most severe first.
+pub(crate) fn findings_panel_lines(findings: &[FindingEvent]) -> Vec<Line<'static>> {
+    if findings.is_empty() {
+        return vec![
+            "No findings have been reported in this session."
+                .italic()
+                .into(),
+        ];
+    }
+    let mut sorted: Vec<&FindingEvent> = findings.iter().collect();
+    sorted.sort_by(|a, b| {
+        b.finding
+            .severity
+            .cmp(&a.finding.severity)
+            .then(a.number.cmp(&b.number))
+    });
+
+    let mut summary: Vec<Span<'static>> = vec![format!("{} finding(s)", findings.len()).bold()];
+    for severity in [
+        FindingSeverity::Critical,
+        FindingSeverity::High,
+        FindingSeverity::Medium,
+        FindingSeverity::Low,
+        FindingSeverity::Info,
+    ] {
+        let count = findings
+            .iter()
+            .filter(|f| f.finding.severity == severity)
+            .count();
+        if count > 0 {
+            summary.push("  ".into());
+            summary.push(severity_span(severity));
+            summary.push(format!(" {count}").into());
+        }
+    }
+
+    let mut lines: Vec<Line<'static>> = vec![summary.into()];
+    for event in sorted {
+        lines.push("".into());
+        lines.extend(finding_lines(event.number, &event.finding));
+    }
+    lines
+}
+
+fn finding_lines(number: usize, finding: &Finding) -> Vec<Line<'static>> {
+    let location = match (finding.start_line, finding.end_line) {
+        (Some(start), Some(end)) if end != start => format!("{}:{start}-{end}", finding.path),
+        (Some(start), _) => format!("{}:{start}", finding.path),
+        _ => finding.path.clone(),
+    };
+    let mut header: Vec<Span<'static>> = vec![
+        format!("#{number} ").dim(),
+        severity_span(finding.severity),
+        " ".into(),
+        finding.rule_id.clone().bold(),
+    ];
+    if let Some(cwe) = &finding.cwe {
+        header.push(format!(" ({cwe})").dim());
+    }
+    header.push(format!(" {location}").cyan());
+
+    let mut lines: Vec<Line<'static>> = vec![header.into()];
+    for line in finding.description.lines() {
+        lines.push(Line::from(format!("  {line}")));
+    }
+    if let Some(remediation) = &finding.remediation {
+        let mut remediation_lines = remediation.lines();
+        if let Some(first) = remediation_lines.next() {
+            lines.push(vec!["  fix: ".dim(), first.to_string().into()].into());
+        }
+        for line in remediation_lines {
+            lines.push(Line::from(format!("       {line}")));
+        }
+    }
+    lines
+}
+
+fn severity_span(severity: FindingSeverity) -> Span<'static> {
+    let label = severity.to_string().to_uppercase();
+    match severity {
+        FindingSeverity::Critical => label.red().bold(),
+        FindingSeverity::High => label.red(),
+        FindingSeverity::Medium => label.yellow(),
+        FindingSeverity::Low => label.cyan(),
+        FindingSeverity::Info => label.dim(),
+    }
+}
+
 /// Render a user‑friendly plan update styled like a checkbox todo list.
 pub(crate) fn new_plan_update(update: UpdatePlanArgs) -> PlainHistoryCell {
     let UpdatePlanArgs { explanation, plan } = update;
@@ -1234,7 +1604,7 @@ fn output_lines(
     out
 }
 
-fn format_mcp_invocation<'a>(invocation: McpInvocation) -> Line<'a> {
+pub(crate) fn format_mcp_invocation<'a>(invocation: McpInvocation) -> Line<'a> {
     let args_str = invocation
         .arguments
         .as_ref()
@@ -48,6 +48,7 @@ mod markdown_stream;
 pub mod onboarding;
 mod pager_overlay;
 mod render;
+mod resume_picker;
 mod session_log;
 mod shimmer;
 mod slash_command;
@@ -66,6 +67,7 @@ mod chatwidget_stream_tests;
 mod updates;
 
 pub use cli::Cli;
+pub use cli::ResumeSelection;
 
 use crate::onboarding::TrustDirectorySelection;
 use crate::onboarding::onboarding_screen::OnboardingScreenArgs;
@@ -175,6 +177,17 @@ pub async fn run_main(
         }
     };
 
+    #[allow(clippy::print_stderr)]
+    if let Some(selection) = &cli.resume {
+        match resolve_resume_path(&config.codex_home, selection) {
+            Ok(path) => config.experimental_resume = path,
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     let should_show_trust_screen = determine_repo_trust_state(
         &mut config,
         &config_toml,
@@ -301,7 +314,12 @@ async fn run_ratatui_app(
     // Initialize high-fidelity session event logging if enabled.
     session_log::maybe_init(&config);
 
-    let Cli { prompt, images, .. } = cli;
+    let Cli {
+        prompt,
+        images,
+        resume: cli_resume,
+        ..
+    } = cli;
 
     let auth_manager = AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
     let login_status = get_login_status(&config);
@@ -327,6 +345,11 @@ async fn run_ratatui_app(
         }
     }
 
+    if cli_resume == Some(ResumeSelection::Picker) {
+        config.experimental_resume =
+            resume_picker::run_resume_picker(&mut tui, &config.codex_home).await?;
+    }
+
     let app_result = App::run(&mut tui, auth_manager, config, prompt, images).await;
 
     restore();
@@ -336,6 +359,27 @@ async fn run_ratatui_app(
     app_result
 }
 
+/// Resolves `--last` or a session id to a rollout path. The picker is shown
+/// later, once the terminal has been initialized, so it resolves to `None`
+/// here.
+fn resolve_resume_path(
+    codex_home: &std::path::Path,
+    selection: &ResumeSelection,
+) -> std::io::Result<Option<PathBuf>> {
+    let session = match selection {
+        ResumeSelection::Picker => return Ok(None),
+        ResumeSelection::Last => codex_core::list_sessions(codex_home)?.into_iter().next(),
+        ResumeSelection::Id(id) => codex_core::find_session(codex_home, id)?,
+    };
+    match session {
+        Some(session) => Ok(Some(session.path)),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "No session found to resume. Run `codex sessions list` to see recorded sessions.",
+        )),
+    }
+}
+
 #[expect(
     clippy::print_stderr,
     reason = "TUI should no longer be displayed, so we can write to stderr."
@@ -0,0 +1,191 @@
+//! Full-screen picker shown by `codex resume` when no session id is given.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::SessionSummary;
+use codex_core::list_sessions;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+use crate::tui::Tui;
+use crate::tui::TuiEvent;
+
+/// Number of lines used by the header and footer around the session list.
+const CHROME_LINES: u16 = 4;
+
+/// Lets the user choose a recorded session. Returns the rollout path of the
+/// chosen session, or `None` to start a new session instead.
+pub(crate) async fn run_resume_picker(
+    tui: &mut Tui,
+    codex_home: &Path,
+) -> color_eyre::Result<Option<PathBuf>> {
+    use tokio_stream::StreamExt;
+
+    let sessions = list_sessions(codex_home)?;
+    if sessions.is_empty() {
+        return Ok(None);
+    }
+    let mut picker = ResumePicker::new(sessions);
+
+    tui.draw(u16::MAX, |frame| {
+        frame.render_widget_ref(&picker, frame.area());
+    })?;
+
+    let frame_requester = tui.frame_requester();
+    let tui_events = tui.event_stream();
+    tokio::pin!(tui_events);
+
+    while let Some(event) = tui_events.next().await {
+        match event {
+            TuiEvent::Key(key_event) => {
+                if let Some(choice) = picker.handle_key_event(key_event) {
+                    return Ok(choice);
+                }
+                frame_requester.schedule_frame();
+            }
+            TuiEvent::Draw => {
+                let _ = tui.draw(u16::MAX, |frame| {
+                    frame.render_widget_ref(&picker, frame.area());
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+struct ResumePicker {
+    sessions: Vec<SessionSummary>,
+    selected: usize,
+}
+
+impl ResumePicker {
+    fn new(sessions: Vec<SessionSummary>) -> Self {
+        Self {
+            sessions,
+            selected: 0,
+        }
+    }
+
+    /// Returns `Some` once the user has made a choice: the selected rollout
+    /// path, or `None` to start a new session.
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<Option<PathBuf>> {
+        if key_event.kind == KeyEventKind::Release {
+            return None;
+        }
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.sessions.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                return Some(self.sessions.get(self.selected).map(|s| s.path.clone()));
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return Some(None),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl WidgetRef for &ResumePicker {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = vec![
+            Line::from("Resume a previous session".bold()),
+            Line::from(""),
+        ];
+
+        // Keep the selected row visible when there are more sessions than
+        // rows on screen.
+        let visible = usize::from(area.height.saturating_sub(CHROME_LINES)).max(1);
+        let first = self.selected.saturating_sub(visible - 1);
+        for (idx, session) in self.sessions.iter().enumerate().skip(first).take(visible) {
+            let label = session_label(session);
+            if idx == self.selected {
+                lines.push(Line::from(format!("> {label}")).cyan());
+            } else {
+                lines.push(Line::from(format!("  {label}")));
+            }
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(
+            "Press Enter to resume, Esc to start a new session".dim(),
+        ));
+
+        Paragraph::new(lines).render_ref(area, buf);
+    }
+}
+
+fn session_label(session: &SessionSummary) -> String {
+    let timestamp = session.timestamp.get(..16).unwrap_or(&session.timestamp);
+    let mut label = timestamp.replace('T', " ");
+    if let Some(branch) = &session.git_branch {
+        label.push_str(&format!("  [{branch}]"));
+    }
+    if let Some(cwd) = &session.cwd {
+        label.push_str(&format!("  {}", cwd.display()));
+    }
+    if let Some(message) = &session.first_user_message {
+        let first_line = message.lines().next().unwrap_or_default();
+        label.push_str(&format!("  {first_line}"));
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use uuid::Uuid;
+
+    fn session(name: &str) -> SessionSummary {
+        SessionSummary {
+            id: Uuid::new_v4(),
+            path: PathBuf::from(format!("/sessions/{name}.jsonl")),
+            timestamp: "2025-01-02T10:00:00.000Z".to_string(),
+            cwd: Some(PathBuf::from("/repo")),
+            git_branch: Some("main".to_string()),
+            first_user_message: Some(format!("{name}\nmore")),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn enter_returns_highlighted_session() {
+        let mut picker = ResumePicker::new(vec![session("a"), session("b")]);
+        assert_eq!(picker.handle_key_event(key(KeyCode::Down)), None);
+        assert_eq!(picker.handle_key_event(key(KeyCode::Down)), None);
+        assert_eq!(
+            picker.handle_key_event(key(KeyCode::Enter)),
+            Some(Some(PathBuf::from("/sessions/b.jsonl")))
+        );
+    }
+
+    #[test]
+    fn escape_starts_new_session() {
+        let mut picker = ResumePicker::new(vec![session("a")]);
+        assert_eq!(picker.handle_key_event(key(KeyCode::Esc)), Some(None));
+    }
+
+    #[test]
+    fn label_shows_date_branch_cwd_and_first_line() {
+        assert_eq!(
+            session_label(&session("audit")),
+            "2025-01-02 10:00  [main]  /repo  audit"
+        );
+    }
+}
@@ -38,6 +38,8 @@ use ratatui::crossterm::terminal::enable_raw_mode;
 use ratatui::layout::Offset;
 use ratatui::text::Line;
 
+use codex_core::config_types::NotificationMethod;
+
 use crate::clipboard_paste::paste_image_to_temp_png;
 use crate::custom_terminal;
 use crate::custom_terminal::Terminal as CustomTerminal;
@@ -89,6 +91,31 @@ impl Command for EnableAlternateScroll {
     }
 }
 
+/// Desktop notification through the OSC 9 escape sequence, which terminals
+/// such as iTerm2, WezTerm, Ghostty and kitty turn into a system notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PostNotification(String);
+
+impl Command for PostNotification {
+    fn write_ansi(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        // Control characters would end the sequence early.
+        let message: String = self.0.chars().filter(|c| !c.is_control()).collect();
+        write!(f, "\x1b]9;{message}\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Err(std::io::Error::other(
+            "tried to execute PostNotification using WinAPI; use ANSI instead",
+        ))
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct DisableAlternateScroll;
 
@@ -476,6 +503,22 @@ impl Tui {
         Ok(())
     }
 
+    /// Raise a terminal notification so the user notices Codex while the
+    /// terminal is in the background.
+    pub fn notify(&mut self, message: String, method: NotificationMethod) -> Result<()> {
+        match method {
+            NotificationMethod::Osc9 => {
+                execute!(self.terminal.backend_mut(), PostNotification(message))
+            }
+            NotificationMethod::Bell => {
+                use std::io::Write;
+                let backend = self.terminal.backend_mut();
+                backend.write_all(b"\x07")?;
+                backend.flush()
+            }
+        }
+    }
+
     pub fn insert_history_lines(&mut self, lines: Vec<Line<'static>>) {
         self.pending_history_lines.extend(lines);
         self.frame_requester().schedule_frame();
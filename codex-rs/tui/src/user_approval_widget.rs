@@ -7,8 +7,11 @@
 //! driven workflow – a fully‑fledged visual match is not required.
 
 use std::path::PathBuf;
-use std::sync::LazyLock;
 
+use codex_core::approval_grants::TEMPORARY_GRANT_DURATION;
+use codex_core::approval_grants::command_prefix;
+use codex_core::approval_grants::command_program;
+use codex_core::protocol::McpInvocation;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
@@ -29,6 +32,7 @@ use ratatui::widgets::Wrap;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::exec_command::strip_bash_lc_and_escape;
+use crate::history_cell::format_mcp_invocation;
 
 /// Request coming from the agent that needs user approval.
 pub(crate) enum ApprovalRequest {
@@ -42,6 +46,13 @@ pub(crate) enum ApprovalRequest {
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     },
+    McpTool {
+        id: String,
+        invocation: McpInvocation,
+        reason: Option<String>,
+    },
+    /// Trust the changed definitions of `tools` of an MCP server.
+    McpToolChanges { server: String, tools: Vec<String> },
 }
 
 /// Options displayed in the *select* mode.
@@ -49,57 +60,129 @@ pub(crate) enum ApprovalRequest {
 /// The `key` is matched case-insensitively.
 struct SelectOption {
     label: Line<'static>,
-    description: &'static str,
+    description: String,
     key: KeyCode,
     decision: ReviewDecision,
 }
 
-static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
-    vec![
+/// Options for running `command`. Approving by prefix or by program is only
+/// offered for a single simple command.
+fn command_select_options(command: &[String]) -> Vec<SelectOption> {
+    let prefix = command_prefix(command).map(|prefix| strip_bash_lc_and_escape(&prefix));
+    let minutes = TEMPORARY_GRANT_DURATION.as_secs() / 60;
+
+    let mut options = vec![
         SelectOption {
             label: Line::from(vec!["Y".underlined(), "es".into()]),
-            description: "Approve and run the command",
+            description: "Approve and run the command".to_string(),
             key: KeyCode::Char('y'),
             decision: ReviewDecision::Approved,
         },
         SelectOption {
             label: Line::from(vec!["A".underlined(), "lways".into()]),
-            description: "Approve the command for the remainder of this session",
+            description: "Approve the command for the remainder of this session".to_string(),
             key: KeyCode::Char('a'),
             decision: ReviewDecision::ApprovedForSession,
         },
+    ];
+    if let Some(prefix) = &prefix {
+        options.push(SelectOption {
+            label: Line::from(vec!["P".underlined(), "refix".into()]),
+            description: format!(
+                "Approve every command starting with `{prefix}` for the remainder of this session"
+            ),
+            key: KeyCode::Char('p'),
+            decision: ReviewDecision::ApprovedPrefixForSession,
+        });
+    }
+    if let Some(program) = command_program(command) {
+        options.push(SelectOption {
+            label: Line::from(vec!["D".underlined(), "irectory".into()]),
+            description: format!(
+                "Approve every `{program}` command in this directory for the remainder of this session"
+            ),
+            key: KeyCode::Char('d'),
+            decision: ReviewDecision::ApprovedProgramInDirectory,
+        });
+    }
+    options.push(SelectOption {
+        label: Line::from(vec!["F".underlined(), format!("or {minutes} min").into()]),
+        description: format!("Approve the command for {minutes} minutes"),
+        key: KeyCode::Char('f'),
+        decision: ReviewDecision::ApprovedTemporarily,
+    });
+    options.push(SelectOption {
+        label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
+        description: "Do not run the command; provide feedback".to_string(),
+        key: KeyCode::Char('n'),
+        decision: ReviewDecision::Abort,
+    });
+    options
+}
+
+fn patch_select_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Approve and apply the changes".to_string(),
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
-            description: "Do not run the command; provide feedback",
+            description: "Do not apply the changes; provide feedback".to_string(),
             key: KeyCode::Char('n'),
             decision: ReviewDecision::Abort,
         },
     ]
-});
+}
 
-static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+fn mcp_tool_select_options() -> Vec<SelectOption> {
     vec![
         SelectOption {
             label: Line::from(vec!["Y".underlined(), "es".into()]),
-            description: "Approve and apply the changes",
+            description: "Approve and call the tool".to_string(),
             key: KeyCode::Char('y'),
             decision: ReviewDecision::Approved,
         },
+        SelectOption {
+            label: Line::from(vec!["A".underlined(), "lways".into()]),
+            description: "Approve calls to this tool for the remainder of this session".to_string(),
+            key: KeyCode::Char('a'),
+            decision: ReviewDecision::ApprovedForSession,
+        },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o, provide feedback".into()]),
-            description: "Do not apply the changes; provide feedback",
+            description: "Do not call the tool; provide feedback".to_string(),
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Abort,
+        },
+    ]
+}
+
+fn mcp_tool_changes_select_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Trust the new definitions and enable the tools".to_string(),
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o".into()]),
+            description: "Keep the tools disabled".to_string(),
             key: KeyCode::Char('n'),
             decision: ReviewDecision::Abort,
         },
     ]
-});
+}
 
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget {
     approval_request: ApprovalRequest,
     app_event_tx: AppEventSender,
     confirmation_prompt: Paragraph<'static>,
-    select_options: &'static Vec<SelectOption>,
+    select_options: Vec<SelectOption>,
 
     /// Currently selected index in *select* mode.
     selected_option: usize,
@@ -177,12 +260,39 @@ impl UserApprovalWidget {
 
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
+            ApprovalRequest::McpTool {
+                invocation, reason, ..
+            } => {
+                let mut spans = vec!["? ".fg(Color::Cyan), "Codex wants to call ".bold()];
+                spans.extend(format_mcp_invocation(invocation.clone()).spans);
+                let mut contents: Vec<Line> = vec![Line::from(spans), Line::from("")];
+                if let Some(reason) = reason {
+                    contents.push(Line::from(reason.clone().italic()));
+                    contents.push(Line::from(""));
+                }
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
+            ApprovalRequest::McpToolChanges { server, tools } => {
+                let contents: Vec<Line> = vec![
+                    Line::from(vec![
+                        "? ".fg(Color::Cyan),
+                        "MCP server ".bold(),
+                        server.clone().bold(),
+                        " changed the definitions of ".bold(),
+                        tools.join(", ").dim(),
+                    ]),
+                    Line::from(""),
+                ];
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
         };
 
         Self {
             select_options: match &approval_request {
-                ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
-                ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
+                ApprovalRequest::Exec { command, .. } => command_select_options(command),
+                ApprovalRequest::ApplyPatch { .. } => patch_select_options(),
+                ApprovalRequest::McpTool { .. } => mcp_tool_select_options(),
+                ApprovalRequest::McpToolChanges { .. } => mcp_tool_changes_select_options(),
             },
             approval_request,
             app_event_tx,
@@ -291,6 +401,21 @@ impl UserApprovalWidget {
                             vec![" every time this session".bold()],
                         ));
                     }
+                    ReviewDecision::ApprovedPrefixForSession
+                    | ReviewDecision::ApprovedProgramInDirectory
+                    | ReviewDecision::ApprovedTemporarily => {
+                        let scope = approval_scope_summary(decision, command);
+                        lines.extend(to_command_display(
+                            vec![
+                                "✔ ".fg(Color::Green),
+                                "You ".into(),
+                                "approved".bold(),
+                                " codex to run ".into(),
+                            ],
+                            cmd,
+                            vec![" and ".into(), scope.bold()],
+                        ));
+                    }
                     ReviewDecision::Denied => {
                         lines.extend(to_command_display(
                             vec![
@@ -320,6 +445,47 @@ impl UserApprovalWidget {
             ApprovalRequest::ApplyPatch { .. } => {
                 lines.push(Line::from(format!("patch approval decision: {decision:?}")));
             }
+            ApprovalRequest::McpTool { invocation, .. } => {
+                let (prefix, verb, suffix) = match decision {
+                    ReviewDecision::Approved => ("✔ ".fg(Color::Green), "approved", " this time"),
+                    ReviewDecision::ApprovedForSession
+                    | ReviewDecision::ApprovedPrefixForSession
+                    | ReviewDecision::ApprovedProgramInDirectory
+                    | ReviewDecision::ApprovedTemporarily => (
+                        "✔ ".fg(Color::Green),
+                        "approved",
+                        " every time this session",
+                    ),
+                    ReviewDecision::Denied => ("✗ ".fg(Color::Red), "did not approve", ""),
+                    ReviewDecision::Abort => ("✗ ".fg(Color::Red), "canceled", ""),
+                };
+                let mut spans = vec![prefix, "You ".into(), verb.bold(), " codex to call ".into()];
+                spans.extend(format_mcp_invocation(invocation.clone()).spans);
+                if !suffix.is_empty() {
+                    spans.push(suffix.bold());
+                }
+                lines.push(Line::from(spans));
+            }
+            ApprovalRequest::McpToolChanges { server, .. } => {
+                lines.push(if decision == ReviewDecision::Approved {
+                    Line::from(vec![
+                        "✔ ".fg(Color::Green),
+                        "You ".into(),
+                        "trusted".bold(),
+                        " the new tool definitions of ".into(),
+                        server.clone().bold(),
+                    ])
+                } else {
+                    Line::from(vec![
+                        "✗ ".fg(Color::Red),
+                        "You ".into(),
+                        "kept".bold(),
+                        " the changed tools of ".into(),
+                        server.clone().bold(),
+                        " disabled".into(),
+                    ])
+                });
+            }
         }
         if !feedback.trim().is_empty() {
             lines.push(Line::from("feedback:"));
@@ -330,17 +496,30 @@ impl UserApprovalWidget {
         self.app_event_tx.send(AppEvent::InsertHistoryLines(lines));
 
         let op = match &self.approval_request {
-            ApprovalRequest::Exec { id, .. } => Op::ExecApproval {
+            ApprovalRequest::Exec { id, .. } => Some(Op::ExecApproval {
                 id: id.clone(),
                 decision,
-            },
-            ApprovalRequest::ApplyPatch { id, .. } => Op::PatchApproval {
+            }),
+            ApprovalRequest::ApplyPatch { id, .. } => Some(Op::PatchApproval {
                 id: id.clone(),
                 decision,
-            },
+            }),
+            ApprovalRequest::McpTool { id, .. } => Some(Op::McpToolApproval {
+                id: id.clone(),
+                decision,
+            }),
+            // Declining leaves the tools disabled; there is nothing to tell
+            // the agent.
+            ApprovalRequest::McpToolChanges { server, .. } => {
+                (decision == ReviewDecision::Approved).then(|| Op::TrustMcpTools {
+                    server: server.clone(),
+                })
+            }
         };
 
-        self.app_event_tx.send(AppEvent::CodexOp(op));
+        if let Some(op) = op {
+            self.app_event_tx.send(AppEvent::CodexOp(op));
+        }
         self.done = true;
     }
 
@@ -359,6 +538,25 @@ impl UserApprovalWidget {
     }
 }
 
+/// Describes what a grant made by `decision` for `command` covers, e.g.
+/// "every `cargo test` command this session".
+fn approval_scope_summary(decision: ReviewDecision, command: &[String]) -> String {
+    let prefix = command_prefix(command).map(|prefix| strip_bash_lc_and_escape(&prefix));
+    let minutes = TEMPORARY_GRANT_DURATION.as_secs() / 60;
+    match (decision, prefix, command_program(command)) {
+        (ReviewDecision::ApprovedPrefixForSession, Some(prefix), _) => {
+            format!("every `{prefix}` command this session")
+        }
+        (ReviewDecision::ApprovedProgramInDirectory, _, Some(program)) => {
+            format!("every `{program}` command in this directory this session")
+        }
+        (ReviewDecision::ApprovedTemporarily, _, _) => {
+            format!("the same command for {minutes} minutes")
+        }
+        _ => "every time this session".to_string(),
+    }
+}
+
 impl WidgetRef for &UserApprovalWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         let prompt_height = self.get_confirmation_prompt_height(area.width);
@@ -390,6 +588,8 @@ impl WidgetRef for &UserApprovalWidget {
         let title = match &self.approval_request {
             ApprovalRequest::Exec { .. } => "Allow command?",
             ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
+            ApprovalRequest::McpTool { .. } => "Allow tool call?",
+            ApprovalRequest::McpToolChanges { .. } => "Trust changed tools?",
         };
         Line::from(title).render(title_area, buf);
 
@@ -406,9 +606,13 @@ impl WidgetRef for &UserApprovalWidget {
             line.render(*area, buf);
         }
 
-        Line::from(self.select_options[self.selected_option].description)
-            .style(Style::new().italic().add_modifier(Modifier::DIM))
-            .render(description_area.inner(Margin::new(1, 0)), buf);
+        Line::from(
+            self.select_options[self.selected_option]
+                .description
+                .as_str(),
+        )
+        .style(Style::new().italic().add_modifier(Modifier::DIM))
+        .render(description_area.inner(Margin::new(1, 0)), buf);
 
         Block::bordered()
             .border_type(BorderType::QuadrantOutside)
@@ -478,4 +682,55 @@ mod tests {
             })
         )));
     }
+
+    #[test]
+    fn prefix_and_directory_are_offered_for_simple_commands() {
+        let decisions = |command: &[&str]| -> Vec<ReviewDecision> {
+            let command: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+            command_select_options(&command)
+                .iter()
+                .map(|opt| opt.decision)
+                .collect()
+        };
+        assert_eq!(
+            decisions(&["cargo", "test", "-p", "codex-core"]),
+            vec![
+                ReviewDecision::Approved,
+                ReviewDecision::ApprovedForSession,
+                ReviewDecision::ApprovedPrefixForSession,
+                ReviewDecision::ApprovedProgramInDirectory,
+                ReviewDecision::ApprovedTemporarily,
+                ReviewDecision::Abort,
+            ]
+        );
+        assert_eq!(
+            decisions(&["bash", "-lc", "make && make install"]),
+            vec![
+                ReviewDecision::Approved,
+                ReviewDecision::ApprovedForSession,
+                ReviewDecision::ApprovedTemporarily,
+                ReviewDecision::Abort,
+            ]
+        );
+
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let req = ApprovalRequest::Exec {
+            id: "3".to_string(),
+            command: vec!["cargo".to_string(), "test".to_string()],
+            reason: None,
+        };
+        let mut widget = UserApprovalWidget::new(req, AppEventSender::new(tx_raw));
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+        let mut events: Vec<AppEvent> = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            events.push(ev);
+        }
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::ApprovedPrefixForSession,
+                ..
+            })
+        )));
+    }
 }